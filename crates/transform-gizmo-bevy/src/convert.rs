@@ -0,0 +1,109 @@
+//! Conversions between Bevy types and the types used by [`transform_gizmo`].
+//!
+//! These are used by [`crate::TransformGizmoPlugin`], but can also be used for driving
+//! a [`transform_gizmo::Gizmo`] manually, e.g. from a custom system.
+
+use bevy_math::{DQuat, DVec3};
+use bevy_render::camera::Camera;
+use bevy_transform::components::{GlobalTransform, Transform};
+use transform_gizmo::{math, mint};
+
+/// Converts a Bevy transform into a gizmo target transform.
+pub fn to_gizmo_transform(transform: &Transform) -> math::Transform {
+    math::Transform {
+        translation: transform.translation.as_dvec3().into(),
+        rotation: transform.rotation.as_dquat().into(),
+        scale: transform.scale.as_dvec3().into(),
+    }
+}
+
+/// Converts a gizmo target transform back into a Bevy transform.
+pub fn from_gizmo_transform(transform: &math::Transform) -> Transform {
+    Transform {
+        translation: DVec3::from(transform.translation).as_vec3(),
+        rotation: DQuat::from(transform.rotation).as_quat(),
+        scale: DVec3::from(transform.scale).as_vec3(),
+    }
+}
+
+/// View and projection matrices of a camera, as used in [`transform_gizmo::GizmoConfig`].
+pub fn camera_matrices(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> (mint::RowMatrix4<f64>, mint::RowMatrix4<f64>) {
+    let view_matrix = camera_transform.compute_matrix().inverse();
+    let projection_matrix = camera.clip_from_view();
+
+    (
+        view_matrix.as_dmat4().into(),
+        projection_matrix.as_dmat4().into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{DMat4, Quat, Vec3};
+    use transform_gizmo::math::Pos2;
+    use transform_gizmo::{Gizmo, GizmoConfig, GizmoInteraction, GizmoMode};
+
+    use super::*;
+
+    #[test]
+    fn transforms_round_trip() {
+        let transform = Transform {
+            translation: Vec3::new(1.0, 2.0, 3.0),
+            rotation: Quat::from_rotation_y(0.5),
+            scale: Vec3::new(1.0, 2.0, 0.5),
+        };
+
+        assert_eq!(
+            from_gizmo_transform(&to_gizmo_transform(&transform)),
+            transform
+        );
+    }
+
+    #[test]
+    fn camera_drives_a_gizmo_and_back() {
+        let camera_transform = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        );
+        let (view_matrix, _) = camera_matrices(&Camera::default(), &camera_transform);
+        let expected = DMat4::look_at_rh(DVec3::Z * 10.0, DVec3::ZERO, DVec3::Y);
+        assert!(DMat4::from(view_matrix).abs_diff_eq(expected, 1e-5));
+
+        let viewport = math::Rect::from_min_size(Pos2::ZERO, (800.0, 600.0).into());
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix,
+            projection_matrix: DMat4::perspective_rh(
+                45f64.to_radians(),
+                800.0 / 600.0,
+                0.1,
+                1000.0,
+            )
+            .into(),
+            viewport,
+            modes: GizmoMode::TranslateX.into(),
+            ..Default::default()
+        });
+
+        let target = Transform::from_scale(Vec3::splat(2.0));
+        let targets = [to_gizmo_transform(&target)];
+        let grab = Pos2::new(450.0, 300.0);
+        for (pos, dragging) in [(grab, true), (grab + math::Vec2::X * 40.0, true)] {
+            let interaction = GizmoInteraction {
+                cursor_pos: (pos.x, pos.y),
+                drag_started: pos == grab,
+                dragging,
+                ..Default::default()
+            };
+            let (_, new_targets) = gizmo.update(interaction, &targets).unwrap();
+            let moved = from_gizmo_transform(&new_targets[0]);
+            assert_eq!(moved.scale, target.scale);
+            assert_eq!(moved.rotation, target.rotation);
+            assert!(moved.translation.y.abs() < 1e-5 && moved.translation.z.abs() < 1e-5);
+            if pos != grab {
+                assert!(moved.translation.x > 0.3, "{}", moved.translation);
+            }
+        }
+    }
+}
@@ -32,7 +32,7 @@ use bevy_app::prelude::*;
 use bevy_asset::{AssetApp, Assets};
 use bevy_ecs::prelude::*;
 use bevy_input::prelude::*;
-use bevy_math::{DQuat, DVec3, Vec2};
+use bevy_math::Vec2;
 use bevy_render::prelude::*;
 use bevy_transform::prelude::*;
 use bevy_utils::HashMap;
@@ -49,6 +49,7 @@ pub use transform_gizmo::{
     GizmoConfig, *,
 };
 
+pub mod convert;
 pub mod prelude;
 
 mod render;
@@ -91,6 +92,16 @@ pub struct GizmoOptions {
     /// When snapping is enabled, snap twice as often.
     /// This may be overwritten with hotkeys ([`GizmoHotkeys::enable_accurate_mode`]).
     pub accurate_mode: bool,
+    /// Multiplier for the rotation angle computed from the pointer movement.
+    pub rotation_sensitivity: f64,
+    /// When enabled, rotations use [`GizmoOptions::fine_rotation_sensitivity`] instead.
+    /// This may be overwritten with hotkeys ([`GizmoHotkeys::enable_fine_rotation`]).
+    pub fine_rotation: bool,
+    /// Multiplier for the rotation angle when fine rotation is enabled.
+    pub fine_rotation_sensitivity: f64,
+    /// When enabled, axis and plane scale handles scale all axes uniformly.
+    /// This may be overwritten with hotkeys ([`GizmoHotkeys::enable_uniform_scale`]).
+    pub uniform_scale: bool,
     /// Angle increment for snapping rotations, in radians.
     pub snap_angle: f32,
     /// Distance increment for snapping translations.
@@ -121,6 +132,10 @@ impl Default for GizmoOptions {
             visuals: Default::default(),
             snapping: false,
             accurate_mode: false,
+            rotation_sensitivity: 1.0,
+            fine_rotation: false,
+            fine_rotation_sensitivity: 0.1,
+            uniform_scale: false,
             snap_angle: DEFAULT_SNAP_ANGLE,
             snap_distance: DEFAULT_SNAP_DISTANCE,
             snap_scale: DEFAULT_SNAP_SCALE,
@@ -140,6 +155,10 @@ pub struct GizmoHotkeys {
     pub enable_snapping: Option<KeyCode>,
     /// When pressed, snapping is twice as accurate.
     pub enable_accurate_mode: Option<KeyCode>,
+    /// When pressed, rotations are slower for finer adjustments.
+    pub enable_fine_rotation: Option<KeyCode>,
+    /// When pressed, axis and plane scale handles scale all axes uniformly.
+    pub enable_uniform_scale: Option<KeyCode>,
     /// Toggles gizmo to rotate-only mode.
     pub toggle_rotate: Option<KeyCode>,
     /// Toggles gizmo to translate-only mode.
@@ -165,6 +184,8 @@ impl Default for GizmoHotkeys {
         Self {
             enable_snapping: Some(KeyCode::ControlLeft),
             enable_accurate_mode: Some(KeyCode::ShiftLeft),
+            enable_fine_rotation: Some(KeyCode::AltLeft),
+            enable_uniform_scale: Some(KeyCode::ShiftRight),
             toggle_rotate: Some(KeyCode::KeyR),
             toggle_translate: Some(KeyCode::KeyG),
             toggle_scale: Some(KeyCode::KeyS),
@@ -247,6 +268,14 @@ fn handle_hotkeys(
         gizmo_options.accurate_mode = keyboard_input.pressed(accurate_mode_key);
     }
 
+    if let Some(fine_rotation_key) = hotkeys.enable_fine_rotation {
+        gizmo_options.fine_rotation = keyboard_input.pressed(fine_rotation_key);
+    }
+
+    if let Some(uniform_scale_key) = hotkeys.enable_uniform_scale {
+        gizmo_options.uniform_scale = keyboard_input.pressed(uniform_scale_key);
+    }
+
     // Modifier for inverting the mode axis selection.
     // For example, X would force X axis, but Shift-X would force Y and Z axes.
     let invert_modifier = keyboard_input.pressed(KeyCode::ShiftLeft);
@@ -422,9 +451,7 @@ fn update_gizmos(
         Pos2::new(viewport.max.x, viewport.max.y),
     );
 
-    let projection_matrix = camera.clip_from_view();
-
-    let view_matrix = camera_transform.compute_matrix().inverse();
+    let (view_matrix, projection_matrix) = convert::camera_matrices(camera, camera_transform);
 
     let mut snap_angle = gizmo_options.snap_angle;
     let mut snap_distance = gizmo_options.snap_distance;
@@ -436,9 +463,15 @@ fn update_gizmos(
         snap_scale /= 2.0;
     }
 
+    let rotation_sensitivity = if gizmo_options.fine_rotation {
+        gizmo_options.fine_rotation_sensitivity
+    } else {
+        gizmo_options.rotation_sensitivity
+    };
+
     let gizmo_config = GizmoConfig {
-        view_matrix: view_matrix.as_dmat4().into(),
-        projection_matrix: projection_matrix.as_dmat4().into(),
+        view_matrix,
+        projection_matrix,
         viewport,
         modes: gizmo_options.gizmo_modes,
         mode_override: gizmo_options.mode_override,
@@ -447,15 +480,19 @@ fn update_gizmos(
         visuals: gizmo_options.visuals,
         snapping: gizmo_options.snapping,
         snap_angle,
+        rotation_sensitivity,
         snap_distance,
         snap_scale,
-        pixels_per_point: scale_factor,
+        uniform_scale: gizmo_options.uniform_scale,
+        pixels_per_point: Some(scale_factor),
+        ..Default::default()
     };
 
     let gizmo_interaction = GizmoInteraction {
         cursor_pos: (cursor_pos.x, cursor_pos.y),
         drag_started: mouse.just_pressed(MouseButton::Left),
         dragging: mouse.any_pressed([MouseButton::Left]),
+        ..Default::default()
     };
 
     let mut target_entities: Vec<Entity> = vec![];
@@ -488,11 +525,7 @@ fn update_gizmos(
 
         let gizmo_result = gizmo.update(
             gizmo_interaction,
-            &[math::Transform {
-                translation: target_transform.translation.as_dvec3().into(),
-                rotation: target_transform.rotation.as_dquat().into(),
-                scale: target_transform.scale.as_dvec3().into(),
-            }],
+            &[convert::to_gizmo_transform(&target_transform)],
         );
 
         let is_focused = gizmo.is_focused();
@@ -506,9 +539,7 @@ fn update_gizmos(
                 continue;
             };
 
-            target_transform.translation = DVec3::from(result_transform.translation).as_vec3();
-            target_transform.rotation = DQuat::from(result_transform.rotation).as_quat();
-            target_transform.scale = DVec3::from(result_transform.scale).as_vec3();
+            *target_transform = convert::from_gizmo_transform(result_transform);
         }
 
         gizmo_target.latest_result = gizmo_result.map(|(result, _)| result);
@@ -522,11 +553,7 @@ fn update_gizmos(
             gizmo_interaction,
             target_transforms
                 .iter()
-                .map(|transform| transform_gizmo::math::Transform {
-                    translation: transform.translation.as_dvec3().into(),
-                    rotation: transform.rotation.as_dquat().into(),
-                    scale: transform.scale.as_dvec3().into(),
-                })
+                .map(convert::to_gizmo_transform)
                 .collect::<Vec<_>>()
                 .as_slice(),
         );
@@ -543,9 +570,7 @@ fn update_gizmos(
                     continue;
                 };
 
-                target_transform.translation = DVec3::from(result_transform.translation).as_vec3();
-                target_transform.rotation = DQuat::from(result_transform.rotation).as_quat();
-                target_transform.scale = DVec3::from(result_transform.scale).as_vec3();
+                *target_transform = convert::from_gizmo_transform(result_transform);
             }
 
             gizmo_target.latest_result = gizmo_result.as_ref().map(|(result, _)| *result);
@@ -578,15 +603,15 @@ fn draw_gizmos(
         asset
             .0
             .vertices
-            .extend(draw_data.vertices.into_iter().map(|vert| {
+            .extend(draw_data.vertices.iter().map(|vert| {
                 [
                     ((vert[0] - viewport.left()) / viewport.width()) * 2.0 - 1.0,
                     ((vert[1] - viewport.top()) / viewport.height()) * 2.0 - 1.0,
                 ]
             }));
 
-        asset.0.colors = draw_data.colors;
-        asset.0.indices = draw_data.indices;
+        asset.0.colors = draw_data.colors.clone();
+        asset.0.indices = draw_data.indices.clone();
 
         if is_new_asset {
             let asset = draw_data_assets.add(bevy_draw_data);
@@ -44,18 +44,60 @@
 //! ```
 //!
 //!
-use egui::{epaint::Vertex, Mesh, PointerButton, Pos2, Rgba, Ui};
+use egui::{
+    epaint::Vertex, Align2, Color32, FontId, Key, Mesh, Modifiers, Painter, PointerButton, Pos2,
+    Rgba, Ui, Vec2,
+};
 
+use transform_gizmo::config::GizmoModeKind;
 use transform_gizmo::math::Transform;
 pub use transform_gizmo::*;
 pub mod prelude;
 
+/// Keys that control the gizmo.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct GizmoKeyBindings {
+    /// Enables snapping while held, in addition to [`GizmoConfig::snapping`].
+    pub snap: Option<Modifiers>,
+    /// Enables precision mode while held. See [`GizmoInteraction::precision`].
+    pub precision: Option<Modifiers>,
+    /// Keys for switching to translation, rotation and scale modes, in that order.
+    /// Switching is ignored during a drag, and while a widget has keyboard focus.
+    /// See [`Gizmo::set_mode_kind`].
+    pub mode_hotkeys: Option<[Key; 3]>,
+}
+
 pub trait GizmoExt {
     /// Interact with the gizmo and draw it to Ui.
     ///
     /// Returns result of the gizmo interaction.
     fn interact(&mut self, ui: &Ui, targets: &[Transform])
         -> Option<(GizmoResult, Vec<Transform>)>;
+
+    /// Interact with the gizmo and draw it to Ui, using the given keys
+    /// for snapping, precision mode and switching modes.
+    ///
+    /// Returns result of the gizmo interaction. If the mode was switched,
+    /// the new modes can be read from [`Gizmo::config`].
+    fn interact_with_key_bindings(
+        &mut self,
+        ui: &Ui,
+        targets: &[Transform],
+        key_bindings: GizmoKeyBindings,
+    ) -> Option<(GizmoResult, Vec<Transform>)>;
+
+    /// Interact with the gizmo like [`GizmoExt::interact_with_key_bindings`], but draw it
+    /// with the given painter instead of into the layer of the Ui. This can be used for
+    /// drawing the gizmo above other content, e.g. with a painter for a foreground layer.
+    ///
+    /// The drawing is clipped to the gizmo viewport, in addition to the clip rect of the painter.
+    fn interact_with_painter(
+        &mut self,
+        ui: &Ui,
+        painter: &Painter,
+        targets: &[Transform],
+        key_bindings: GizmoKeyBindings,
+    ) -> Option<(GizmoResult, Vec<Transform>)>;
 }
 
 impl GizmoExt for Gizmo {
@@ -64,6 +106,45 @@ impl GizmoExt for Gizmo {
         ui: &Ui,
         targets: &[Transform],
     ) -> Option<(GizmoResult, Vec<Transform>)> {
+        self.interact_with_key_bindings(ui, targets, GizmoKeyBindings::default())
+    }
+
+    fn interact_with_key_bindings(
+        &mut self,
+        ui: &Ui,
+        targets: &[Transform],
+        key_bindings: GizmoKeyBindings,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        let painter = Painter::new(ui.ctx().clone(), ui.layer_id(), egui::Rect::EVERYTHING);
+        self.interact_with_painter(ui, &painter, targets, key_bindings)
+    }
+
+    fn interact_with_painter(
+        &mut self,
+        ui: &Ui,
+        painter: &Painter,
+        targets: &[Transform],
+        key_bindings: GizmoKeyBindings,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        let modifier_held = |modifier: Option<Modifiers>| {
+            modifier.is_some_and(|modifier| ui.input(|input| input.modifiers.contains(modifier)))
+        };
+
+        let has_keyboard_focus = ui.memory(|memory| memory.focused().is_some());
+        if let (Some(mode_hotkeys), false) = (key_bindings.mode_hotkeys, has_keyboard_focus) {
+            let kinds = [
+                GizmoModeKind::Translate,
+                GizmoModeKind::Rotate,
+                GizmoModeKind::Scale,
+            ];
+
+            for (key, kind) in mode_hotkeys.into_iter().zip(kinds) {
+                if ui.input(|input| input.key_pressed(key)) {
+                    self.set_mode_kind(kind);
+                }
+            }
+        }
+
         let cursor_pos = ui
             .input(|input| input.pointer.hover_pos())
             .unwrap_or_default();
@@ -78,9 +159,9 @@ impl GizmoExt for Gizmo {
             max: Pos2::new(viewport.max.x, viewport.max.y),
         };
 
+        self.set_context_pixels_per_point(ui.ctx().pixels_per_point());
         self.update_config(GizmoConfig {
             viewport,
-            pixels_per_point: ui.ctx().pixels_per_point(),
             ..*self.config()
         });
 
@@ -90,19 +171,28 @@ impl GizmoExt for Gizmo {
                 drag_started: ui
                     .input(|input| input.pointer.button_pressed(PointerButton::Primary)),
                 dragging: ui.input(|input| input.pointer.button_down(PointerButton::Primary)),
+                double_clicked: ui
+                    .input(|input| input.pointer.button_double_clicked(PointerButton::Primary)),
+                snapping: modifier_held(key_bindings.snap),
+                precision: modifier_held(key_bindings.precision),
+                scroll_delta: ui.input(|input| input.smooth_scroll_delta.y),
+                delta_time: ui.input(|input| input.stable_dt),
+                ..Default::default()
             },
             targets,
         );
 
         let draw_data = self.draw();
 
-        egui::Painter::new(ui.ctx().clone(), ui.layer_id(), egui_viewport).add(Mesh {
-            indices: draw_data.indices,
+        let painter = painter.with_clip_rect(egui_viewport);
+
+        painter.add(Mesh {
+            indices: draw_data.indices.clone(),
             vertices: draw_data
                 .vertices
-                .into_iter()
-                .zip(draw_data.colors)
-                .map(|(pos, [r, g, b, a])| Vertex {
+                .iter()
+                .zip(&draw_data.colors)
+                .map(|(&pos, &[r, g, b, a])| Vertex {
                     pos: pos.into(),
                     uv: Pos2::default(),
                     color: Rgba::from_rgba_premultiplied(r, g, b, a).into(),
@@ -111,6 +201,98 @@ impl GizmoExt for Gizmo {
             ..Default::default()
         });
 
+        for label in self.axis_labels() {
+            let [r, g, b, a] = label.color.to_array();
+            painter.text(
+                Pos2::new(label.position.x, label.position.y),
+                Align2::CENTER_CENTER,
+                label.text,
+                FontId::default(),
+                Color32::from_rgba_premultiplied(r, g, b, a),
+            );
+        }
+
+        if self.config().visuals.show_rotation_readout {
+            if let Some((
+                GizmoResult::Rotation {
+                    total,
+                    phase: GizmoPhase::Begin | GizmoPhase::Update,
+                    ..
+                },
+                _,
+            )) = &gizmo_result
+            {
+                painter.text(
+                    cursor_pos + Vec2::new(16.0, -16.0),
+                    Align2::LEFT_BOTTOM,
+                    format!("{:.1}°", total.to_degrees()),
+                    FontId::default(),
+                    ui.visuals().strong_text_color(),
+                );
+            }
+        }
+
         gizmo_result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use egui::{Context, Id, LayerId, Order, RawInput, Shape};
+    use transform_gizmo::math::{DMat4, DVec3};
+
+    use super::*;
+
+    /// Runs a frame with a gizmo in a central panel, and returns the number of meshes
+    /// drawn into the panel's layer and into a foreground overlay layer.
+    fn meshes_per_layer(into_overlay: bool) -> (usize, usize) {
+        let ctx = Context::default();
+        let overlay = LayerId::new(Order::Foreground, Id::new("gizmo overlay"));
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: DMat4::look_at_rh(DVec3::Z * 10.0, DVec3::ZERO, DVec3::Y).into(),
+            projection_matrix: DMat4::perspective_rh(45f64.to_radians(), 4.0 / 3.0, 0.1, 1000.0)
+                .into(),
+            ..Default::default()
+        });
+
+        let input = RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                Pos2::ZERO,
+                Vec2::new(800.0, 600.0),
+            )),
+            ..Default::default()
+        };
+
+        let mut counts = (0, 0);
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let targets = [Transform::default()];
+                if into_overlay {
+                    let painter = ctx.layer_painter(overlay);
+                    gizmo.interact_with_painter(ui, &painter, &targets, Default::default());
+                } else {
+                    gizmo.interact(ui, &targets);
+                }
+
+                let meshes = |layer| {
+                    ctx.graphics(|graphics| {
+                        graphics.get(layer).map_or(0, |list| {
+                            list.all_entries()
+                                .filter(|clipped| matches!(clipped.shape, Shape::Mesh(_)))
+                                .count()
+                        })
+                    })
+                };
+                counts = (meshes(ui.layer_id()), meshes(overlay));
+            });
+        });
+
+        counts
+    }
+
+    #[test]
+    fn gizmo_is_drawn_with_the_given_painter() {
+        assert_eq!(meshes_per_layer(false), (1, 0));
+        assert_eq!(meshes_per_layer(true), (0, 1));
+    }
+}
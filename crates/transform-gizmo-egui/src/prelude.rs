@@ -1,3 +1,3 @@
 pub use transform_gizmo::prelude::*;
 
-pub use crate::GizmoExt;
+pub use crate::{GizmoExt, GizmoKeyBindings};
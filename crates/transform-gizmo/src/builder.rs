@@ -0,0 +1,340 @@
+use std::fmt;
+
+use emath::Rect;
+use enumset::EnumSet;
+use glam::Mat4;
+
+use crate::config::{
+    GizmoConfig, GizmoMode, GizmoOrientation, GizmoVisuals, Handedness, TransformPivotPoint,
+};
+use crate::gizmo::Gizmo;
+
+/// Builder for creating a [`Gizmo`] with a validated [`GizmoConfig`].
+///
+/// ```
+/// use transform_gizmo::prelude::*;
+/// use transform_gizmo::math::DMat4;
+///
+/// let gizmo = Gizmo::builder()
+///     .view_matrix(DMat4::IDENTITY)
+///     .projection_matrix(mint::ColumnMatrix4::from(DMat4::IDENTITY))
+///     .mode(GizmoMode::TranslateX)
+///     .orientation(GizmoOrientation::Local)
+///     .snapping(true)
+///     .build();
+///
+/// assert!(gizmo.is_ok());
+///
+/// let gizmo = Gizmo::builder()
+///     .view_matrix(DMat4::IDENTITY.into())
+///     .projection_matrix(DMat4::IDENTITY.into())
+///     .build();
+///
+/// assert_eq!(gizmo.unwrap_err(), GizmoBuildError::NoModes);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct GizmoBuilder {
+    config: GizmoConfig,
+    view_matrix: Option<mint::RowMatrix4<f64>>,
+    projection_matrix: Option<mint::RowMatrix4<f64>>,
+}
+
+impl Default for GizmoBuilder {
+    fn default() -> Self {
+        Self {
+            config: GizmoConfig {
+                modes: EnumSet::empty(),
+                ..Default::default()
+            },
+            view_matrix: None,
+            projection_matrix: None,
+        }
+    }
+}
+
+impl GizmoBuilder {
+    /// Creates a new builder with no modes enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// View matrix of the camera. Required.
+    ///
+    /// Accepts any matrix convertible to [`mint::RowMatrix4`], including [`mint::ColumnMatrix4`].
+    pub fn view_matrix(mut self, view_matrix: impl Into<mint::RowMatrix4<f64>>) -> Self {
+        self.view_matrix = Some(view_matrix.into());
+        self
+    }
+
+    /// Projection matrix of the camera. Required.
+    ///
+    /// Accepts any matrix convertible to [`mint::RowMatrix4`], including [`mint::ColumnMatrix4`].
+    pub fn projection_matrix(
+        mut self,
+        projection_matrix: impl Into<mint::RowMatrix4<f64>>,
+    ) -> Self {
+        self.projection_matrix = Some(projection_matrix.into());
+        self
+    }
+
+    /// View matrix of the camera, in single precision.
+    ///
+    /// Accepts e.g. `glam::Mat4` or column-major `[[f32; 4]; 4]` arrays.
+    /// The matrix is converted to double precision, which the gizmo uses for all calculations.
+    /// Converting does not restore precision lost in the original matrix, so large world
+    /// coordinates may still cause jitter.
+    pub fn view_matrix_f32(self, view_matrix: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+        self.view_matrix(matrix_to_f64(view_matrix.into()))
+    }
+
+    /// Projection matrix of the camera, in single precision.
+    /// See [`GizmoBuilder::view_matrix_f32`].
+    pub fn projection_matrix_f32(
+        self,
+        projection_matrix: impl Into<mint::ColumnMatrix4<f32>>,
+    ) -> Self {
+        self.projection_matrix(matrix_to_f64(projection_matrix.into()))
+    }
+
+    /// Screen area where the gizmo is displayed.
+    pub fn viewport(mut self, viewport: Rect) -> Self {
+        self.config.viewport = viewport;
+        self
+    }
+
+    /// Enables given mode, in addition to previously enabled modes.
+    pub fn mode(mut self, mode: GizmoMode) -> Self {
+        self.config.modes.insert(mode);
+        self
+    }
+
+    /// Enables given modes, in addition to previously enabled modes.
+    pub fn modes(mut self, modes: EnumSet<GizmoMode>) -> Self {
+        self.config.modes.insert_all(modes);
+        self
+    }
+
+    /// Orientation of the gizmo.
+    pub fn orientation(mut self, orientation: GizmoOrientation) -> Self {
+        self.config.orientation = orientation;
+        self
+    }
+
+    /// Handedness of the coordinate system. Detected from the projection matrix by default.
+    pub fn handedness(mut self, handedness: Handedness) -> Self {
+        self.config.handedness = Some(handedness);
+        self
+    }
+
+    /// Pivot point for transformations.
+    pub fn pivot_point(mut self, pivot_point: TransformPivotPoint) -> Self {
+        self.config.pivot_point = pivot_point;
+        self
+    }
+
+    /// Whether snapping is enabled.
+    pub fn snapping(mut self, snapping: bool) -> Self {
+        self.config.snapping = snapping;
+        self
+    }
+
+    /// Angle increment for snapping rotations, in radians.
+    pub fn snap_angle(mut self, snap_angle: f32) -> Self {
+        self.config.snap_angle = snap_angle;
+        self
+    }
+
+    /// Distance increment for snapping translations.
+    pub fn snap_distance(mut self, snap_distance: f32) -> Self {
+        self.config.snap_distance = snap_distance;
+        self
+    }
+
+    /// Scale increment for snapping scalings.
+    pub fn snap_scale(mut self, snap_scale: f32) -> Self {
+        self.config.snap_scale = snap_scale;
+        self
+    }
+
+    /// Visual settings of the gizmo.
+    pub fn visuals(mut self, visuals: GizmoVisuals) -> Self {
+        self.config.visuals = visuals;
+        self
+    }
+
+    /// Ratio of window's physical size to logical size.
+    pub fn pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.config.pixels_per_point = Some(pixels_per_point);
+        self
+    }
+
+    /// Validates the configuration without creating a gizmo.
+    pub fn build_config(self) -> Result<GizmoConfig, GizmoBuildError> {
+        let view_matrix = self.view_matrix.ok_or(GizmoBuildError::MissingViewMatrix)?;
+        let projection_matrix = self
+            .projection_matrix
+            .ok_or(GizmoBuildError::MissingProjectionMatrix)?;
+
+        if self.config.modes.is_empty() {
+            return Err(GizmoBuildError::NoModes);
+        }
+
+        Ok(GizmoConfig {
+            view_matrix,
+            projection_matrix,
+            ..self.config
+        })
+    }
+
+    /// Creates the gizmo.
+    pub fn build(self) -> Result<Gizmo, GizmoBuildError> {
+        self.build_config().map(Gizmo::new)
+    }
+}
+
+fn matrix_to_f64(matrix: mint::ColumnMatrix4<f32>) -> mint::ColumnMatrix4<f64> {
+    Mat4::from(matrix).as_dmat4().into()
+}
+
+/// Error returned when a [`GizmoBuilder`] has an invalid configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GizmoBuildError {
+    /// View matrix was not given.
+    MissingViewMatrix,
+    /// Projection matrix was not given.
+    MissingProjectionMatrix,
+    /// No modes were enabled.
+    NoModes,
+}
+
+impl fmt::Display for GizmoBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingViewMatrix => write!(f, "view matrix is required"),
+            Self::MissingProjectionMatrix => write!(f, "projection matrix is required"),
+            Self::NoModes => write!(f, "at least one gizmo mode is required"),
+        }
+    }
+}
+
+impl std::error::Error for GizmoBuildError {}
+
+#[cfg(test)]
+mod tests {
+    use glam::{DMat4, DQuat, DVec3};
+
+    use super::*;
+    use crate::math::Transform;
+    use crate::test_util::{self, X_ARROW};
+    use crate::GizmoResult;
+
+    #[test]
+    fn builds_the_given_config() {
+        let config = GizmoBuilder::new()
+            .view_matrix(test_util::view_matrix(test_util::CAMERA_POSITION))
+            .projection_matrix(test_util::projection_matrix())
+            .viewport(test_util::VIEWPORT)
+            .mode(GizmoMode::TranslateX)
+            .orientation(GizmoOrientation::Local)
+            .snapping(true)
+            .build_config()
+            .unwrap();
+
+        assert_eq!(config.modes, EnumSet::only(GizmoMode::TranslateX));
+        assert_eq!(config.orientation, GizmoOrientation::Local);
+        assert!(config.snapping);
+        assert_eq!(config.viewport, test_util::VIEWPORT);
+        assert_eq!(
+            DMat4::from(config.projection_matrix),
+            test_util::projection_matrix()
+        );
+    }
+
+    #[test]
+    fn invalid_configs_are_errors() {
+        let builder = GizmoBuilder::new();
+        assert_eq!(
+            builder.build().unwrap_err(),
+            GizmoBuildError::MissingViewMatrix
+        );
+
+        let builder = builder.view_matrix(DMat4::IDENTITY);
+        assert_eq!(
+            builder.build().unwrap_err(),
+            GizmoBuildError::MissingProjectionMatrix
+        );
+
+        let builder = builder.projection_matrix(DMat4::IDENTITY);
+        assert_eq!(builder.build().unwrap_err(), GizmoBuildError::NoModes);
+        assert!(builder.mode(GizmoMode::RotateX).build().is_ok());
+    }
+
+    #[test]
+    fn mint_matrices_and_results_round_trip() {
+        let view = test_util::view_matrix(test_util::CAMERA_POSITION);
+        let projection = test_util::projection_matrix();
+
+        let mut gizmo = GizmoBuilder::new()
+            .view_matrix(mint::ColumnMatrix4::from(view))
+            .projection_matrix(mint::ColumnMatrix4::from(projection))
+            .viewport(test_util::VIEWPORT)
+            .mode(GizmoMode::TranslateX)
+            .build()
+            .unwrap();
+        assert_eq!(DMat4::from(gizmo.config().view_matrix), view);
+        assert_eq!(DMat4::from(gizmo.config().projection_matrix), projection);
+
+        let rotation = DQuat::from_rotation_y(0.5);
+        let mut targets = [Transform::from_scale_rotation_translation(
+            mint::Vector3::from(DVec3::ONE),
+            mint::Quaternion::from(rotation),
+            mint::Vector3::from(DVec3::ZERO),
+        )];
+        let offsets = [DVec3::X * 0.5];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let GizmoResult::Translation { total, .. } = *results.last().unwrap() else {
+            panic!("expected a translation result, got {results:?}");
+        };
+        assert_eq!(mint::Vector3::from(DVec3::from(total)), total);
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            translation.abs_diff_eq(DVec3::X * 0.5, 1e-4),
+            "{translation}"
+        );
+        assert_eq!(DQuat::from(targets[0].rotation), rotation);
+        assert_eq!(DVec3::from(targets[0].scale), DVec3::ONE);
+    }
+
+    #[test]
+    fn f32_matrices_pick_like_f64_matrices() {
+        let view = test_util::view_matrix(test_util::CAMERA_POSITION);
+        let projection = test_util::projection_matrix();
+
+        let f64_gizmo = GizmoBuilder::new()
+            .view_matrix(view)
+            .projection_matrix(projection)
+            .viewport(test_util::VIEWPORT)
+            .mode(GizmoMode::TranslateX)
+            .build()
+            .unwrap();
+        let f32_gizmo = GizmoBuilder::new()
+            .view_matrix_f32(view.as_mat4())
+            .projection_matrix_f32(projection.as_mat4().to_cols_array_2d())
+            .viewport(test_util::VIEWPORT)
+            .mode(GizmoMode::TranslateX)
+            .build()
+            .unwrap();
+
+        let path = test_util::offset_path(&f64_gizmo, X_ARROW, &[DVec3::X * 0.5]);
+        let totals = [f64_gizmo, f32_gizmo].map(|mut gizmo| {
+            let mut targets = [Transform::default()];
+            let results = test_util::drag(&mut gizmo, &path, &mut targets);
+            test_util::translation_total(results.last().unwrap())
+        });
+
+        assert!(totals[0].abs_diff_eq(DVec3::X * 0.5, 1e-4), "{}", totals[0]);
+        assert!(totals[1].abs_diff_eq(totals[0], 1e-5), "{}", totals[1]);
+    }
+}
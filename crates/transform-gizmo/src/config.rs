@@ -16,38 +16,163 @@ pub const DEFAULT_SNAP_DISTANCE: f32 = 0.1;
 /// The default snapping distance for scale
 pub const DEFAULT_SNAP_SCALE: f32 = 0.1;
 
+/// The default scale factor per point of wheel scrolling
+pub const DEFAULT_WHEEL_SCALE_SENSITIVITY: f64 = 0.002;
+
+/// The default pick tolerance in screen pixels
+pub const DEFAULT_PICK_TOLERANCE: f32 = 5.0;
+/// The minimum pick tolerance in screen pixels when using touch input
+pub const TOUCH_PICK_TOLERANCE: f32 = 15.0;
+/// Multiplier of the pick tolerance for the last dragged handle,
+/// when [`GizmoConfig::sticky_handles`] is enabled
+pub const STICKY_PICK_TOLERANCE_SCALE: f32 = 1.5;
+
 /// Configuration of a gizmo.
 ///
 /// Defines how the gizmo is drawn to the screen and
 /// how it can be interacted with.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GizmoConfig {
     /// View matrix for the gizmo, aligning it with the camera's viewpoint.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub view_matrix: mint::RowMatrix4<f64>,
     /// Projection matrix for the gizmo, determining how it is projected onto the screen.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub projection_matrix: mint::RowMatrix4<f64>,
     /// Screen area where the gizmo is displayed.
     pub viewport: Rect,
     /// The gizmo's operation modes.
+    /// Only the handles of these modes are shown. For example, [`GizmoMode::TranslateXZ`]
+    /// alone gives a single ground plane handle in a Y-up world, without any axis arrows.
     pub modes: EnumSet<GizmoMode>,
     /// If set, this mode is forced active and other modes are disabled
     pub mode_override: Option<GizmoMode>,
     /// Determines the gizmo's orientation relative to global or local axes.
     pub orientation: GizmoOrientation,
+    /// Handedness of the coordinate system.
+    /// If not set, it is detected from the projection matrix.
+    /// Set this if rotations come out mirrored, e.g. when the view matrix flips the handedness.
+    pub handedness: Option<Handedness>,
+    /// Axes along which translation is not allowed.
+    /// Translation subgizmos that would move along these axes are not shown,
+    /// and the locked components are removed from the translation of other subgizmos.
+    pub locked_axes: EnumSet<GizmoDirection>,
+    /// Directions whose handles are drawn dimmed with [`GizmoVisuals::disabled_alpha`]
+    /// and cannot be interacted with. Unlike [`GizmoConfig::locked_axes`], the handles
+    /// are still shown. For plane handles, the direction is the plane normal.
+    pub disabled_directions: EnumSet<GizmoDirection>,
+    /// If set, rotation is only possible around this axis.
+    /// Only the rotation ring of this axis is shown, and arcball rotation is disabled.
+    pub constrained_rotation_axis: Option<GizmoDirection>,
+    /// If set, translations keep the gizmo position inside this
+    /// world space axis-aligned box, given as (min, max).
+    pub translation_bounds: Option<(mint::Vector3<f64>, mint::Vector3<f64>)>,
+    /// If set, translations keep the gizmo position on this world space line segment,
+    /// given as its end points. The gizmo moves to the point on the segment nearest
+    /// to the dragged position. The segment is drawn along with the gizmo.
+    pub translation_rail: Option<(mint::Vector3<f64>, mint::Vector3<f64>)>,
     /// Pivot point for transformations
     pub pivot_point: TransformPivotPoint,
+    /// If set, rotations orbit the targets around this world space point instead of
+    /// [`GizmoConfig::pivot_point`]. The translation this induces is included in the
+    /// returned target transforms.
+    pub rotation_pivot: Option<mint::Vector3<f64>>,
+    /// If set, the plane handles are aligned to the axes of this rotation, projected onto
+    /// each handle's plane, instead of to the gizmo axes. This can make the plane handles
+    /// look natural in tilted scenes. The rotation is relative to the gizmo orientation.
+    /// Translations along a plane are snapped along the aligned axes.
+    /// Scaling is not affected.
+    pub reference_frame: Option<mint::ColumnMatrix3<f64>>,
+    /// Up axis of the world, snapped to the nearest world axis. Defaults to +Y.
+    /// The plane handles and the start of the rotation rings are oriented relative to it,
+    /// so that the gizmo looks the same in Y-up and Z-up worlds viewed from the front.
+    pub world_up: mint::Vector3<f64>,
+    /// Offset of the gizmo from the pivot of the targets, in the targets' local space.
+    /// This moves the gizmo handles without affecting the applied translations.
+    /// With [`TransformPivotPoint::MedianPoint`], rotations are centered at the offset position.
+    pub gizmo_offset: mint::Vector3<f64>,
     /// Toggles snapping to predefined increments during transformations for precision.
     pub snapping: bool,
+    /// If true, translations snap the target position to multiples of the snap distance,
+    /// and rotations snap the target orientation around the rotation axis to multiples
+    /// of the snap angle, instead of snapping relative to the drag start.
+    /// In local orientation, the multiples are measured along the target's rotated axes.
+    /// View axis rotations always snap relative to the drag start.
+    pub snap_absolute: bool,
     /// Angle increment for snapping rotations, in radians.
     pub snap_angle: f32,
-    /// Distance increment for snapping translations.
+    /// Angle increments for snapping rotations around the X, Y and Z axes, in radians.
+    /// If set, these override [`GizmoConfig::snap_angle`] for the corresponding rings.
+    /// View axis rotations always use [`GizmoConfig::snap_angle`].
+    pub snap_angles: Option<[f32; 3]>,
+    /// Multiplier for the rotation angle computed from the pointer movement.
+    /// Lower values allow finer adjustments. Applied before snapping.
+    pub rotation_sensitivity: f64,
+    /// Multiplier for the pointer movement while precision mode is enabled
+    /// with [`crate::GizmoInteraction::precision`]. Applies to translation, scale and
+    /// rotation rings, but not to the arcball.
+    pub precision_sensitivity: f64,
+    /// If true, the arcball rotates around the camera's up and right axes
+    /// based on horizontal and vertical pointer movement, like orbit controls,
+    /// instead of projecting the pointer onto a virtual sphere.
+    pub orbit_rotation: bool,
+    /// Distance increment for snapping translations, in world units.
     pub snap_distance: f32,
-    /// Scale increment for snapping scalings.
+    /// Scale increment for snapping scalings, as a fraction of the scale when the drag started.
+    /// For example, 0.1 snaps the scale to 90%, 100%, 110% and so on.
+    /// Unlike [`GizmoConfig::snap_distance`], this is a multiplicative step.
     pub snap_scale: f32,
+    /// Minimum scale multiplier per axis, relative to the scale when the drag started.
+    /// Prevents scaling to zero or flipping the target inside out.
+    pub min_scale: f32,
+    /// If true, scaling past the gizmo center results in negative scale,
+    /// and [`GizmoConfig::min_scale`] is not applied.
+    pub allow_negative_scale: bool,
+    /// If true, axis and plane scale handles scale all axes uniformly,
+    /// by the factor that the grabbed handle would give.
+    pub uniform_scale: bool,
+    /// If true, scrolling the mouse wheel over a scale handle scales the targets
+    /// without dragging. See [`crate::GizmoInteraction::scroll_delta`].
+    pub wheel_scale: bool,
+    /// Scale factor per point of wheel scrolling, when [`GizmoConfig::wheel_scale`] is enabled.
+    /// The scale is multiplied by `1 + scroll_delta * wheel_scale_sensitivity`.
+    pub wheel_scale_sensitivity: f64,
+    /// Bounds of the targets in their local space, given as (min, max).
+    /// Used with [`GizmoConfig::anchored_scale`].
+    pub object_bounds: Option<(mint::Vector3<f64>, mint::Vector3<f64>)>,
+    /// If true and [`GizmoConfig::object_bounds`] is set, scaling keeps the side of the
+    /// bounds opposite to the scale handles in place, instead of scaling around the pivot.
+    /// The targets are translated accordingly.
+    pub anchored_scale: bool,
+    /// How far outside the axis arrows and rotation rings the mouse pointer can be,
+    /// while still grabbing them, in screen pixels. Defaults to [`DEFAULT_PICK_TOLERANCE`].
+    /// Larger values make thin handles easier to grab, e.g. on touch screens.
+    pub pick_tolerance: f32,
+    /// Kind of the input device used for interacting with the gizmo.
+    pub input_mode: InputMode,
+    /// How far the pointer must move from where a drag was started, in screen pixels,
+    /// before the targets are transformed. Prevents accidental transformations from
+    /// small movements when clicking a subgizmo. Once exceeded, the transformation is
+    /// measured from where the drag was started.
+    pub drag_threshold: f32,
+    /// If true, [`crate::Gizmo::update`] returns [`None`] during a drag on frames where the
+    /// transformation did not change, e.g. when snapping has not reached the next step.
+    /// The first result of a drag is then the first one that changes the transformation.
+    pub skip_unchanged_results: bool,
+    /// If true, the handle that was dragged last is preferred when picking, and can be
+    /// picked from slightly further away. This avoids accidentally switching between
+    /// adjacent handles in repetitive edits.
+    pub sticky_handles: bool,
     /// Visual settings for the gizmo, affecting appearance and visibility.
     pub visuals: GizmoVisuals,
     /// Ratio of window's physical size to logical size.
-    pub pixels_per_point: f32,
+    /// If not set, the ratio given with [`crate::Gizmo::set_context_pixels_per_point`]
+    /// is used, or 1.0. `transform-gizmo-egui` gives the ratio of the egui context,
+    /// so this only needs to be set for rendering to targets with a different ratio,
+    /// such as offscreen textures.
+    pub pixels_per_point: Option<f32>,
 }
 
 impl Default for GizmoConfig {
@@ -59,13 +184,40 @@ impl Default for GizmoConfig {
             modes: GizmoMode::all(),
             mode_override: None,
             orientation: GizmoOrientation::default(),
+            handedness: None,
+            locked_axes: EnumSet::empty(),
+            disabled_directions: EnumSet::empty(),
+            constrained_rotation_axis: None,
+            translation_bounds: None,
+            translation_rail: None,
             pivot_point: TransformPivotPoint::default(),
+            rotation_pivot: None,
+            reference_frame: None,
+            world_up: DVec3::Y.into(),
+            gizmo_offset: DVec3::ZERO.into(),
             snapping: false,
+            snap_absolute: false,
             snap_angle: DEFAULT_SNAP_ANGLE,
+            snap_angles: None,
+            rotation_sensitivity: 1.0,
+            precision_sensitivity: 0.1,
+            orbit_rotation: false,
             snap_distance: DEFAULT_SNAP_DISTANCE,
             snap_scale: DEFAULT_SNAP_SCALE,
+            min_scale: 1e-4,
+            allow_negative_scale: false,
+            uniform_scale: false,
+            wheel_scale: false,
+            wheel_scale_sensitivity: DEFAULT_WHEEL_SCALE_SENSITIVITY,
+            object_bounds: None,
+            anchored_scale: false,
+            pick_tolerance: DEFAULT_PICK_TOLERANCE,
+            input_mode: InputMode::default(),
+            drag_threshold: 0.0,
+            skip_unchanged_results: false,
+            sticky_handles: false,
             visuals: GizmoVisuals::default(),
-            pixels_per_point: 1.0,
+            pixels_per_point: None,
         }
     }
 }
@@ -86,20 +238,12 @@ impl GizmoConfig {
         DVec4::from(self.view_matrix.x).xyz()
     }
 
-    /// Whether local orientation is used
-    pub(crate) fn local_space(&self) -> bool {
-        self.orientation() == GizmoOrientation::Local
-    }
-
-    /// Transform orientation of the gizmo
-    pub(crate) fn orientation(&self) -> GizmoOrientation {
-        self.orientation
-    }
-
     /// Whether the modes have changed, compared to given other config
     pub(crate) fn modes_changed(&self, other: &Self) -> bool {
         (self.modes != other.modes && self.mode_override.is_none())
             || (self.mode_override != other.mode_override)
+            || (self.locked_axes != other.locked_axes)
+            || (self.constrained_rotation_axis != other.constrained_rotation_axis)
     }
 }
 
@@ -120,12 +264,25 @@ pub(crate) struct PreparedGizmoConfig {
     pub(crate) mvp: DMat4,
     /// Scale factor for the gizmo rendering
     pub(crate) scale_factor: f32,
+    /// Size of the gizmo in pixels, based on the size mode of the visuals
+    pub(crate) gizmo_size: f32,
     /// How close the mouse pointer needs to be to a subgizmo before it is focused
     pub(crate) focus_distance: f32,
-    /// Whether left-handed projection is used
+    /// Whether a left-handed coordinate system is used
     pub(crate) left_handed: bool,
+    /// Whether an orthographic projection is used.
+    /// Pointer rays are parallel to each other in orthographic projection.
+    pub(crate) orthographic: bool,
     /// Direction from the camera to the gizmo in world space
     pub(crate) eye_to_model_dir: DVec3,
+    /// Temporarily overrides the orientation of the config
+    pub(crate) orientation_override: Option<GizmoOrientation>,
+    /// Temporarily enables snapping, in addition to the config
+    pub(crate) snapping_override: bool,
+    /// Whether precision mode is enabled this frame
+    pub(crate) precision: bool,
+    /// Ratio of the rendering context, used if not set in the config
+    pub(crate) context_pixels_per_point: Option<f32>,
 }
 
 impl Deref for PreparedGizmoConfig {
@@ -143,21 +300,99 @@ impl DerefMut for PreparedGizmoConfig {
 }
 
 impl PreparedGizmoConfig {
+    /// Whether the viewport and camera matrices can be used for projecting the gizmo.
+    /// Uninitialized matrices, such as all zeros, are singular and would produce NaN values.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.config.viewport.is_finite()
+            && self.view_projection.is_finite()
+            && self.view_projection.determinant() != 0.0
+    }
+
+    /// Whether the model matrix is mirrored, i.e. has a negative determinant.
+    /// This is the case when an odd number of scale axes are negative.
+    pub(crate) fn mirrored(&self) -> bool {
+        self.scale.x * self.scale.y * self.scale.z < 0.0
+    }
+
+    /// Signs of the local gizmo axes. When the model matrix is mirrored, the axes with
+    /// a negative scale are flipped, so that the handles follow the visible axes of the
+    /// targets. A proper rotation alone cannot represent a mirrored basis.
+    pub(crate) fn axis_signs(&self) -> DVec3 {
+        if self.mirrored() {
+            DVec3::select(self.scale.cmplt(DVec3::ZERO), DVec3::NEG_ONE, DVec3::ONE)
+        } else {
+            DVec3::ONE
+        }
+    }
+
+    /// Rotation from a Y-up world to the world with [`GizmoConfig::world_up`] snapped
+    /// to the nearest world axis. Rotating a world axis with this gives another world axis.
+    pub(crate) fn world_up_rotation(&self) -> DQuat {
+        let up = DVec3::from(self.config.world_up);
+        if !up.is_finite() || up == DVec3::ZERO {
+            return DQuat::IDENTITY;
+        }
+
+        let abs = up.abs();
+        let up = if abs.x > abs.y && abs.x > abs.z {
+            DVec3::X * up.x.signum()
+        } else if abs.z > abs.y {
+            DVec3::Z * up.z.signum()
+        } else {
+            DVec3::Y * up.y.signum()
+        };
+
+        DQuat::from_rotation_arc(DVec3::Y, up)
+    }
+
+    /// Whether local orientation is used
+    pub(crate) fn local_space(&self) -> bool {
+        self.orientation() == GizmoOrientation::Local
+    }
+
+    /// Transform orientation of the gizmo
+    pub(crate) fn orientation(&self) -> GizmoOrientation {
+        self.orientation_override.unwrap_or(self.config.orientation)
+    }
+
+    /// Whether snapping is enabled
+    pub(crate) fn snapping(&self) -> bool {
+        self.config.snapping || self.snapping_override
+    }
+
+    /// Ratio of window's physical size to logical size
+    pub(crate) fn pixels_per_point(&self) -> f32 {
+        self.config
+            .pixels_per_point
+            .or(self.context_pixels_per_point)
+            .unwrap_or(1.0)
+    }
+
+    /// Fraction of the pointer movement to skip, based on whether precision mode is enabled
+    pub(crate) fn precision_skip(&self) -> f64 {
+        if self.precision {
+            1.0 - self.config.precision_sensitivity
+        } else {
+            0.0
+        }
+    }
+
     pub(crate) fn update_for_config(&mut self, config: GizmoConfig) {
         let projection_matrix = DMat4::from(config.projection_matrix);
         let view_matrix = DMat4::from(config.view_matrix);
 
         let view_projection = projection_matrix * view_matrix;
 
-        let left_handed = if projection_matrix.z_axis.w == 0.0 {
-            projection_matrix.z_axis.z > 0.0
-        } else {
-            projection_matrix.z_axis.w > 0.0
+        let left_handed = match config.handedness {
+            Some(handedness) => handedness == Handedness::LeftHanded,
+            None if projection_matrix.z_axis.w == 0.0 => projection_matrix.z_axis.z > 0.0,
+            None => projection_matrix.z_axis.w > 0.0,
         };
 
         self.config = config;
         self.view_projection = view_projection;
         self.left_handed = left_handed;
+        self.orthographic = projection_matrix.w_axis.w == 1.0;
 
         self.update_transform(Transform {
             scale: self.scale.into(),
@@ -187,6 +422,15 @@ impl PreparedGizmoConfig {
             scale /= target_count as f64;
         }
 
+        if let (TransformPivotPoint::ActiveTarget, Some(target)) =
+            (self.config.pivot_point, targets.last())
+        {
+            translation = target.translation.into();
+            scale = target.scale.into();
+        }
+
+        translation += rotation * DVec3::from(self.config.gizmo_offset);
+
         self.update_transform(Transform {
             scale: scale.into(),
             rotation: rotation.into(),
@@ -207,6 +451,11 @@ impl PreparedGizmoConfig {
             / self.config.viewport.width()
             * 2.0;
 
+        self.gizmo_size = match self.config.visuals.size_mode {
+            GizmoSizeMode::WorldUnits(size) if self.scale_factor > 0.0 => size / self.scale_factor,
+            _ => self.config.visuals.gizmo_size,
+        };
+
         let gizmo_screen_pos =
             world_to_screen(self.config.viewport, self.mvp, self.translation).unwrap_or_default();
 
@@ -217,7 +466,13 @@ impl PreparedGizmoConfig {
             -1.0,
         );
 
-        self.focus_distance = self.scale_factor * (self.config.visuals.stroke_width / 2.0 + 5.0);
+        let pick_tolerance = match self.config.input_mode {
+            InputMode::Mouse => self.config.pick_tolerance.max(0.0),
+            InputMode::Touch => self.config.pick_tolerance.max(TOUCH_PICK_TOLERANCE),
+        };
+
+        self.focus_distance =
+            self.scale_factor * (self.config.visuals.stroke_width / 2.0 + pick_tolerance);
 
         self.eye_to_model_dir = (gizmo_view_near - self.translation).normalize_or_zero();
     }
@@ -233,6 +488,7 @@ impl PreparedGizmoConfig {
 
 /// Operation mode of a gizmo.
 #[derive(Debug, EnumSetType, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GizmoMode {
     /// Rotate around the X axis
     RotateX,
@@ -240,7 +496,7 @@ pub enum GizmoMode {
     RotateY,
     /// Rotate around the Z axis
     RotateZ,
-    /// Rotate around the view forward axis
+    /// Rotate around the view forward axis, using the outer ring of the gizmo
     RotateView,
     /// Translate along the X axis
     TranslateX,
@@ -270,8 +526,10 @@ pub enum GizmoMode {
     ScaleYZ,
     /// Scale uniformly in all directions
     ScaleUniform,
-    /// Rotate using an arcball (trackball)
+    /// Rotate freely using an arcball (trackball), by grabbing the inner disc of the gizmo
     Arcball,
+    /// Translate along the custom axes set with [`crate::Gizmo::set_custom_translation_axes`]
+    TranslateCustom,
 }
 
 impl GizmoMode {
@@ -295,6 +553,7 @@ impl GizmoMode {
                 | Self::TranslateXZ
                 | Self::TranslateYZ
                 | Self::TranslateView
+                | Self::TranslateCustom
         )
     }
 
@@ -341,6 +600,9 @@ impl GizmoMode {
             Self::RotateView | Self::TranslateView => {
                 enum_set!(GizmoDirection::View)
             }
+            Self::TranslateCustom => {
+                enum_set!(GizmoDirection::Custom)
+            }
             Self::ScaleUniform | Self::Arcball => {
                 enum_set!(GizmoDirection::X | GizmoDirection::Y | GizmoDirection::Z)
             }
@@ -375,7 +637,8 @@ impl GizmoMode {
             | Self::TranslateXY
             | Self::TranslateXZ
             | Self::TranslateYZ
-            | Self::TranslateView => GizmoModeKind::Translate,
+            | Self::TranslateView
+            | Self::TranslateCustom => GizmoModeKind::Translate,
             Self::ScaleX
             | Self::ScaleY
             | Self::ScaleZ
@@ -398,16 +661,20 @@ pub enum GizmoModeKind {
 
 /// The point in space around which all rotations are centered.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransformPivotPoint {
     /// Pivot around the median point of targets
     #[default]
     MedianPoint,
     /// Pivot around each target's own origin
     IndividualOrigins,
+    /// Pivot around the last target, e.g. the active object of a selection
+    ActiveTarget,
 }
 
 /// Orientation of a gizmo.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GizmoOrientation {
     /// Transformation axes are aligned to world space.
     #[default]
@@ -416,7 +683,69 @@ pub enum GizmoOrientation {
     Local,
 }
 
+/// Kind of the input device used for interacting with a gizmo.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputMode {
+    /// Mouse or other pointer that can hover over the gizmo
+    #[default]
+    Mouse,
+    /// Touch screen. Since there is no hover, subgizmos are only picked when a touch starts,
+    /// and [`GizmoConfig::pick_tolerance`] is at least [`TOUCH_PICK_TOLERANCE`].
+    Touch,
+}
+
+/// Falloff curve of proportional editing. See [`crate::Gizmo::proportional_weights`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Falloff {
+    /// Weight eases out at the center and at the radius
+    #[default]
+    Smooth,
+    /// Weight decreases linearly with distance
+    Linear,
+    /// Weight drops quickly near the center
+    Sharp,
+}
+
+impl Falloff {
+    /// Weight of an object at the given distance from the center, between 0.0 and 1.0.
+    /// Objects at the center have weight 1.0, and objects at or beyond the radius have none.
+    ///
+    /// ```
+    /// # use transform_gizmo::config::Falloff;
+    /// assert_eq!(Falloff::Linear.weight(0.5, 2.0), 0.75);
+    /// assert_eq!(Falloff::Smooth.weight(1.0, 2.0), 0.5);
+    /// assert_eq!(Falloff::Sharp.weight(1.0, 2.0), 0.25);
+    /// assert_eq!(Falloff::Smooth.weight(3.0, 2.0), 0.0);
+    /// ```
+    pub fn weight(&self, distance: f64, radius: f64) -> f64 {
+        if radius.is_nan() || distance.is_nan() || radius <= 0.0 || distance >= radius {
+            return 0.0;
+        }
+
+        let t = 1.0 - distance.max(0.0) / radius;
+        match self {
+            Self::Smooth => t * t * (3.0 - 2.0 * t),
+            Self::Linear => t,
+            Self::Sharp => t * t,
+        }
+    }
+}
+
+/// Handedness of a coordinate system.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Handedness {
+    /// Right-handed coordinate system, e.g. X right, Y up and Z towards the viewer.
+    #[default]
+    RightHanded,
+    /// Left-handed coordinate system, e.g. X right, Y up and Z away from the viewer.
+    LeftHanded,
+}
+
 #[derive(Debug, EnumSetType, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GizmoDirection {
     /// Gizmo points in the X-direction
     X,
@@ -426,10 +755,52 @@ pub enum GizmoDirection {
     Z,
     /// Gizmo points in the view direction
     View,
+    /// Gizmo points along one of the custom translation axes
+    Custom,
+}
+
+/// Kind of a subgizmo handle
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TransformKind {
+    /// Handle that acts along a single axis
+    Axis,
+    /// Handle that acts along a plane
+    Plane,
+}
+
+/// Determines how the size of the gizmo is measured.
+///
+/// By default, the gizmo keeps a constant size on screen. The distance from the camera
+/// is accounted for when the targets or the camera move, so callers do not need to scale
+/// the gizmo themselves. Use [`GizmoSizeMode::WorldUnits`] for a gizmo that is sized like
+/// the objects in the scene instead.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GizmoSizeMode {
+    /// The gizmo has a fixed size on screen, given by [`GizmoVisuals::gizmo_size`] in pixels
+    #[default]
+    ScreenPixels,
+    /// The gizmo has a fixed size in the world, given in world units,
+    /// so it scales with perspective like the targets do.
+    /// [`GizmoVisuals::gizmo_size`] is ignored.
+    WorldUnits(f32),
+}
+
+/// Shape of the stroke ends of the gizmo arrows.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StrokeCap {
+    /// The stroke ends exactly at its end points
+    #[default]
+    Butt,
+    /// The stroke ends with a half circle
+    Round,
 }
 
 /// Controls the visual style of the gizmo
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct GizmoVisuals {
     /// Color of the x axis
     pub x_color: Color32,
@@ -445,10 +816,48 @@ pub struct GizmoVisuals {
     pub highlight_alpha: f32,
     /// Color to use for highlighted and active axes. By default, the axis color is used with `highlight_alpha`
     pub highlight_color: Option<Color32>,
+    /// How fast the highlight fades in and out, in 1/seconds.
+    /// Zero disables the animation. Requires [`crate::GizmoInteraction::delta_time`] to be set.
+    pub opacity_animation_speed: f32,
+    /// Additional alpha multiplier for the plane handles
+    pub plane_alpha: f32,
+    /// Alpha multiplier for the parts of the gizmo that are behind the scene geometry.
+    /// Only used if a depth test is set with [`crate::Gizmo::set_depth_test`].
+    pub occluded_alpha: f32,
+    /// Alpha multiplier for the handles disabled with [`GizmoConfig::disabled_directions`]
+    pub disabled_alpha: f32,
     /// Width (thickness) of the gizmo strokes
     pub stroke_width: f32,
+    /// Shape of the arrow shaft ends
+    pub stroke_cap: StrokeCap,
+    /// Length of the arrowheads, relative to the gizmo size
+    pub arrowhead_length: f32,
+    /// Width of the arrowheads, relative to the gizmo size
+    pub arrowhead_width: f32,
     /// Gizmo size in pixels
     pub gizmo_size: f32,
+    /// Whether the gizmo size is given in pixels or in world units
+    pub size_mode: GizmoSizeMode,
+    /// Multiplier of the gizmo size for the translation handles
+    pub translate_size_mul: f32,
+    /// Multiplier of the gizmo size for the rotation rings and the arcball
+    pub rotate_size_mul: f32,
+    /// Multiplier of the gizmo size for the scale handles
+    pub scale_size_mul: f32,
+    /// Number of line segments in full circles, such as the rotation rings.
+    /// If not set, the number is chosen from the size of each circle on screen.
+    pub circle_segments: Option<usize>,
+    /// Whether to draw a dot at the gizmo origin
+    pub draw_origin: bool,
+    /// Whether to draw only the camera facing half of the rotation rings.
+    /// If false, the rings are drawn and can be grabbed as full circles.
+    pub cull_rotation_rings: bool,
+    /// Whether to show the rotation angle next to the cursor while rotating.
+    /// The text is drawn by the integrations, e.g. the egui integration.
+    pub show_rotation_readout: bool,
+    /// Whether to show X, Y and Z labels beyond the handles of each axis.
+    /// See [`crate::Gizmo::axis_labels`].
+    pub show_axis_labels: bool,
 }
 
 impl Default for GizmoVisuals {
@@ -461,8 +870,251 @@ impl Default for GizmoVisuals {
             inactive_alpha: 0.7,
             highlight_alpha: 1.0,
             highlight_color: None,
+            opacity_animation_speed: 0.0,
+            plane_alpha: 1.0,
+            occluded_alpha: 0.3,
+            disabled_alpha: 0.25,
             stroke_width: 4.0,
+            stroke_cap: StrokeCap::default(),
+            arrowhead_length: 0.13,
+            arrowhead_width: 0.13,
             gizmo_size: 75.0,
+            size_mode: GizmoSizeMode::default(),
+            translate_size_mul: 1.0,
+            rotate_size_mul: 1.0,
+            scale_size_mul: 1.0,
+            circle_segments: None,
+            draw_origin: false,
+            cull_rotation_rings: true,
+            show_rotation_readout: false,
+            show_axis_labels: false,
+        }
+    }
+}
+
+impl GizmoVisuals {
+    /// Visuals with axis colors that remain distinguishable with the common forms of
+    /// color blindness. The colors are from the Okabe-Ito palette, and focused handles
+    /// are highlighted with a color instead of only by their opacity.
+    pub fn colorblind() -> Self {
+        Self {
+            x_color: Color32::from_rgb(213, 94, 0),
+            y_color: Color32::from_rgb(240, 228, 66),
+            z_color: Color32::from_rgb(0, 114, 178),
+            highlight_color: Some(Color32::from_rgb(255, 255, 255)),
+            ..Default::default()
+        }
+    }
+
+    /// Multiplier of the gizmo size for the handles of the given mode
+    pub(crate) fn size_mul(&self, mode: GizmoMode) -> f32 {
+        match mode.kind() {
+            GizmoModeKind::Translate => self.translate_size_mul,
+            GizmoModeKind::Rotate | GizmoModeKind::Arcball => self.rotate_size_mul,
+            GizmoModeKind::Scale => self.scale_size_mul,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Pos2;
+    use crate::test_util;
+    use crate::{Gizmo, GizmoInteraction};
+
+    /// Largest distance of the drawn gizmo from the viewport center
+    fn screen_extent(size_mode: GizmoSizeMode, camera_distance: f64) -> f32 {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::Z * camera_distance).into(),
+            visuals: GizmoVisuals {
+                size_mode,
+                ..Default::default()
+            },
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        });
+        gizmo.update(GizmoInteraction::default(), &[Transform::default()]);
+        gizmo
+            .draw()
+            .vertices
+            .iter()
+            .map(|vertex| (vertex[0] - test_util::CENTER.x).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn world_units_size_scales_with_perspective() {
+        let far = screen_extent(GizmoSizeMode::WorldUnits(1.0), 10.0);
+        let near = screen_extent(GizmoSizeMode::WorldUnits(1.0), 5.0);
+        assert!(near > far * 1.8, "{near} {far}");
+
+        let far = screen_extent(GizmoSizeMode::ScreenPixels, 10.0);
+        let near = screen_extent(GizmoSizeMode::ScreenPixels, 5.0);
+        assert!((near - far).abs() < 1.0, "{near} {far}");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn visuals_round_trip_through_json() {
+        let visuals = GizmoVisuals {
+            x_color: Color32::from_rgba_unmultiplied(1, 2, 3, 4),
+            y_color: Color32::from_rgb(5, 6, 7),
+            z_color: Color32::from_rgb(8, 9, 10),
+            s_color: Color32::from_rgb(11, 12, 13),
+            inactive_alpha: 0.1,
+            highlight_alpha: 0.2,
+            highlight_color: Some(Color32::GOLD),
+            opacity_animation_speed: 3.0,
+            plane_alpha: 0.3,
+            occluded_alpha: 0.4,
+            disabled_alpha: 0.5,
+            stroke_width: 6.0,
+            stroke_cap: StrokeCap::Round,
+            arrowhead_length: 0.7,
+            arrowhead_width: 0.8,
+            gizmo_size: 90.0,
+            size_mode: GizmoSizeMode::WorldUnits(2.5),
+            translate_size_mul: 1.1,
+            rotate_size_mul: 1.2,
+            scale_size_mul: 1.3,
+            circle_segments: Some(48),
+            draw_origin: true,
+            cull_rotation_rings: false,
+            show_rotation_readout: true,
+            show_axis_labels: true,
+        };
+
+        let json = serde_json::to_string(&visuals).unwrap();
+        assert_eq!(
+            serde_json::from_str::<GizmoVisuals>(&json).unwrap(),
+            visuals
+        );
+
+        let modes = GizmoMode::all_translate();
+        let json = serde_json::to_string(&modes).unwrap();
+        assert_eq!(
+            serde_json::from_str::<EnumSet<GizmoMode>>(&json).unwrap(),
+            modes
+        );
+    }
+
+    #[test]
+    fn pick_tolerance_widens_the_handles() {
+        // 8 pixels beside the X arrow, which is 4 pixels wide
+        let cursor_pos = (test_util::X_ARROW.x, test_util::X_ARROW.y + 8.0);
+
+        let hovered = |pick_tolerance| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                pick_tolerance,
+                ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+            });
+            let targets = [Transform::default()];
+            gizmo.update(test_util::hover(Pos2::ZERO), &targets);
+            gizmo.hovered(cursor_pos)
+        };
+
+        assert_eq!(hovered(2.0), None);
+        assert_eq!(
+            hovered(10.0),
+            Some((
+                GizmoMode::TranslateX,
+                GizmoDirection::X,
+                TransformKind::Axis
+            ))
+        );
+    }
+
+    #[test]
+    fn screen_pixel_size_ignores_the_camera_distance() {
+        let target = Transform {
+            translation: DVec3::new(1.0, 0.5, -2.0).into(),
+            ..Default::default()
+        };
+        let direction = DVec3::new(1.0, 2.0, 3.0).normalize();
+
+        let screen_size = |distance: f64| {
+            let eye = DVec3::from(target.translation) + direction * distance;
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                view_matrix: DMat4::look_at_rh(eye, target.translation.into(), DVec3::Y).into(),
+                ..test_util::config(GizmoMode::all_translate())
+            });
+            gizmo.update(GizmoInteraction::default(), &[target]);
+            gizmo.screen_rect().size()
+        };
+
+        let (near, far) = (screen_size(8.0), screen_size(16.0));
+        assert!(near.x > 0.0 && near.y > 0.0, "{near:?}");
+        assert!((far.x / near.x - 1.0).abs() < 0.05, "{near:?} {far:?}");
+        assert!((far.y / near.y - 1.0).abs() < 0.05, "{near:?} {far:?}");
+    }
+
+    #[test]
+    fn size_multipliers_apply_per_mode() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            visuals: GizmoVisuals {
+                rotate_size_mul: 1.5,
+                ..Default::default()
+            },
+            ..test_util::config(GizmoMode::TranslateX | GizmoMode::RotateZ)
+        });
+        gizmo.update(test_util::hover(Pos2::ZERO), &[Transform::default()]);
+        let size = GizmoVisuals::default().gizmo_size;
+        let center = test_util::CENTER;
+
+        // Below the center, the ring is 1.5 times the arrow length away
+        let below = |distance: f32| gizmo.hovered((center.x, center.y + distance));
+        let ring = Some((GizmoMode::RotateZ, GizmoDirection::Z, TransformKind::Axis));
+        assert_eq!(below(size * 1.5), ring);
+        assert_eq!(below(size), None);
+
+        // The arrow keeps its length
+        let right = |distance: f32| gizmo.hovered((center.x + distance, center.y));
+        let arrow = Some((
+            GizmoMode::TranslateX,
+            GizmoDirection::X,
+            TransformKind::Axis,
+        ));
+        assert_eq!(right(size * 0.9), arrow);
+        assert_eq!(right(size * 1.2), None);
+    }
+
+    #[test]
+    fn colorblind_visuals_replace_every_axis_color() {
+        let default = GizmoVisuals::default();
+        let colorblind = GizmoVisuals::colorblind();
+
+        let colors = [colorblind.x_color, colorblind.y_color, colorblind.z_color];
+        let default_colors = [default.x_color, default.y_color, default.z_color];
+        for (index, color) in colors.iter().enumerate() {
+            assert!(!default_colors.contains(color), "{color:?}");
+            assert!(!colors[index + 1..].contains(color), "{color:?}");
+        }
+
+        let draw_colors = |visuals: GizmoVisuals, mode: GizmoMode| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                visuals,
+                ..test_util::config(EnumSet::only(mode))
+            });
+            gizmo.update(GizmoInteraction::default(), &[Transform::default()]);
+            gizmo.draw().colors.clone()
+        };
+
+        // Arrows, rings and planes are all drawn with the palette
+        for mode in [
+            GizmoMode::TranslateX,
+            GizmoMode::TranslateY,
+            GizmoMode::TranslateZ,
+            GizmoMode::RotateX,
+            GizmoMode::RotateY,
+            GizmoMode::RotateZ,
+            GizmoMode::TranslateXY,
+            GizmoMode::TranslateXZ,
+            GizmoMode::TranslateYZ,
+        ] {
+            let default = draw_colors(default, mode);
+            let colorblind = draw_colors(colorblind, mode);
+            assert!(!colorblind.is_empty(), "{mode:?}");
+            assert_ne!(default, colorblind, "{mode:?}");
         }
     }
 }
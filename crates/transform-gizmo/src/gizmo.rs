@@ -1,25 +1,39 @@
-use ecolor::Rgba;
-use emath::Pos2;
+use ecolor::{Color32, Rgba};
+use emath::{Pos2, Rect};
 use enumset::EnumSet;
+use std::borrow::BorrowMut;
+use std::hash::{BuildHasher, Hash, Hasher};
 use std::ops::{Add, AddAssign, Sub};
+use std::sync::{Arc, Mutex, OnceLock};
 
+use crate::builder::GizmoBuilder;
 use crate::config::{
-    GizmoConfig, GizmoDirection, GizmoMode, PreparedGizmoConfig, TransformPivotPoint,
+    Falloff, GizmoConfig, GizmoDirection, GizmoMode, GizmoModeKind, InputMode, PreparedGizmoConfig,
+    TransformKind, TransformPivotPoint, STICKY_PICK_TOLERANCE_SCALE,
+};
+use crate::math::{
+    ray_to_plane, screen_to_world, world_to_screen, world_to_screen_depth, Transform,
 };
-use crate::math::{screen_to_world, Transform};
 use crate::GizmoOrientation;
 use epaint::Mesh;
-use glam::{DMat4, DQuat, DVec3};
+use glam::{DMat4, DQuat, DVec3, DVec4};
 
 use crate::subgizmo::rotation::RotationParams;
 use crate::subgizmo::scale::ScaleParams;
-use crate::subgizmo::translation::TranslationParams;
+use crate::subgizmo::translation::{constrain_translation, CustomAxis, TranslationParams};
 use crate::subgizmo::{
-    common::TransformKind, ArcballSubGizmo, RotationSubGizmo, ScaleSubGizmo, SubGizmo,
-    SubGizmoControl, TranslationSubGizmo,
+    common::{draw_circle, draw_line_segment, gizmo_color, gizmo_normal, valid_snap_interval},
+    ArcballSubGizmo, RotationSubGizmo, ScaleSubGizmo, SubGizmo, SubGizmoControl,
+    TranslationSubGizmo,
 };
 
+/// Distance of the axis labels from the gizmo origin, relative to the outermost handle
+const AXIS_LABEL_OFFSET: f32 = 1.2;
+
 /// A 3D transformation gizmo.
+///
+/// All interaction state, including the state of an active drag, is owned by the gizmo itself.
+/// Keep the same instance alive between frames to preserve it.
 #[derive(Clone, Debug, Default)]
 pub struct Gizmo {
     /// Prepared configuration of the gizmo.
@@ -30,10 +44,42 @@ pub struct Gizmo {
     /// Subgizmos used in the gizmo.
     subgizmos: Vec<SubGizmo>,
     active_subgizmo_id: Option<u64>,
+    /// Subgizmo that was dragged last, preferred in picking if handles are sticky.
+    last_active_subgizmo_id: Option<u64>,
 
     target_start_transforms: Vec<Transform>,
 
     gizmo_start_transform: Transform,
+
+    /// Additional axes to translate along, in gizmo local space.
+    custom_translation_axes: Vec<DVec3>,
+
+    /// Points in world space that translations snap to.
+    snap_targets: Vec<DVec3>,
+
+    /// Latest result of the active interaction.
+    last_result: Option<GizmoResult>,
+    /// Identifier of the latest interaction, incremented when an interaction starts.
+    interaction_id: u64,
+
+    /// Cursor position where the active interaction was started.
+    drag_start_pos: Pos2,
+    /// Whether the cursor has moved past [`GizmoConfig::drag_threshold`] during the active interaction.
+    drag_threshold_exceeded: bool,
+
+    /// Used for dimming the parts of the gizmo that are behind the scene geometry.
+    depth_test: Option<DepthTest>,
+
+    /// Called when a snapped interaction crosses snapping steps.
+    snap_step_callback: Option<SnapStepCallback>,
+
+    /// Draw data built with the inputs of [`Gizmo::draw_key`], reused while they are unchanged.
+    draw_cache: OnceLock<Arc<GizmoDrawData>>,
+    /// Inputs that the cached draw data was built with
+    draw_key: Option<DrawKey>,
+    /// Incremented whenever something affecting the drawing changes that is not otherwise
+    /// part of the [`DrawKey`], such as the visuals or the state of a dragged subgizmo.
+    draw_generation: u64,
 }
 
 impl Gizmo {
@@ -44,6 +90,11 @@ impl Gizmo {
         gizmo
     }
 
+    /// Creates a builder for configuring a new gizmo.
+    pub fn builder() -> GizmoBuilder {
+        GizmoBuilder::new()
+    }
+
     /// Current configuration used by the gizmo.
     pub fn config(&self) -> &GizmoConfig {
         &self.config
@@ -51,6 +102,10 @@ impl Gizmo {
 
     /// Updates the configuration used by the gizmo.
     pub fn update_config(&mut self, config: GizmoConfig) {
+        if config != *self.config() {
+            self.draw_generation += 1;
+        }
+
         if config.modes_changed(&self.config) {
             self.subgizmos.clear();
             self.active_subgizmo_id = None;
@@ -63,6 +118,73 @@ impl Gizmo {
             self.add_translation();
             self.add_scale();
         }
+
+        self.refresh_draw_cache();
+    }
+
+    /// Sets the ratio of physical to logical size of the rendering context, e.g. of an egui context.
+    ///
+    /// The ratio is used if [`GizmoConfig::pixels_per_point`] is not set. It is not stored
+    /// in the configuration, so it can be given again every frame without overriding the config.
+    pub fn set_context_pixels_per_point(&mut self, pixels_per_point: f32) {
+        self.config.context_pixels_per_point = Some(pixels_per_point);
+        self.refresh_draw_cache();
+    }
+
+    /// Sets additional axes that the gizmo can translate along.
+    ///
+    /// An arrow is shown for each axis when [`GizmoMode::TranslateCustom`] is enabled.
+    /// The axes are in the gizmo's local space, so they follow the target
+    /// rotation when local orientation is used. Axes of zero length are ignored.
+    ///
+    /// Translation results of the custom axes have the [`GizmoDirection::Custom`] direction,
+    /// and the index of the axis in the given slice.
+    pub fn set_custom_translation_axes(&mut self, axes: &[mint::Vector3<f64>]) {
+        self.custom_translation_axes = axes.iter().map(|axis| DVec3::from(*axis)).collect();
+
+        self.subgizmos.clear();
+        self.active_subgizmo_id = None;
+
+        self.update_config(*self.config());
+    }
+
+    /// Sets points in world space that translations snap to, e.g. the origins of other objects.
+    ///
+    /// While snapping is enabled, the gizmo snaps exactly onto the nearest point within
+    /// [`GizmoConfig::snap_distance`] from it, in addition to the regular snapping.
+    /// The points are snapped to even if they are not along the dragged axis or plane,
+    /// but not if the locked axes, bounds or rail of the configuration prevent reaching them.
+    pub fn set_snap_targets(&mut self, targets: &[mint::Vector3<f64>]) {
+        self.snap_targets = targets.iter().copied().map(DVec3::from).collect();
+    }
+
+    /// Enables all modes of the given kind and disables the others,
+    /// e.g. for switching between translation, rotation and scale with hotkeys.
+    ///
+    /// Ignored while the gizmo is being dragged. Returns true if the enabled modes were changed.
+    /// The new modes can be read from [`Gizmo::config`].
+    pub fn set_mode_kind(&mut self, kind: GizmoModeKind) -> bool {
+        if self.active_subgizmo_id.is_some() {
+            return false;
+        }
+
+        let modes = match kind {
+            GizmoModeKind::Rotate => GizmoMode::all_rotate(),
+            GizmoModeKind::Translate => GizmoMode::all_translate(),
+            GizmoModeKind::Scale => GizmoMode::all_scale(),
+            GizmoModeKind::Arcball => EnumSet::only(GizmoMode::Arcball),
+        };
+
+        if modes == self.config.modes {
+            return false;
+        }
+
+        self.update_config(GizmoConfig {
+            modes,
+            ..*self.config()
+        });
+
+        true
     }
 
     /// Was this gizmo focused after the latest [`Gizmo::update`] call.
@@ -70,6 +192,87 @@ impl Gizmo {
         self.subgizmos.iter().any(|subgizmo| subgizmo.is_focused())
     }
 
+    /// Whether the gizmo is using the pointer after the latest [`Gizmo::update`] call,
+    /// i.e. a subgizmo is hovered or dragged.
+    ///
+    /// This can be used for disabling other pointer controls, such as camera controls,
+    /// while the gizmo is being used. Stays true for the whole duration of a drag,
+    /// even if the pointer leaves the dragged subgizmo.
+    pub fn is_capturing_pointer(&self) -> bool {
+        self.interaction_state() != InteractionState::Idle
+    }
+
+    /// Accumulated state of the subgizmo that is being dragged, if any.
+    ///
+    /// The snapshot is a copy, so reading it does not affect the interaction.
+    pub fn active_state(&self) -> Option<ActiveStateSnapshot> {
+        self.active_subgizmo_id
+            .and_then(|id| self.subgizmos.iter().find(|subgizmo| subgizmo.id() == id))
+            .map(SubGizmo::state_snapshot)
+    }
+
+    /// Identifier of the latest interaction. This is incremented whenever a drag starts,
+    /// and for each interaction that completes at once, such as [`Gizmo::nudge`].
+    ///
+    /// All results of a drag belong to the same interaction, so this can be used for
+    /// grouping results, e.g. for coalescing undo steps. Each result carries the identifier
+    /// of its interaction, see [`GizmoResult::interaction_id`].
+    pub fn interaction_id(&self) -> u64 {
+        self.interaction_id
+    }
+
+    /// Weights for proportional editing of the given object positions, in world space.
+    ///
+    /// The weights fall off with the distance of each object from the gizmo, measured
+    /// from where the gizmo was when the active drag started. Scaling the applied deltas
+    /// by these weights drags nearby objects along smoothly.
+    pub fn proportional_weights(
+        &self,
+        positions: &[mint::Vector3<f64>],
+        radius: f64,
+        falloff: Falloff,
+    ) -> Vec<f64> {
+        let center = if self.active_subgizmo_id.is_some() {
+            DVec3::from(self.gizmo_start_transform.translation)
+        } else {
+            self.config.translation
+        };
+
+        positions
+            .iter()
+            .map(|&position| falloff.weight(DVec3::from(position).distance(center), radius))
+            .collect()
+    }
+
+    /// Current state of the interaction, after the latest [`Gizmo::update`] call.
+    ///
+    /// This can be used for keeping other UI, such as toolbars, in sync with the gizmo.
+    pub fn interaction_state(&self) -> InteractionState {
+        if let Some(subgizmo) = self
+            .active_subgizmo_id
+            .and_then(|id| self.subgizmos.iter().find(|subgizmo| subgizmo.id() == id))
+        {
+            let (mode, direction, kind) = subgizmo.handle();
+            return InteractionState::Dragging {
+                mode,
+                direction,
+                kind,
+            };
+        }
+
+        self.subgizmos
+            .iter()
+            .find(|subgizmo| subgizmo.is_focused())
+            .map_or(InteractionState::Idle, |subgizmo| {
+                let (mode, direction, kind) = subgizmo.handle();
+                InteractionState::Hovered {
+                    mode,
+                    direction,
+                    kind,
+                }
+            })
+    }
+
     /// Updates the gizmo based on given interaction information.
     ///
     /// # Examples
@@ -86,7 +289,8 @@ impl Gizmo {
     /// let interaction = GizmoInteraction {
     ///     cursor_pos,
     ///     drag_started,
-    ///     dragging
+    ///     dragging,
+    ///     ..Default::default()
     /// };
     ///
     /// if let Some((_result, new_transforms)) = gizmo.update(interaction, &transforms) {
@@ -102,61 +306,215 @@ impl Gizmo {
     /// Returns the result of the interaction with the updated transformation.
     ///
     /// [`Some`] is returned when any of the subgizmos is being dragged, [`None`] otherwise.
+    /// The phase of the interaction is reported in the result. On the frame the drag is released,
+    /// a result with [`GizmoPhase::End`] is returned, containing the final total values.
+    ///
+    /// [`None`] is also returned if the viewport is not set, or if the camera matrices are
+    /// singular or contain non-finite values, so that the targets never receive NaN values.
+    /// Use [`Gizmo::try_update`] to tell these cases apart.
     pub fn update(
         &mut self,
         interaction: GizmoInteraction,
         targets: &[Transform],
     ) -> Option<(GizmoResult, Vec<Transform>)> {
+        self.try_update(interaction, targets).ok().flatten()
+    }
+
+    /// Updates the gizmo like [`Gizmo::update`], but returns an error if the configuration
+    /// cannot be used for projecting the gizmo. The targets are not transformed then.
+    pub fn try_update(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+    ) -> Result<Option<(GizmoResult, Vec<Transform>)>, GizmoError> {
         if !self.config.viewport.is_finite() {
-            return None;
+            return Err(GizmoError::InvalidViewport);
+        }
+
+        if !self.config.is_valid() {
+            return Err(GizmoError::InvalidMatrices);
+        }
+
+        let result = self.update_interaction(interaction, targets);
+
+        // A dragged subgizmo may be drawn based on its state, e.g. the dragged angle
+        if self.active_subgizmo_id.is_some() {
+            self.draw_generation += 1;
         }
+        self.refresh_draw_cache();
+
+        Ok(result)
+    }
 
+    fn update_interaction(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
         // Update the gizmo based on the given target transforms,
         // unless the gizmo is currently being interacted with.
         if self.active_subgizmo_id.is_none() {
+            self.config.orientation_override = interaction.orientation_override;
             self.config.update_for_targets(targets);
         }
 
+        // Snapping and precision can be toggled in the middle of a drag.
+        self.config.snapping_override = interaction.snapping;
+        self.config.precision = interaction.precision;
+
+        let sticky_subgizmo_id = self.sticky_subgizmo_id();
         for subgizmo in &mut self.subgizmos {
             // Update current configuration to each subgizmo.
-            subgizmo.update_config(self.config);
+            let mut config = self.config;
+            if sticky_subgizmo_id == Some(subgizmo.id()) {
+                config.focus_distance *= STICKY_PICK_TOLERANCE_SCALE;
+            }
+            subgizmo.update_config(config);
             // All subgizmos are initially considered unfocused.
             subgizmo.set_focused(false);
         }
 
+        // Nothing can be picked if the gizmo is behind the camera.
+        // An active drag continues, since the camera may move back during it.
+        if self.active_subgizmo_id.is_none() && self.is_behind_camera() {
+            return None;
+        }
+
         let force_active = self.config.mode_override.is_some();
 
-        let pointer_ray = self.pointer_ray(Pos2::from(interaction.cursor_pos));
+        let cursor_pos = Pos2::from(interaction.cursor_pos);
+        let pointer_ray = self.pointer_ray(cursor_pos);
+
+        // Subgizmos can only be grabbed from inside the viewport.
+        // An active drag continues even if the pointer leaves it.
+        let cursor_in_viewport = self.config.viewport.contains(cursor_pos);
+
+        // Touch input has no hover, so subgizmos are only picked when a touch starts.
+        let can_pick =
+            self.config.input_mode == InputMode::Mouse || interaction.drag_started || force_active;
 
         // If there is no active subgizmo, find which one of them
         // is under the mouse pointer, if any.
-        if self.active_subgizmo_id.is_none() {
+        if self.active_subgizmo_id.is_none() && (cursor_in_viewport || force_active) && can_pick {
             if let Some(subgizmo) = self.pick_subgizmo(pointer_ray) {
                 subgizmo.set_focused(true);
 
                 // If we started dragging from one of the subgizmos, mark it as active.
                 if interaction.drag_started || force_active {
                     self.active_subgizmo_id = Some(subgizmo.id());
+                    self.last_active_subgizmo_id = self.active_subgizmo_id;
+                    self.interaction_id += 1;
                     self.target_start_transforms = targets.to_vec();
                     self.gizmo_start_transform = self.config.as_transform();
+                    self.last_result = None;
+                    self.drag_start_pos = cursor_pos;
+                    self.drag_threshold_exceeded = false;
                 }
             }
         }
 
         let mut result = None;
 
+        if self.active_subgizmo_id.is_some() && !self.drag_threshold_exceeded {
+            self.drag_threshold_exceeded = force_active
+                || self.config.drag_threshold <= 0.0
+                || cursor_pos.distance(self.drag_start_pos) > self.config.drag_threshold;
+        }
+        let drag_threshold_exceeded = self.drag_threshold_exceeded;
+
+        // Double clicking a subgizmo may reset the transformation it controls.
+        // The second click of a double click also starts a drag, so this is checked
+        // before the drag is updated or ended. The reset takes the place of the drag.
+        if interaction.double_clicked && cursor_in_viewport {
+            result = self
+                .pick_subgizmo(pointer_ray)
+                .and_then(|subgizmo| subgizmo.reset());
+
+            if result.is_some() {
+                if let Some(subgizmo) = self.active_subgizmo_mut() {
+                    subgizmo.set_active(false);
+                    subgizmo.set_focused(false);
+                } else {
+                    self.interaction_id += 1;
+                }
+                self.active_subgizmo_id = None;
+                self.last_result = None;
+                self.target_start_transforms = targets.to_vec();
+                self.gizmo_start_transform = self.config.as_transform();
+            }
+        }
+
         if let Some(subgizmo) = self.active_subgizmo_mut() {
             if interaction.dragging || force_active {
                 subgizmo.set_active(true);
                 subgizmo.set_focused(true);
-                result = subgizmo.update(pointer_ray);
+
+                // The subgizmo keeps the state from when it was picked,
+                // so the first update is measured from where the drag was started.
+                let mut snap_steps = 0;
+                if drag_threshold_exceeded {
+                    result = subgizmo.update(pointer_ray);
+                    snap_steps = subgizmo.take_snap_steps();
+                }
+                let (mode, _, _) = subgizmo.handle();
+
+                if snap_steps != 0 {
+                    if let Some(callback) = &self.snap_step_callback {
+                        callback.call(mode, snap_steps);
+                    }
+                }
+
+                if let Some(subgizmo_result) = result {
+                    result = Some(self.snap_to_targets(subgizmo_result));
+                }
+
+                if self.config.skip_unchanged_results {
+                    result = result.filter(|result| !result.is_unchanged());
+                }
+
+                if let Some(subgizmo_result) = result {
+                    let phase = if self.last_result.is_none() {
+                        GizmoPhase::Begin
+                    } else {
+                        GizmoPhase::Update
+                    };
+
+                    result = Some(subgizmo_result.with_phase(phase));
+                    self.last_result = result;
+                }
             } else {
                 subgizmo.set_active(false);
                 subgizmo.set_focused(false);
                 self.active_subgizmo_id = None;
+
+                result = self.last_result.take().map(GizmoResult::into_end);
+            }
+        }
+
+        // Scrolling over a scale subgizmo may scale the targets without dragging.
+        if result.is_none()
+            && self.config.wheel_scale
+            && interaction.scroll_delta != 0.0
+            && self.active_subgizmo_id.is_none()
+            && cursor_in_viewport
+        {
+            let factor =
+                1.0 + interaction.scroll_delta as f64 * self.config.wheel_scale_sensitivity;
+            result = self
+                .pick_subgizmo(pointer_ray)
+                .and_then(|subgizmo| subgizmo.scroll(factor));
+
+            if result.is_some() {
+                self.target_start_transforms = targets.to_vec();
+                self.gizmo_start_transform = self.config.as_transform();
+                self.interaction_id += 1;
             }
         }
 
+        for subgizmo in &mut self.subgizmos {
+            subgizmo.update_highlight(interaction.delta_time);
+        }
+
         let Some(result) = result else {
             // No interaction, no result.
 
@@ -169,7 +527,112 @@ impl Gizmo {
             return None;
         };
 
+        let result = result.with_interaction_id(self.interaction_id);
+        self.update_config_with_result(result);
+
+        let updated_targets =
+            self.update_transforms_with_result(result, targets, &self.target_start_transforms);
+
+        Some((result, updated_targets))
+    }
+
+    /// Returns the mode, direction and kind of the subgizmo under the given cursor position.
+    ///
+    /// Unlike [`Gizmo::update`], this does not modify the gizmo or start a drag,
+    /// so it can be called at any time, e.g. for showing tooltips.
+    /// An in-progress drag is not affected. Uses the configuration of the latest update.
+    pub fn hovered(
+        &self,
+        cursor_pos: (f32, f32),
+    ) -> Option<(GizmoMode, GizmoDirection, TransformKind)> {
+        let cursor_pos = Pos2::from(cursor_pos);
+
+        if !self.config.is_valid()
+            || !self.config.viewport.contains(cursor_pos)
+            || self.is_behind_camera()
+        {
+            return None;
+        }
+
+        let pointer_ray = self.pointer_ray(cursor_pos);
+
+        // Picking may store state in the subgizmos, so pick from copies of them.
+        // The copies are made one at a time, without allocating.
+        pick_subgizmo(
+            self.subgizmos.iter().cloned(),
+            self.config.mode_override.is_some(),
+            self.sticky_subgizmo_id(),
+            pointer_ray,
+        )
+        .map(|subgizmo| subgizmo.handle())
+    }
+
+    /// Translates the targets by a number of snap distance steps along the given direction.
+    ///
+    /// This can be used for moving the targets with a keyboard, for example.
+    /// [`GizmoConfig::snap_distance`] is used as the step size, even if snapping is disabled.
+    /// The direction is aligned to the target rotation when local orientation is used.
+    ///
+    /// Since the nudge is a complete interaction by itself, the result has [`GizmoPhase::End`].
+    /// The translation is constrained by [`GizmoConfig::locked_axes`],
+    /// [`GizmoConfig::translation_bounds`] and [`GizmoConfig::translation_rail`].
+    ///
+    /// Returns [`None`] if the gizmo is currently being dragged, if the direction is
+    /// [`GizmoDirection::Custom`], disabled or locked, or if the snap distance is not
+    /// a positive finite number.
+    pub fn nudge(
+        &mut self,
+        direction: GizmoDirection,
+        steps: i32,
+        targets: &[Transform],
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        if self.active_subgizmo_id.is_some()
+            || direction == GizmoDirection::Custom
+            || self.config.disabled_directions.contains(direction)
+            || self.config.locked_axes.contains(direction)
+            || !valid_snap_interval(self.config.snap_distance as f64)
+        {
+            return None;
+        }
+
+        self.config.update_for_targets(targets);
+
+        // The nudge is constrained like a drag would be, in world space.
+        let start = self.config.translation;
+        let step = gizmo_normal(&self.config, direction) * self.config.snap_distance as f64;
+        let end = constrain_translation(&self.config, start, start + step * steps as f64);
+
+        let mut delta = end - start;
+        if self.config.local_space() {
+            // Translation deltas are expected in local space.
+            delta = self.config.rotation.inverse() * delta;
+        }
+
+        let mode = match direction {
+            GizmoDirection::X => GizmoMode::TranslateX,
+            GizmoDirection::Y => GizmoMode::TranslateY,
+            GizmoDirection::Z => GizmoMode::TranslateZ,
+            GizmoDirection::View => GizmoMode::TranslateView,
+            GizmoDirection::Custom => unreachable!("custom directions are not nudged"),
+        };
+
+        self.interaction_id += 1;
+        let result = GizmoResult::Translation {
+            delta: delta.into(),
+            total: delta.into(),
+            mode,
+            direction,
+            kind: TransformKind::Axis,
+            custom_axis: None,
+            phase: GizmoPhase::End,
+            interaction_id: self.interaction_id,
+        };
+
+        self.target_start_transforms = targets.to_vec();
+        self.gizmo_start_transform = self.config.as_transform();
+
         self.update_config_with_result(result);
+        self.refresh_draw_cache();
 
         let updated_targets =
             self.update_transforms_with_result(result, targets, &self.target_start_transforms);
@@ -177,24 +640,390 @@ impl Gizmo {
         Some((result, updated_targets))
     }
 
+    /// Cancels the active drag, e.g. when escape is pressed during a drag.
+    ///
+    /// The returned targets are the transforms from when the drag was started. The result has
+    /// [`GizmoPhase::End`], with deltas that undo the total transformation of the drag, and
+    /// identity totals. The gizmo is idle afterwards, until a new drag is started.
+    ///
+    /// Returns [`None`] if the gizmo is not being dragged, or the drag has not transformed
+    /// the targets yet.
+    pub fn cancel_drag(&mut self) -> Option<(GizmoResult, Vec<Transform>)> {
+        let subgizmo = self.active_subgizmo_mut()?;
+        subgizmo.set_active(false);
+        subgizmo.set_focused(false);
+        self.active_subgizmo_id = None;
+
+        self.config.update_transform(self.gizmo_start_transform);
+        for subgizmo in &mut self.subgizmos {
+            subgizmo.update_config(self.config);
+        }
+        self.refresh_draw_cache();
+
+        let result = self
+            .last_result
+            .take()?
+            .into_cancel()
+            .with_interaction_id(self.interaction_id);
+
+        Some((result, self.target_start_transforms.clone()))
+    }
+
     /// Return all the necessary data to draw the latest gizmo interaction.
     ///
     /// The gizmo draw data consists of vertices in viewport coordinates.
-    pub fn draw(&self) -> GizmoDrawData {
-        if !self.config.viewport.is_finite() {
-            return GizmoDrawData::default();
+    /// Nothing is drawn if the gizmo is behind the camera.
+    ///
+    /// The draw data is shared with a cache, and only rebuilt when something affecting
+    /// the drawing has changed, so drawing an unchanged gizmo every frame is cheap.
+    pub fn draw(&self) -> Arc<GizmoDrawData> {
+        if !self.config.is_valid() || self.is_behind_camera() {
+            return Arc::default();
+        }
+
+        let mut draw_data = Arc::clone(
+            self.draw_cache
+                .get_or_init(|| Arc::new(self.build_draw_data())),
+        );
+
+        if let Some(depth_test) = &self.depth_test {
+            // Occlusion depends on the scene, so a copy of the cached data is dimmed.
+            self.dim_occluded(Arc::make_mut(&mut draw_data), depth_test);
         }
 
+        draw_data
+    }
+
+    fn build_draw_data(&self) -> GizmoDrawData {
         let mut draw_data = GizmoDrawData::default();
         for subgizmo in &self.subgizmos {
-            if self.active_subgizmo_id.is_none() || subgizmo.is_active() {
-                draw_data += subgizmo.draw();
+            if self.active_subgizmo_id.is_some() && !subgizmo.is_active() {
+                continue;
             }
+
+            let mut subgizmo_draw_data = subgizmo.draw();
+            if subgizmo.is_disabled() {
+                let alpha = self.config.visuals.disabled_alpha;
+                for color in &mut subgizmo_draw_data.colors {
+                    for component in color {
+                        *component *= alpha;
+                    }
+                }
+            }
+            draw_data += subgizmo_draw_data;
+        }
+
+        if let Some((start, end)) = self.config.translation_rail {
+            draw_data += draw_line_segment(
+                &self.config,
+                self.config.visuals.s_color,
+                start.into(),
+                end.into(),
+            );
+        }
+
+        if self.config.visuals.draw_origin {
+            draw_data += draw_circle(
+                &self.config,
+                self.config.visuals.s_color,
+                (self.config.scale_factor * self.config.visuals.stroke_width) as f64,
+                true,
+            );
         }
 
         draw_data
     }
 
+    /// Whether the gizmo origin is behind the camera. The gizmo cannot be
+    /// projected to the viewport then, so it is neither drawn nor picked.
+    fn is_behind_camera(&self) -> bool {
+        let clip_pos = self.config.view_projection * DVec4::from((self.config.translation, 1.0));
+        clip_pos.w < 1e-10
+    }
+
+    /// Clears the draw data cached from the previous [`Gizmo::draw`] call.
+    ///
+    /// The cache is invalidated automatically whenever anything affecting the drawing changes,
+    /// so this is only needed for freeing the memory.
+    pub fn clear_cache(&mut self) {
+        self.draw_cache = OnceLock::new();
+        self.draw_key = None;
+    }
+
+    /// Inputs that affect drawing the gizmo in its current state
+    fn draw_key(&self) -> DrawKey {
+        let build_hasher = ahash::RandomState::with_seeds(1, 2, 3, 4);
+
+        let mut hasher = build_hasher.build_hasher();
+        for matrix in [self.config.view_projection, self.config.model_matrix] {
+            for value in matrix.to_cols_array() {
+                value.to_bits().hash(&mut hasher);
+            }
+        }
+        let viewport = self.config.viewport;
+        for value in [
+            viewport.min.x,
+            viewport.min.y,
+            viewport.max.x,
+            viewport.max.y,
+            self.config.scale_factor,
+            self.config.pixels_per_point(),
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
+        (self.config.local_space(), self.config.snapping()).hash(&mut hasher);
+        let transform_hash = hasher.finish();
+
+        let mut hasher = build_hasher.build_hasher();
+        for subgizmo in &self.subgizmos {
+            let (highlight, opacity) = subgizmo.draw_state();
+            subgizmo.id().hash(&mut hasher);
+            subgizmo.is_active().hash(&mut hasher);
+            highlight.to_bits().hash(&mut hasher);
+            opacity.to_bits().hash(&mut hasher);
+        }
+        let subgizmo_hash = hasher.finish();
+
+        DrawKey {
+            modes: self.config.modes,
+            gizmo_size: self.config.gizmo_size.to_bits(),
+            transform_hash,
+            active_subgizmo_id: self.active_subgizmo_id,
+            subgizmo_hash,
+            generation: self.draw_generation,
+        }
+    }
+
+    /// Drops the cached draw data if anything affecting the drawing has changed.
+    fn refresh_draw_cache(&mut self) {
+        let key = self.draw_key();
+        if self.draw_key != Some(key) {
+            self.draw_cache = OnceLock::new();
+            self.draw_key = Some(key);
+        }
+    }
+
+    /// Sets a depth test used for dimming the parts of the gizmo that are behind
+    /// the scene geometry. Occluded parts are drawn with [`crate::GizmoVisuals::occluded_alpha`].
+    pub fn set_depth_test(&mut self, depth_test: Option<DepthTest>) {
+        self.depth_test = depth_test;
+    }
+
+    /// Sets a callback that is called whenever a snapped translation, rotation or scale
+    /// crosses snapping steps, e.g. for playing a sound or triggering haptic feedback.
+    /// It is called at most once per update, with the signed number of steps crossed.
+    pub fn set_snap_step_callback(&mut self, callback: Option<SnapStepCallback>) {
+        self.snap_step_callback = callback;
+    }
+
+    fn dim_occluded(&self, draw_data: &mut GizmoDrawData, depth_test: &DepthTest) {
+        let Some(increasing) = self.depth_increasing() else {
+            return;
+        };
+
+        let alpha = self.config.visuals.occluded_alpha;
+
+        for ((vertex, color), depth) in draw_data
+            .vertices
+            .iter()
+            .zip(&mut draw_data.colors)
+            .zip(&draw_data.depths)
+        {
+            let pos = Pos2::new(vertex[0], vertex[1]);
+            if depth_test.is_occluded(pos, *depth, increasing) {
+                for component in color {
+                    *component *= alpha;
+                }
+            }
+        }
+    }
+
+    /// Whether the depth increases with distance from the camera, depending on the projection.
+    fn depth_increasing(&self) -> Option<bool> {
+        let mvp = self.config.view_projection;
+        let center = world_to_screen_depth(self.config.viewport, mvp, self.config.translation);
+        let behind = world_to_screen_depth(
+            self.config.viewport,
+            mvp,
+            self.config.translation - self.config.eye_to_model_dir,
+        );
+        let ((_, center), (_, behind)) = center.zip(behind)?;
+        Some(behind > center)
+    }
+
+    /// Labels of the X, Y and Z axes, placed just beyond the outermost handle of each axis.
+    ///
+    /// Empty unless [`crate::GizmoVisuals::show_axis_labels`] is set. Axes that have no
+    /// visible handles, e.g. because their direction is disabled, have no label.
+    /// With a depth test, occluded labels are dimmed like the handles.
+    pub fn axis_labels(&self) -> Vec<AxisLabel> {
+        if !self.config.visuals.show_axis_labels
+            || !self.config.is_valid()
+            || self.is_behind_camera()
+        {
+            return Vec::new();
+        }
+
+        let increasing = self.depth_increasing();
+
+        [
+            (GizmoDirection::X, "X"),
+            (GizmoDirection::Y, "Y"),
+            (GizmoDirection::Z, "Z"),
+        ]
+        .into_iter()
+        .filter_map(|(direction, text)| {
+            // During a drag, only the active subgizmo is drawn.
+            let size_mul = self
+                .subgizmos
+                .iter()
+                .filter(|subgizmo| {
+                    self.active_subgizmo_id
+                        .map_or(!subgizmo.is_disabled(), |id| subgizmo.id() == id)
+                })
+                .filter_map(|subgizmo| {
+                    let (mode, subgizmo_direction, kind) = subgizmo.handle();
+                    (subgizmo_direction == direction && kind == TransformKind::Axis)
+                        .then(|| self.config.visuals.size_mul(mode))
+                })
+                .reduce(f32::max)?;
+
+            let distance = self.config.scale_factor * self.config.gizmo_size * size_mul;
+            let point = self.config.translation
+                + gizmo_normal(&self.config, direction) * (distance * AXIS_LABEL_OFFSET) as f64;
+            let (position, depth) =
+                world_to_screen_depth(self.config.viewport, self.config.view_projection, point)?;
+
+            let mut color = gizmo_color(&self.config, 0.0, direction);
+            if let (Some(depth_test), Some(increasing)) = (&self.depth_test, increasing) {
+                if depth_test.is_occluded(position, depth, increasing) {
+                    color = color.linear_multiply(self.config.visuals.occluded_alpha);
+                }
+            }
+
+            Some(AxisLabel {
+                direction,
+                text,
+                position,
+                color,
+            })
+        })
+        .collect()
+    }
+
+    /// Bounding rectangle of all the gizmo handles in viewport space.
+    ///
+    /// Uses the configuration and targets of the latest update, including the current
+    /// gizmo size and enabled modes. All handles are included, even during a drag.
+    /// Returns [`Rect::NOTHING`] if nothing would be drawn.
+    pub fn screen_rect(&self) -> Rect {
+        if !self.config.is_valid() {
+            return Rect::NOTHING;
+        }
+
+        let mut rect = Rect::NOTHING;
+        for subgizmo in &self.subgizmos {
+            for vertex in subgizmo.draw().vertices {
+                rect.extend_with(Pos2::from(vertex));
+            }
+        }
+
+        rect
+    }
+
+    /// Projects a point in world space to the viewport, e.g. for placing custom labels.
+    ///
+    /// Uses the camera matrices and viewport of the latest configuration.
+    /// Returns [`None`] if the point is behind the camera.
+    pub fn world_to_screen(&self, point: impl Into<mint::Vector3<f64>>) -> Option<Pos2> {
+        if !self.config.is_valid() {
+            return None;
+        }
+
+        world_to_screen(
+            self.config.viewport,
+            self.config.view_projection,
+            DVec3::from(point.into()),
+        )
+    }
+
+    /// Finds the point in world space under the given viewport position, on the given plane.
+    ///
+    /// This is the inverse of [`Gizmo::world_to_screen`] for points on the plane.
+    /// Returns [`None`] if the plane is not visible at the position,
+    /// e.g. because it is parallel to the view direction.
+    pub fn screen_to_world_on_plane(
+        &self,
+        screen_pos: Pos2,
+        plane_normal: impl Into<mint::Vector3<f64>>,
+        plane_origin: impl Into<mint::Vector3<f64>>,
+    ) -> Option<mint::Vector3<f64>> {
+        if !self.config.is_valid() {
+            return None;
+        }
+
+        let ray = self.pointer_ray(screen_pos);
+
+        ray_to_plane(
+            DVec3::from(plane_normal.into()),
+            DVec3::from(plane_origin.into()),
+            ray.origin,
+            ray.direction,
+        )
+        .map(|(_, point)| point.into())
+    }
+
+    /// Describes the latest change of a result as a transformation relative to the gizmo pivot.
+    ///
+    /// This can be used for applying the result to objects that were not given as targets,
+    /// e.g. the other objects of a multi-selection. Call this right after [`Gizmo::update`].
+    /// Local axes are those of the gizmo, i.e. of the last target.
+    /// Returns [`None`] for results that cannot be expressed as a relative change.
+    pub fn relative_transform(&self, result: &GizmoResult) -> Option<RelativeTransform> {
+        let local_rotation = if self.config.local_space() {
+            self.config.rotation
+        } else {
+            DQuat::IDENTITY
+        };
+
+        let mut relative = RelativeTransform {
+            pivot: Some(self.config.translation.into()),
+            ..Default::default()
+        };
+
+        match *result {
+            GizmoResult::Rotation {
+                axis,
+                delta,
+                is_view_axis,
+                ..
+            } => {
+                let axis = if is_view_axis {
+                    DVec3::from(axis)
+                } else {
+                    (local_rotation * DVec3::from(axis)).normalize()
+                };
+                relative.rotation = DQuat::from_axis_angle(axis, delta).into();
+            }
+            GizmoResult::Arcball { delta, .. } => relative.rotation = delta,
+            GizmoResult::Translation { delta, .. } => {
+                relative.translation = (local_rotation * DVec3::from(delta)).into();
+            }
+            GizmoResult::Scale { delta, .. } => relative.scale = delta,
+            GizmoResult::TranslationReset { .. } => return None,
+        }
+
+        if self.config.pivot_point == TransformPivotPoint::IndividualOrigins {
+            relative.pivot = None;
+        }
+
+        if self.config.rotation_pivot.is_some() {
+            relative.pivot = self.config.rotation_pivot;
+        }
+
+        Some(relative)
+    }
+
     fn active_subgizmo_mut(&mut self) -> Option<&mut SubGizmo> {
         self.active_subgizmo_id.and_then(|id| {
             self.subgizmos
@@ -216,18 +1045,21 @@ impl Gizmo {
                 GizmoResult::Rotation {
                     axis,
                     delta,
-                    total: _,
                     is_view_axis,
+                    ..
                 } => self.update_rotation(transform, axis, delta, is_view_axis),
-                GizmoResult::Translation { delta, total: _ } => {
+                GizmoResult::Translation { delta, .. } => {
                     self.update_translation(delta, transform, start_transform)
                 }
-                GizmoResult::Scale { total } => {
+                GizmoResult::Scale { total, .. } => {
                     self.update_scale(transform, start_transform, total)
                 }
-                GizmoResult::Arcball { delta, total: _ } => {
+                GizmoResult::Arcball { delta, .. } => {
                     self.update_rotation_quat(transform, delta.into())
                 }
+                GizmoResult::TranslationReset { axis, .. } => {
+                    self.reset_translation(transform, axis)
+                }
             })
             .collect()
     }
@@ -252,13 +1084,18 @@ impl Gizmo {
     }
 
     fn update_rotation_quat(&self, transform: &Transform, delta: DQuat) -> Transform {
-        let translation = match self.config.pivot_point {
-            TransformPivotPoint::MedianPoint => (self.config.translation
-                + delta * (DVec3::from(transform.translation) - self.config.translation))
-                .into(),
-            TransformPivotPoint::IndividualOrigins => transform.translation,
+        let pivot = match (self.config.rotation_pivot, self.config.pivot_point) {
+            (Some(pivot), _) => Some(DVec3::from(pivot)),
+            (None, TransformPivotPoint::MedianPoint | TransformPivotPoint::ActiveTarget) => {
+                Some(self.config.translation)
+            }
+            (None, TransformPivotPoint::IndividualOrigins) => None,
         };
 
+        let translation = pivot.map_or(transform.translation, |pivot| {
+            (pivot + delta * (DVec3::from(transform.translation) - pivot)).into()
+        });
+
         let new_rotation = (delta * DQuat::from(transform.rotation)).normalize();
 
         Transform {
@@ -286,32 +1123,143 @@ impl Gizmo {
         }
     }
 
+    fn reset_translation(&self, transform: &Transform, axis: mint::Vector3<f64>) -> Transform {
+        let axis = match self.config.orientation() {
+            GizmoOrientation::Global => DVec3::from(axis),
+            GizmoOrientation::Local => DQuat::from(transform.rotation) * DVec3::from(axis),
+        };
+
+        let translation = DVec3::from(transform.translation);
+
+        Transform {
+            translation: (translation - axis * translation.dot(axis)).into(),
+            ..*transform
+        }
+    }
+
     fn update_scale(
         &self,
         transform: &Transform,
         start_transform: &Transform,
         scale: mint::Vector3<f64>,
     ) -> Transform {
+        let start_scale = DVec3::from(start_transform.scale);
+        let start_rotation = DQuat::from(start_transform.rotation);
+        let scale = DVec3::from(scale);
+
         let new_scale = match self.config.orientation() {
             GizmoOrientation::Global => {
-                let scaled_transform_mat = DMat4::from_scale(scale.into())
+                let scaled_transform_mat = DMat4::from_scale(scale)
                     * DMat4::from_scale_rotation_translation(
-                        DVec3::from(start_transform.scale),
-                        DQuat::from(start_transform.rotation),
+                        start_scale,
+                        start_rotation,
                         DVec3::from(start_transform.translation),
                     );
-                let (scale, _, _) = scaled_transform_mat.to_scale_rotation_translation();
-                scale
+                let (new_scale, _, _) = scaled_transform_mat.to_scale_rotation_translation();
+                // The decomposition moves the sign of a mirrored matrix to the X axis, but
+                // the rotation is kept from the start transform. Keep the signs on the axes
+                // that were negative, so the rotation stays consistent with the scale.
+                new_scale.abs() * mirrored_scale_signs(start_scale, start_rotation, scale)
             }
-            GizmoOrientation::Local => DVec3::from(start_transform.scale) * DVec3::from(scale),
+            GizmoOrientation::Local => start_scale * scale,
+        };
+
+        let translation = match self.config.object_bounds {
+            Some((min, _)) if self.config.anchored_scale => {
+                // The scale handles are on the positive side of each axis,
+                // so keep the minimum corner of the bounds in place.
+                let anchor = DVec3::from(min);
+                let anchor_offset = match self.config.orientation() {
+                    GizmoOrientation::Global => {
+                        (start_rotation * (start_scale * anchor)) * (DVec3::ONE - scale)
+                    }
+                    GizmoOrientation::Local => {
+                        start_rotation * (start_scale * anchor * (DVec3::ONE - scale))
+                    }
+                };
+                (DVec3::from(start_transform.translation) + anchor_offset).into()
+            }
+            _ => transform.translation,
         };
 
         Transform {
             scale: new_scale.into(),
+            translation,
             ..*transform
         }
     }
 
+    /// Snaps a translation result onto the nearest snap target, if any are close enough.
+    fn snap_to_targets(&self, result: GizmoResult) -> GizmoResult {
+        let GizmoResult::Translation {
+            mode,
+            direction,
+            kind,
+            custom_axis,
+            phase,
+            total,
+            interaction_id,
+            ..
+        } = result
+        else {
+            return result;
+        };
+
+        if self.snap_targets.is_empty() || !self.config.snapping() {
+            return result;
+        }
+
+        let start_rotation = DQuat::from(self.gizmo_start_transform.rotation);
+        let start_translation = DVec3::from(self.gizmo_start_transform.translation);
+
+        let to_world = |local: DVec3| {
+            if self.config.local_space() {
+                start_rotation * local
+            } else {
+                local
+            }
+        };
+
+        let mut total = DVec3::from(total);
+        let translation = start_translation + to_world(total);
+
+        let nearest = self
+            .snap_targets
+            .iter()
+            .filter(|&&target| {
+                constrain_translation(&self.config, start_translation, target)
+                    .abs_diff_eq(target, 1e-9)
+            })
+            .map(|target| (target.distance(translation), *target))
+            .filter(|(distance, _)| *distance <= self.config.snap_distance as f64)
+            .min_by(|(first, _), (second, _)| first.total_cmp(second));
+
+        if let Some((_, target)) = nearest {
+            total = target - start_translation;
+            if self.config.local_space() {
+                total = start_rotation.inverse() * total;
+            }
+        }
+
+        // Snapping may move the gizmo differently from the subgizmo,
+        // so the delta is calculated from the previous result.
+        let last_total = match self.last_result {
+            Some(GizmoResult::Translation { total, .. }) => DVec3::from(total),
+            _ => DVec3::ZERO,
+        };
+
+        GizmoResult::Translation {
+            delta: (total - last_total).into(),
+            total: total.into(),
+            mode,
+            direction,
+            kind,
+            custom_axis,
+            phase,
+            interaction_id,
+        }
+    }
+
     fn update_config_with_result(&mut self, result: GizmoResult) {
         let new_config_transform = self.update_transforms_with_result(
             result,
@@ -324,31 +1272,41 @@ impl Gizmo {
 
     /// Picks the subgizmo that is closest to the given world space ray.
     fn pick_subgizmo(&mut self, ray: Ray) -> Option<&mut SubGizmo> {
-        // If mode is overridden, assume we only have that mode, and choose it.
-        if self.config.mode_override.is_some() {
-            return self.subgizmos.first_mut().map(|subgizmo| {
-                subgizmo.pick(ray);
-
-                subgizmo
-            });
-        }
+        let preferred_id = self.sticky_subgizmo_id();
+        pick_subgizmo(
+            &mut self.subgizmos,
+            self.config.mode_override.is_some(),
+            preferred_id,
+            ray,
+        )
+    }
 
-        self.subgizmos
-            .iter_mut()
-            .filter_map(|subgizmo| subgizmo.pick(ray).map(|t| (t, subgizmo)))
-            .min_by(|(first, _), (second, _)| {
-                first
-                    .partial_cmp(second)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-            .map(|(_, subgizmo)| subgizmo)
+    /// Subgizmo that is preferred in picking, if handles are sticky
+    fn sticky_subgizmo_id(&self) -> Option<u64> {
+        self.last_active_subgizmo_id
+            .filter(|_| self.config.sticky_handles)
     }
 
     /// Get all modes that are currently enabled
     fn enabled_modes(&self) -> EnumSet<GizmoMode> {
-        self.config
+        let modes = self
+            .config
             .mode_override
-            .map_or(self.config.modes, EnumSet::only)
+            .map_or(self.config.modes, EnumSet::only);
+
+        let Some(axis) = self.config.constrained_rotation_axis else {
+            return modes;
+        };
+
+        // Only keep the rotation ring of the constrained axis
+        modes
+            .iter()
+            .filter(|mode| match mode.kind() {
+                GizmoModeKind::Rotate => mode.axes() == EnumSet::only(axis),
+                GizmoModeKind::Arcball => false,
+                GizmoModeKind::Translate | GizmoModeKind::Scale => true,
+            })
+            .collect()
     }
 
     /// Adds rotation subgizmos
@@ -413,7 +1371,9 @@ impl Gizmo {
     fn add_translation(&mut self) {
         let modes = self.enabled_modes();
 
-        if modes.contains(GizmoMode::TranslateX) {
+        if modes.contains(GizmoMode::TranslateX)
+            && !self.translation_locked(GizmoDirection::X, TransformKind::Axis)
+        {
             self.subgizmos.push(
                 TranslationSubGizmo::new(
                     self.config,
@@ -421,13 +1381,16 @@ impl Gizmo {
                         mode: GizmoMode::TranslateX,
                         direction: GizmoDirection::X,
                         transform_kind: TransformKind::Axis,
+                        custom_axis: None,
                     },
                 )
                 .into(),
             );
         }
 
-        if modes.contains(GizmoMode::TranslateY) {
+        if modes.contains(GizmoMode::TranslateY)
+            && !self.translation_locked(GizmoDirection::Y, TransformKind::Axis)
+        {
             self.subgizmos.push(
                 TranslationSubGizmo::new(
                     self.config,
@@ -435,13 +1398,16 @@ impl Gizmo {
                         mode: GizmoMode::TranslateY,
                         direction: GizmoDirection::Y,
                         transform_kind: TransformKind::Axis,
+                        custom_axis: None,
                     },
                 )
                 .into(),
             );
         }
 
-        if modes.contains(GizmoMode::TranslateZ) {
+        if modes.contains(GizmoMode::TranslateZ)
+            && !self.translation_locked(GizmoDirection::Z, TransformKind::Axis)
+        {
             self.subgizmos.push(
                 TranslationSubGizmo::new(
                     self.config,
@@ -449,6 +1415,7 @@ impl Gizmo {
                         mode: GizmoMode::TranslateZ,
                         direction: GizmoDirection::Z,
                         transform_kind: TransformKind::Axis,
+                        custom_axis: None,
                     },
                 )
                 .into(),
@@ -463,13 +1430,16 @@ impl Gizmo {
                         mode: GizmoMode::TranslateView,
                         direction: GizmoDirection::View,
                         transform_kind: TransformKind::Plane,
+                        custom_axis: None,
                     },
                 )
                 .into(),
             );
         }
 
-        if modes.contains(GizmoMode::TranslateXY) {
+        if modes.contains(GizmoMode::TranslateXY)
+            && !self.translation_locked(GizmoDirection::X, TransformKind::Plane)
+        {
             self.subgizmos.push(
                 TranslationSubGizmo::new(
                     self.config,
@@ -477,13 +1447,16 @@ impl Gizmo {
                         mode: GizmoMode::TranslateXY,
                         direction: GizmoDirection::X,
                         transform_kind: TransformKind::Plane,
+                        custom_axis: None,
                     },
                 )
                 .into(),
             );
         }
 
-        if modes.contains(GizmoMode::TranslateXZ) {
+        if modes.contains(GizmoMode::TranslateXZ)
+            && !self.translation_locked(GizmoDirection::Y, TransformKind::Plane)
+        {
             self.subgizmos.push(
                 TranslationSubGizmo::new(
                     self.config,
@@ -491,13 +1464,16 @@ impl Gizmo {
                         mode: GizmoMode::TranslateXZ,
                         direction: GizmoDirection::Y,
                         transform_kind: TransformKind::Plane,
+                        custom_axis: None,
                     },
                 )
                 .into(),
             );
         }
 
-        if modes.contains(GizmoMode::TranslateYZ) {
+        if modes.contains(GizmoMode::TranslateYZ)
+            && !self.translation_locked(GizmoDirection::Z, TransformKind::Plane)
+        {
             self.subgizmos.push(
                 TranslationSubGizmo::new(
                     self.config,
@@ -505,11 +1481,47 @@ impl Gizmo {
                         mode: GizmoMode::TranslateYZ,
                         direction: GizmoDirection::Z,
                         transform_kind: TransformKind::Plane,
+                        custom_axis: None,
                     },
                 )
                 .into(),
             );
         }
+
+        if modes.contains(GizmoMode::TranslateCustom) {
+            let axes = self.custom_translation_axes.iter().enumerate();
+            for (index, axis) in axes.filter(|(_, axis)| axis.length_squared() > 0.0) {
+                self.subgizmos.push(
+                    TranslationSubGizmo::new(
+                        self.config,
+                        TranslationParams {
+                            mode: GizmoMode::TranslateCustom,
+                            direction: GizmoDirection::Custom,
+                            transform_kind: TransformKind::Axis,
+                            custom_axis: Some(CustomAxis {
+                                index,
+                                axis: axis.normalize(),
+                            }),
+                        },
+                    )
+                    .into(),
+                );
+            }
+        }
+    }
+
+    /// Whether the translation subgizmo would move along any of the locked axes
+    fn translation_locked(&self, direction: GizmoDirection, transform_kind: TransformKind) -> bool {
+        let locked_axes = self.config.locked_axes;
+
+        match (transform_kind, direction) {
+            // Locked components of custom axes are removed while dragging
+            (_, GizmoDirection::View | GizmoDirection::Custom) => false,
+            (TransformKind::Axis, _) => locked_axes.contains(direction),
+            (TransformKind::Plane, _) => locked_axes.iter().any(|axis| {
+                axis != direction && !matches!(axis, GizmoDirection::View | GizmoDirection::Custom)
+            }),
+        }
     }
 
     /// Adds scale subgizmos
@@ -524,6 +1536,7 @@ impl Gizmo {
                         mode: GizmoMode::ScaleX,
                         direction: GizmoDirection::X,
                         transform_kind: TransformKind::Axis,
+                        centered: false,
                     },
                 )
                 .into(),
@@ -538,6 +1551,7 @@ impl Gizmo {
                         mode: GizmoMode::ScaleY,
                         direction: GizmoDirection::Y,
                         transform_kind: TransformKind::Axis,
+                        centered: false,
                     },
                 )
                 .into(),
@@ -552,6 +1566,7 @@ impl Gizmo {
                         mode: GizmoMode::ScaleZ,
                         direction: GizmoDirection::Z,
                         transform_kind: TransformKind::Axis,
+                        centered: false,
                     },
                 )
                 .into(),
@@ -566,6 +1581,23 @@ impl Gizmo {
                         mode: GizmoMode::ScaleUniform,
                         direction: GizmoDirection::View,
                         transform_kind: TransformKind::Plane,
+                        centered: false,
+                    },
+                )
+                .into(),
+            );
+        }
+
+        if modes.contains(GizmoMode::ScaleUniform) && !modes.contains(GizmoMode::TranslateView) {
+            // The center of the gizmo is free unless it is used by view plane translation.
+            self.subgizmos.push(
+                ScaleSubGizmo::new(
+                    self.config,
+                    ScaleParams {
+                        mode: GizmoMode::ScaleUniform,
+                        direction: GizmoDirection::View,
+                        transform_kind: TransformKind::Plane,
+                        centered: true,
                     },
                 )
                 .into(),
@@ -580,6 +1612,7 @@ impl Gizmo {
                         mode: GizmoMode::ScaleXY,
                         direction: GizmoDirection::X,
                         transform_kind: TransformKind::Plane,
+                        centered: false,
                     },
                 )
                 .into(),
@@ -594,6 +1627,7 @@ impl Gizmo {
                         mode: GizmoMode::ScaleXZ,
                         direction: GizmoDirection::Y,
                         transform_kind: TransformKind::Plane,
+                        centered: false,
                     },
                 )
                 .into(),
@@ -608,6 +1642,7 @@ impl Gizmo {
                         mode: GizmoMode::ScaleYZ,
                         direction: GizmoDirection::Z,
                         transform_kind: TransformKind::Plane,
+                        centered: false,
                     },
                 )
                 .into(),
@@ -617,18 +1652,80 @@ impl Gizmo {
 
     /// Calculate a world space ray from given screen space position
     fn pointer_ray(&self, screen_pos: Pos2) -> Ray {
-        let mat = self.config.view_projection.inverse();
-        let origin = screen_to_world(self.config.viewport, mat, screen_pos, -1.0);
-        let target = screen_to_world(self.config.viewport, mat, screen_pos, 1.0);
+        Ray::from_screen(
+            self.config.view_matrix.into(),
+            self.config.projection_matrix.into(),
+            self.config.viewport,
+            screen_pos,
+        )
+    }
+}
 
-        let direction = target.sub(origin).normalize();
+/// Finds the subgizmo closest to the camera that is picked by the given ray.
+/// The subgizmos may be given either by reference or by value.
+fn pick_subgizmo<S: BorrowMut<SubGizmo>>(
+    subgizmos: impl IntoIterator<Item = S>,
+    mode_override: bool,
+    preferred_id: Option<u64>,
+    ray: Ray,
+) -> Option<S> {
+    let mut subgizmos = subgizmos.into_iter();
+
+    // If mode is overridden, assume we only have that mode, and choose it.
+    if mode_override {
+        let mut subgizmo = subgizmos.next()?;
+        let picked: &mut SubGizmo = subgizmo.borrow_mut();
+        picked.pick(ray);
+
+        return (!picked.is_disabled()).then_some(subgizmo);
+    }
 
-        Ray {
-            screen_pos,
-            origin,
-            direction,
+    // The preferred subgizmo wins if it was picked. Otherwise the subgizmo closest along
+    // the ray wins. On ties, the subgizmo that was added first wins,
+    // so rotation comes before translation and scale, and X comes before Y and Z.
+    subgizmos
+        .enumerate()
+        .filter_map(|(index, mut subgizmo)| {
+            let picked: &mut SubGizmo = subgizmo.borrow_mut();
+            // Disabled subgizmos are picked too, since picking updates their visibility.
+            let t = picked
+                .pick(ray)
+                .filter(|t| t.is_finite() && !picked.is_disabled())?;
+            let preferred = preferred_id == Some(picked.id());
+
+            Some((!preferred, t, index, subgizmo))
+        })
+        .min_by(
+            |(first_other, first_t, first_index, _), (second_other, second_t, second_index, _)| {
+                first_other
+                    .cmp(second_other)
+                    .then(first_t.total_cmp(second_t))
+                    .then(first_index.cmp(second_index))
+            },
+        )
+        .map(|(_, _, _, subgizmo)| subgizmo)
+}
+
+/// Signs of the local scale axes after scaling a target by a global scale.
+/// Negative global scale mirrors the local axis closest to the scaled world axis.
+fn mirrored_scale_signs(start_scale: DVec3, start_rotation: DQuat, global_scale: DVec3) -> DVec3 {
+    let mut signs = start_scale.signum();
+    for (world_axis, factor) in DVec3::AXES.into_iter().zip(global_scale.to_array()) {
+        if factor >= 0.0 {
+            continue;
         }
+
+        let local_axis = (start_rotation.inverse() * world_axis).abs();
+        let index = if local_axis.x >= local_axis.y && local_axis.x >= local_axis.z {
+            0
+        } else if local_axis.y >= local_axis.z {
+            1
+        } else {
+            2
+        };
+        signs[index] = -signs[index];
     }
+    signs
 }
 
 /// Information needed for interacting with the gizmo.
@@ -644,39 +1741,316 @@ pub struct GizmoInteraction {
     /// Usually this is set to true whenever the primary mouse
     /// button is being pressed.
     pub dragging: bool,
+    /// If set, overrides [`GizmoConfig::orientation`] for this frame, without changing the config.
+    /// For example, this can be set while a key is held to temporarily switch orientation.
+    /// The override is kept for the duration of a drag that started with it.
+    pub orientation_override: Option<GizmoOrientation>,
+    /// Whether the primary mouse button was double clicked this frame.
+    /// Double clicking a translation axis resets the target
+    /// translation along that axis.
+    pub double_clicked: bool,
+    /// Whether snapping is enabled this frame, in addition to [`GizmoConfig::snapping`].
+    /// For example, this can be set while a modifier key is held.
+    pub snapping: bool,
+    /// Whether precision mode is enabled this frame. In precision mode, the pointer
+    /// movement is multiplied by [`GizmoConfig::precision_sensitivity`] for finer adjustments.
+    pub precision: bool,
+    /// Amount the mouse wheel was scrolled this frame, in points. Positive when scrolling up.
+    /// Scrolling over a scale handle scales the targets if [`GizmoConfig::wheel_scale`] is set.
+    pub scroll_delta: f32,
+    /// Time elapsed since the previous interaction, in seconds.
+    /// Used for animating the highlight of focused subgizmos.
+    /// When zero, the highlight changes instantly.
+    pub delta_time: f32,
 }
 
 /// Result of a gizmo transformation
 #[derive(Debug, Copy, Clone)]
 pub enum GizmoResult {
     Rotation {
-        /// The rotation axis,
+        /// The rotation axis. In local orientation, this is in the target's local space.
+        /// For mirrored targets, the local axes with a negative scale are flipped,
+        /// matching the drawn handles.
         axis: mint::Vector3<f64>,
-        /// The latest rotation angle delta
+        /// The rotation axis in world space
+        world_axis: mint::Vector3<f64>,
+        /// The latest rotation angle delta, in radians.
+        /// Angles are signed, right-handed around the rotation axis.
         delta: f64,
-        /// Total rotation angle of the gizmo interaction
+        /// Total rotation angle of the gizmo interaction, in radians.
+        /// Has the same sign as the deltas, and is not wrapped to a single turn.
+        ///
+        /// Earlier versions reported the total with the opposite sign of the deltas.
+        /// Negate the total to migrate code that relied on it.
         total: f64,
+        /// Signed number of full turns completed during the gizmo interaction
+        revolutions: i32,
         /// Whether we are rotating along the view axis
         is_view_axis: bool,
+        /// Mode of the rotated subgizmo
+        mode: GizmoMode,
+        /// Direction of the rotated subgizmo
+        direction: GizmoDirection,
+        /// Kind of the rotated subgizmo
+        kind: TransformKind,
+        /// Phase of the interaction
+        phase: GizmoPhase,
+        /// Identifier of the interaction, see [`Gizmo::interaction_id`]
+        interaction_id: u64,
     },
     Translation {
         /// The latest translation delta
         delta: mint::Vector3<f64>,
         /// Total translation of the gizmo interaction
         total: mint::Vector3<f64>,
+        /// Mode of the translated subgizmo
+        mode: GizmoMode,
+        /// Direction of the translated subgizmo. For planes, this is the plane normal.
+        direction: GizmoDirection,
+        /// Kind of the translated subgizmo
+        kind: TransformKind,
+        /// Index of the translated axis in [`Gizmo::set_custom_translation_axes`],
+        /// if a custom axis was translated. The direction is then [`GizmoDirection::Custom`].
+        custom_axis: Option<usize>,
+        /// Phase of the interaction
+        phase: GizmoPhase,
+        /// Identifier of the interaction, see [`Gizmo::interaction_id`]
+        interaction_id: u64,
     },
     Scale {
-        /// Total scale of the gizmo interaction
+        /// The latest multiplicative scale delta. Axes whose previous scale was zero
+        /// have a delta of 1.0, since no factor applies to them. Use `total` for those.
+        delta: mint::Vector3<f64>,
+        /// Total multiplicative scale of the gizmo interaction, relative to the
+        /// scale when the drag started. Untouched axes are 1.0. The absolute scale
+        /// of each target can be found from the returned transforms. Negative scale
+        /// of mirrored targets is kept on the same axes in the returned transforms.
         total: mint::Vector3<f64>,
+        /// Mode of the scaled subgizmo
+        mode: GizmoMode,
+        /// Direction of the scaled subgizmo. For planes, this is the plane normal.
+        direction: GizmoDirection,
+        /// Kind of the scaled subgizmo
+        kind: TransformKind,
+        /// Phase of the interaction
+        phase: GizmoPhase,
+        /// Identifier of the interaction, see [`Gizmo::interaction_id`]
+        interaction_id: u64,
     },
     Arcball {
         /// The latest rotation delta
         delta: mint::Quaternion<f64>,
         /// Total rotation of the gizmo interaction
         total: mint::Quaternion<f64>,
+        /// Mode of the rotated subgizmo. This is always [`GizmoMode::Arcball`].
+        mode: GizmoMode,
+        /// Phase of the interaction
+        phase: GizmoPhase,
+        /// Identifier of the interaction, see [`Gizmo::interaction_id`]
+        interaction_id: u64,
+    },
+    /// Translation along an axis was reset to zero by double clicking the axis.
+    /// Unlike other results, this is emitted once instead of every frame of a drag.
+    /// The second click of the double click starts a drag, which is ended by the reset
+    /// without a [`GizmoPhase::End`] result.
+    TranslationReset {
+        /// The axis along which the translation was reset
+        axis: mint::Vector3<f64>,
+        /// Identifier of the interaction, see [`Gizmo::interaction_id`]
+        interaction_id: u64,
+    },
+}
+
+impl GizmoResult {
+    /// Identifier of the interaction that gave the result
+    pub fn interaction_id(&self) -> u64 {
+        match *self {
+            Self::Rotation { interaction_id, .. }
+            | Self::Translation { interaction_id, .. }
+            | Self::Scale { interaction_id, .. }
+            | Self::Arcball { interaction_id, .. }
+            | Self::TranslationReset { interaction_id, .. } => interaction_id,
+        }
+    }
+
+    /// Subgizmos leave the interaction identifier at zero,
+    /// and the gizmo sets it with this before returning the result.
+    pub(crate) fn with_interaction_id(mut self, id: u64) -> Self {
+        match &mut self {
+            Self::Rotation { interaction_id, .. }
+            | Self::Translation { interaction_id, .. }
+            | Self::Scale { interaction_id, .. }
+            | Self::Arcball { interaction_id, .. }
+            | Self::TranslationReset { interaction_id, .. } => *interaction_id = id,
+        }
+        self
+    }
+
+    pub(crate) fn with_phase(mut self, new_phase: GizmoPhase) -> Self {
+        match &mut self {
+            Self::Rotation { phase, .. }
+            | Self::Translation { phase, .. }
+            | Self::Scale { phase, .. }
+            | Self::Arcball { phase, .. } => *phase = new_phase,
+            Self::TranslationReset { .. } => {}
+        }
+        self
+    }
+
+    /// Converts the result to one that cancels the interaction.
+    /// Deltas undo the totals, and totals are reset.
+    pub(crate) fn into_cancel(self) -> Self {
+        let mut result = self.with_phase(GizmoPhase::End);
+        match &mut result {
+            Self::Rotation {
+                delta,
+                total,
+                revolutions,
+                ..
+            } => {
+                *delta = -*total;
+                *total = 0.0;
+                *revolutions = 0;
+            }
+            Self::Translation { delta, total, .. } => {
+                *delta = (-DVec3::from(*total)).into();
+                *total = DVec3::ZERO.into();
+            }
+            Self::Scale { delta, total, .. } => {
+                *delta = DVec3::from(*total).recip().into();
+                *total = DVec3::ONE.into();
+            }
+            Self::Arcball { delta, total, .. } => {
+                *delta = DQuat::from(*total).inverse().into();
+                *total = DQuat::IDENTITY.into();
+            }
+            Self::TranslationReset { .. } => {}
+        }
+        result
+    }
+
+    /// Whether the latest delta of the result leaves the transformation unchanged.
+    pub(crate) fn is_unchanged(&self) -> bool {
+        match *self {
+            Self::Rotation { delta, .. } => delta == 0.0,
+            Self::Translation { delta, .. } => DVec3::from(delta) == DVec3::ZERO,
+            Self::Scale { delta, .. } => DVec3::from(delta) == DVec3::ONE,
+            Self::Arcball { delta, .. } => DQuat::from(delta) == DQuat::IDENTITY,
+            Self::TranslationReset { .. } => false,
+        }
+    }
+
+    /// Converts the result to one that ends the interaction.
+    /// Totals are kept as is, but deltas no longer change the transformation.
+    pub(crate) fn into_end(self) -> Self {
+        let mut result = self.with_phase(GizmoPhase::End);
+        match &mut result {
+            Self::Rotation { delta, .. } => *delta = 0.0,
+            Self::Translation { delta, .. } => *delta = DVec3::ZERO.into(),
+            Self::Scale { delta, .. } => *delta = DVec3::ONE.into(),
+            Self::Arcball { delta, .. } => *delta = DQuat::IDENTITY.into(),
+            Self::TranslationReset { .. } => {}
+        }
+        result
+    }
+}
+
+/// State of the interaction with a gizmo, returned by [`Gizmo::interaction_state`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum InteractionState {
+    /// No subgizmo is hovered or dragged
+    #[default]
+    Idle,
+    /// A subgizmo is under the pointer, but not dragged
+    Hovered {
+        /// Mode of the hovered subgizmo
+        mode: GizmoMode,
+        /// Direction of the hovered subgizmo
+        direction: GizmoDirection,
+        /// Kind of the hovered subgizmo
+        kind: TransformKind,
+    },
+    /// A subgizmo is being dragged
+    Dragging {
+        /// Mode of the dragged subgizmo
+        mode: GizmoMode,
+        /// Direction of the dragged subgizmo
+        direction: GizmoDirection,
+        /// Kind of the dragged subgizmo
+        kind: TransformKind,
     },
 }
 
+/// Copy of the accumulated state of the active subgizmo, returned by [`Gizmo::active_state`].
+///
+/// This is intended for debugging and tooling. Positions are in world space.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ActiveStateSnapshot {
+    /// State of a translation subgizmo
+    Translation {
+        /// Position of the gizmo when the drag started
+        start_point: mint::Vector3<f64>,
+        /// Position of the gizmo after the latest update
+        last_point: mint::Vector3<f64>,
+        /// Total translation of the drag, after snapping and constraints
+        current_delta: mint::Vector3<f64>,
+    },
+    /// State of a rotation subgizmo
+    Rotation {
+        /// Angle the pointer has rotated around the gizmo, in radians, before snapping
+        drag_angle: f64,
+        /// Angle of the drag after snapping, in radians.
+        /// This is the `total` of the latest [`GizmoResult::Rotation`].
+        current_delta: f64,
+    },
+    /// State of a scale subgizmo
+    Scale {
+        /// Total multiplicative scale after the latest update
+        last_scale: mint::Vector3<f64>,
+        /// Scale factor given by the pointer, before snapping
+        unsnapped_delta: f64,
+    },
+    /// State of an arcball subgizmo
+    Arcball {
+        /// Viewport position where the drag started
+        start_pos: Pos2,
+        /// Total rotation of the drag
+        total_rotation: mint::Quaternion<f64>,
+    },
+}
+
+/// Error returned by [`Gizmo::try_update`] when the configuration cannot be used.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GizmoError {
+    /// The viewport is not set, or is not finite.
+    InvalidViewport,
+    /// The camera matrices are singular or contain non-finite values.
+    InvalidMatrices,
+}
+
+impl std::fmt::Display for GizmoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidViewport => write!(f, "viewport is not set"),
+            Self::InvalidMatrices => write!(f, "camera matrices are singular or not finite"),
+        }
+    }
+}
+
+impl std::error::Error for GizmoError {}
+
+/// Phase of a gizmo interaction
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum GizmoPhase {
+    /// First frame of the interaction
+    Begin,
+    /// The interaction is ongoing
+    #[default]
+    Update,
+    /// The interaction was finished this frame
+    End,
+}
+
 /// Data used to draw [`Gizmo`].
 #[derive(Default, Clone, Debug)]
 pub struct GizmoDrawData {
@@ -684,26 +2058,181 @@ pub struct GizmoDrawData {
     pub vertices: Vec<[f32; 2]>,
     /// Linear RGBA colors.
     pub colors: Vec<[f32; 4]>,
+    /// Normalized device depth of each vertex, as given by the projection matrix.
+    pub depths: Vec<f32>,
     /// Indices to the vertex data.
     pub indices: Vec<u32>,
 }
 
+/// Inputs that affect drawing the gizmo. The draw data is only rebuilt when these change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct DrawKey {
+    modes: EnumSet<GizmoMode>,
+    /// Bits of the gizmo size in pixels
+    gizmo_size: u32,
+    /// Hash of the view, projection and model matrices, the viewport and the pixel ratio
+    transform_hash: u64,
+    active_subgizmo_id: Option<u64>,
+    /// Hash of the highlight and opacity of each subgizmo
+    subgizmo_hash: u64,
+    /// See [`Gizmo::draw_generation`]
+    generation: u64,
+}
+
+/// Callback that returns the depth of the scene at a viewport position,
+/// e.g. by reading a depth buffer. See [`Gizmo::set_depth_test`].
+#[derive(Clone)]
+pub struct DepthTest(Arc<dyn Fn(Pos2) -> f32 + Send + Sync>);
+
+impl DepthTest {
+    /// Creates a depth test from a function that returns the scene depth at a viewport position.
+    /// The depth should be given in normalized device coordinates, like the depth buffer.
+    pub fn new(depth: impl Fn(Pos2) -> f32 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(depth))
+    }
+
+    /// Whether a point at the given viewport position and depth is behind the scene.
+    /// `increasing` tells whether the depth increases with distance from the camera.
+    fn is_occluded(&self, pos: Pos2, depth: f32, increasing: bool) -> bool {
+        let scene_depth = (self.0)(pos);
+        if increasing {
+            depth > scene_depth
+        } else {
+            depth < scene_depth
+        }
+    }
+}
+
+impl std::fmt::Debug for DepthTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DepthTest")
+    }
+}
+
+/// Label of a gizmo axis, returned by [`Gizmo::axis_labels`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AxisLabel {
+    /// Axis of the label
+    pub direction: GizmoDirection,
+    /// Text of the label, e.g. "X"
+    pub text: &'static str,
+    /// Center of the label in viewport coordinates
+    pub position: Pos2,
+    /// Color of the label, matching the axis
+    pub color: Color32,
+}
+
+/// Callback for crossed snapping steps. See [`Gizmo::set_snap_step_callback`].
+#[derive(Clone)]
+pub struct SnapStepCallback(Arc<Mutex<dyn FnMut(GizmoMode, i32) + Send>>);
+
+impl SnapStepCallback {
+    /// Creates a callback from a function that receives the mode of the snapped
+    /// subgizmo and the signed number of snapping steps crossed.
+    ///
+    /// Steps are positive in the direction of the result totals. For translation
+    /// along a plane, steps along both plane axes are counted.
+    pub fn new(callback: impl FnMut(GizmoMode, i32) + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(callback)))
+    }
+
+    fn call(&self, mode: GizmoMode, steps: i32) {
+        if let Ok(mut callback) = self.0.lock() {
+            callback(mode, steps);
+        }
+    }
+}
+
+impl std::fmt::Debug for SnapStepCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SnapStepCallback")
+    }
+}
+
+/// Relative change of transformation, returned by [`Gizmo::relative_transform`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RelativeTransform {
+    /// Translation in world space
+    pub translation: mint::Vector3<f64>,
+    /// Rotation around the pivot, in world space
+    pub rotation: mint::Quaternion<f64>,
+    /// Multiplicative scale, applied in the local space of each object
+    pub scale: mint::Vector3<f64>,
+    /// Point around which objects are rotated.
+    /// If not set, each object is rotated around its own origin.
+    pub pivot: Option<mint::Vector3<f64>>,
+}
+
+impl Default for RelativeTransform {
+    fn default() -> Self {
+        Self {
+            translation: DVec3::ZERO.into(),
+            rotation: DQuat::IDENTITY.into(),
+            scale: DVec3::ONE.into(),
+            pivot: None,
+        }
+    }
+}
+
+impl RelativeTransform {
+    /// Applies the relative change to the given transform.
+    ///
+    /// ```
+    /// use transform_gizmo::math::{DQuat, DVec3, Transform};
+    /// use transform_gizmo::RelativeTransform;
+    ///
+    /// let relative = RelativeTransform {
+    ///     rotation: DQuat::from_rotation_y(std::f64::consts::FRAC_PI_2).into(),
+    ///     pivot: Some(DVec3::ZERO.into()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let transform = relative.apply(&Transform {
+    ///     translation: DVec3::X.into(),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// assert!(DVec3::from(transform.translation).abs_diff_eq(DVec3::NEG_Z, 1e-10));
+    /// ```
+    pub fn apply(&self, transform: &Transform) -> Transform {
+        let rotation = DQuat::from(self.rotation);
+
+        let mut translation = DVec3::from(transform.translation);
+        if let Some(pivot) = self.pivot {
+            let pivot = DVec3::from(pivot);
+            translation = pivot + rotation * (translation - pivot);
+        }
+        translation += DVec3::from(self.translation);
+
+        Transform {
+            scale: (DVec3::from(transform.scale) * DVec3::from(self.scale)).into(),
+            rotation: (rotation * DQuat::from(transform.rotation))
+                .normalize()
+                .into(),
+            translation: translation.into(),
+        }
+    }
+}
+
 impl From<Mesh> for GizmoDrawData {
     fn from(mesh: Mesh) -> Self {
-        let (vertices, colors): (Vec<_>, Vec<_>) = mesh
+        let vertices = mesh
             .vertices
             .iter()
-            .map(|vertex| {
-                (
-                    [vertex.pos.x, vertex.pos.y],
-                    Rgba::from(vertex.color).to_array(),
-                )
-            })
-            .unzip();
+            .map(|vertex| [vertex.pos.x, vertex.pos.y])
+            .collect();
+        let colors = mesh
+            .vertices
+            .iter()
+            .map(|vertex| Rgba::from(vertex.color).to_array())
+            .collect();
+        // Meshes carry no depth
+        let depths = vec![0.0; mesh.vertices.len()];
 
         Self {
             vertices,
             colors,
+            depths,
             indices: mesh.indices,
         }
     }
@@ -714,6 +2243,7 @@ impl AddAssign for GizmoDrawData {
         let index_offset = self.vertices.len() as u32;
         self.vertices.extend(rhs.vertices);
         self.colors.extend(rhs.colors);
+        self.depths.extend(rhs.depths);
         self.indices
             .extend(rhs.indices.into_iter().map(|idx| index_offset + idx));
     }
@@ -728,9 +2258,1779 @@ impl Add for GizmoDrawData {
     }
 }
 
+/// Ray in world space, cast from a point in the viewport.
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Ray {
+pub struct Ray {
     pub(crate) screen_pos: Pos2,
     pub(crate) origin: DVec3,
     pub(crate) direction: DVec3,
 }
+
+impl Ray {
+    /// Creates a ray from the given viewport position, using the camera's view and projection matrices.
+    ///
+    /// The ray starts from the near plane and points towards the far plane,
+    /// so it works with both perspective and orthographic projections.
+    pub fn from_screen(view: DMat4, projection: DMat4, viewport: Rect, screen_pos: Pos2) -> Self {
+        let mat = (projection * view).inverse();
+        let origin = screen_to_world(viewport, mat, screen_pos, -1.0);
+        let target = screen_to_world(viewport, mat, screen_pos, 1.0);
+
+        let direction = target.sub(origin).normalize();
+
+        Self {
+            screen_pos,
+            origin,
+            direction,
+        }
+    }
+
+    /// Viewport position the ray was cast from.
+    pub fn screen_pos(&self) -> Pos2 {
+        self.screen_pos
+    }
+
+    /// Origin of the ray in world space.
+    pub fn origin(&self) -> mint::Vector3<f64> {
+        self.origin.into()
+    }
+
+    /// Normalized direction of the ray in world space.
+    pub fn direction(&self) -> mint::Vector3<f64> {
+        self.direction.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec2;
+    use crate::test_util::{self, CENTER, X_ARROW};
+    use crate::GizmoVisuals;
+
+    #[test]
+    fn deltas_add_up_to_totals() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let offsets = [DVec3::X * 0.3, DVec3::X * 0.1, DVec3::X * 0.6];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        let delta_sum = results
+            .iter()
+            .fold(DVec3::ZERO, |sum, result| match *result {
+                GizmoResult::Translation { delta, .. } => sum + DVec3::from(delta),
+                _ => panic!("expected a translation result"),
+            });
+        let total = test_util::translation_total(results.last().unwrap());
+        assert!(delta_sum.abs_diff_eq(total, 1e-9), "{delta_sum} != {total}");
+
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let path = test_util::ring_path(&[0.0, 20.0, 50.0, 30.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        let mut delta_sum = 0.0;
+        let mut total = 0.0;
+        for result in &results {
+            let GizmoResult::Rotation {
+                delta,
+                total: result_total,
+                ..
+            } = *result
+            else {
+                panic!("expected a rotation result");
+            };
+            delta_sum += delta;
+            total = result_total;
+        }
+        assert!(total.abs() > 0.1);
+        assert!((delta_sum - total).abs() < 1e-9, "{delta_sum} != {total}");
+
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::ScaleX)));
+        let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        let mut delta_product = DVec3::ONE;
+        let mut total = DVec3::ONE;
+        for result in &results {
+            let GizmoResult::Scale {
+                delta,
+                total: result_total,
+                ..
+            } = *result
+            else {
+                panic!("expected a scale result");
+            };
+            delta_product *= DVec3::from(delta);
+            total = result_total.into();
+        }
+        assert!(total.x > 1.1);
+        assert!(
+            delta_product.abs_diff_eq(total, 1e-9),
+            "{delta_product} != {total}"
+        );
+    }
+
+    #[test]
+    fn double_click_resets_axis_translation() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform::from_scale_rotation_translation(
+            DVec3::ONE,
+            DQuat::IDENTITY,
+            DVec3::new(0.4, 0.3, 0.0),
+        )];
+        let arrow = gizmo.world_to_screen(targets[0].translation).unwrap() + Vec2::new(50.0, 0.0);
+
+        let press = GizmoInteraction {
+            drag_started: true,
+            dragging: true,
+            ..test_util::hover(arrow)
+        };
+        let release = test_util::hover(arrow);
+
+        test_util::update(&mut gizmo, press, &mut targets);
+        test_util::update(&mut gizmo, release, &mut targets);
+        test_util::update(&mut gizmo, press, &mut targets);
+        let result = test_util::update(
+            &mut gizmo,
+            GizmoInteraction {
+                double_clicked: true,
+                ..release
+            },
+            &mut targets,
+        );
+
+        assert!(matches!(result, Some(GizmoResult::TranslationReset { .. })));
+        assert!(DVec3::from(targets[0].translation).abs_diff_eq(DVec3::new(0.0, 0.3, 0.0), 1e-9));
+        assert_eq!(gizmo.interaction_state(), InteractionState::Idle);
+    }
+
+    #[test]
+    fn result_identifies_the_picked_handle() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(10.0, 0.0, 0.0)).into(),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+        let z_arrow = gizmo.world_to_screen(DVec3::Z * 0.7).unwrap();
+
+        let results = test_util::drag(&mut gizmo, &[z_arrow], &mut [Transform::default()]);
+
+        let Some(GizmoResult::Translation {
+            mode,
+            direction,
+            kind,
+            ..
+        }) = results.first()
+        else {
+            panic!("expected a translation result");
+        };
+        assert_eq!(*mode, GizmoMode::TranslateZ);
+        assert_eq!(*direction, GizmoDirection::Z);
+        assert_eq!(*kind, TransformKind::Axis);
+    }
+
+    #[test]
+    fn offset_gizmo_translates_like_the_pivot() {
+        let offset = DVec3::new(0.0, 0.5, 0.0);
+        let mut offset_gizmo = Gizmo::new(GizmoConfig {
+            gizmo_offset: offset.into(),
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        });
+        let mut offset_targets = [Transform::default()];
+        test_util::update(
+            &mut offset_gizmo,
+            test_util::hover(CENTER),
+            &mut offset_targets,
+        );
+
+        // The handles are drawn and picked at the offset position
+        let center = offset_gizmo.world_to_screen(offset).unwrap();
+        assert!(center.y < CENTER.y - 10.0, "{center:?}");
+        let draw_data = offset_gizmo.draw();
+        assert!(!draw_data.vertices.is_empty());
+        assert!(draw_data
+            .vertices
+            .iter()
+            .all(|vertex| (vertex[1] - center.y).abs() < 15.0));
+
+        let x_arrow = X_ARROW + (center - CENTER);
+        assert_eq!(offset_gizmo.hovered((X_ARROW.x, X_ARROW.y)), None);
+        assert_eq!(
+            offset_gizmo.hovered((x_arrow.x, x_arrow.y)),
+            Some((
+                GizmoMode::TranslateX,
+                GizmoDirection::X,
+                TransformKind::Axis
+            ))
+        );
+
+        let offsets = [DVec3::X * 0.3, DVec3::X * 0.6];
+        let path = test_util::offset_path(&offset_gizmo, x_arrow, &offsets);
+        let offset_results = test_util::drag(&mut offset_gizmo, &path, &mut offset_targets);
+
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform::default()];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let offset_total = test_util::translation_total(offset_results.last().unwrap());
+        let total = test_util::translation_total(results.last().unwrap());
+        assert!(
+            offset_total.abs_diff_eq(total, 1e-6),
+            "{offset_total} != {total}"
+        );
+        assert!(DVec3::from(offset_targets[0].translation).abs_diff_eq(DVec3::X * 0.6, 1e-6));
+    }
+
+    #[test]
+    fn drag_reports_begin_update_and_end_phases() {
+        fn phases(results: &[GizmoResult]) -> Vec<GizmoPhase> {
+            results
+                .iter()
+                .map(|result| match *result {
+                    GizmoResult::Rotation { phase, .. }
+                    | GizmoResult::Translation { phase, .. }
+                    | GizmoResult::Scale { phase, .. }
+                    | GizmoResult::Arcball { phase, .. } => phase,
+                    _ => panic!("unexpected result {result:?}"),
+                })
+                .collect()
+        }
+
+        let expected = [
+            GizmoPhase::Begin,
+            GizmoPhase::Update,
+            GizmoPhase::Update,
+            GizmoPhase::End,
+        ];
+        let offsets = [DVec3::X * 0.2, DVec3::X * 0.4];
+
+        for mode in [GizmoMode::TranslateX, GizmoMode::ScaleX] {
+            let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(mode)));
+            let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+            let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+            assert_eq!(phases(&results), expected, "{mode:?}");
+        }
+
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let path = test_util::ring_path(&[0.0, 20.0, 40.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        assert_eq!(phases(&results), expected);
+    }
+
+    #[test]
+    fn nudge_moves_by_snap_steps() {
+        let config = GizmoConfig {
+            snap_distance: 0.5,
+            ..test_util::config(GizmoMode::all_translate())
+        };
+
+        let mut gizmo = Gizmo::new(config);
+        let (_, targets) = gizmo
+            .nudge(GizmoDirection::X, 2, &[Transform::default()])
+            .unwrap();
+        assert!(DVec3::from(targets[0].translation).abs_diff_eq(DVec3::X, 1e-9));
+
+        let rotation = DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2);
+        let target = Transform {
+            rotation: rotation.into(),
+            ..Default::default()
+        };
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            orientation: GizmoOrientation::Local,
+            ..config
+        });
+        let (result, targets) = gizmo.nudge(GizmoDirection::Y, -1, &[target]).unwrap();
+        let expected = rotation * DVec3::new(0.0, -0.5, 0.0);
+        assert!(DVec3::from(targets[0].translation).abs_diff_eq(expected, 1e-9));
+        // Results are in local space with local orientation
+        let total = test_util::translation_total(&result);
+        assert!(
+            total.abs_diff_eq(DVec3::new(0.0, -0.5, 0.0), 1e-9),
+            "{total}"
+        );
+
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            locked_axes: EnumSet::only(GizmoDirection::Y),
+            disabled_directions: EnumSet::only(GizmoDirection::Z),
+            translation_bounds: Some((DVec3::splat(-1.0).into(), DVec3::new(0.7, 1.0, 1.0).into())),
+            ..config
+        });
+        assert!(gizmo
+            .nudge(GizmoDirection::Y, 1, &[Transform::default()])
+            .is_none());
+        assert!(gizmo
+            .nudge(GizmoDirection::Z, 1, &[Transform::default()])
+            .is_none());
+        let (_, targets) = gizmo
+            .nudge(GizmoDirection::X, 2, &[Transform::default()])
+            .unwrap();
+        assert!(DVec3::from(targets[0].translation).abs_diff_eq(DVec3::X * 0.7, 1e-9));
+    }
+
+    #[test]
+    fn constrained_rotation_builds_only_that_ring() {
+        let config = GizmoConfig {
+            constrained_rotation_axis: Some(GizmoDirection::Y),
+            ..test_util::config(GizmoMode::all_rotate())
+        };
+        let gizmo = Gizmo::new(config);
+
+        let handles: Vec<_> = gizmo
+            .subgizmos
+            .iter()
+            .map(|subgizmo| subgizmo.handle())
+            .collect();
+        assert_eq!(
+            handles,
+            [(GizmoMode::RotateY, GizmoDirection::Y, TransformKind::Axis)]
+        );
+
+        // The Z ring faces the test camera, but is not built
+        let ring = test_util::ring_path(&[0.0])[0];
+        assert_eq!(gizmo.hovered((ring.x, ring.y)), None);
+
+        // The Y ring faces a camera looking down the Y axis
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, -DVec3::Z).into(),
+            ..config
+        });
+        test_util::update(
+            &mut gizmo,
+            test_util::hover(ring),
+            &mut [Transform::default()],
+        );
+        assert_eq!(
+            gizmo.hovered((ring.x, ring.y)),
+            Some((GizmoMode::RotateY, GizmoDirection::Y, TransformKind::Axis))
+        );
+    }
+
+    #[test]
+    fn anchored_scale_keeps_the_opposite_side_in_place() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            object_bounds: Some((DVec3::splat(-1.0).into(), DVec3::splat(1.0).into())),
+            anchored_scale: true,
+            ..test_util::config(EnumSet::only(GizmoMode::ScaleX))
+        });
+        let mut targets = [Transform::default()];
+
+        // Twice as far from the center as the grab point
+        let path = [
+            X_ARROW,
+            X_ARROW + Vec2::new(25.0, 0.0),
+            CENTER + Vec2::new(100.0, 0.0),
+        ];
+        test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let scale = DVec3::from(targets[0].scale);
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            scale.abs_diff_eq(DVec3::new(2.0, 1.0, 1.0), 1e-3),
+            "{scale}"
+        );
+        // Half of the width of the bounds, keeping the minimum X side at -1
+        assert!(translation.abs_diff_eq(DVec3::X, 1e-3), "{translation}");
+    }
+
+    #[test]
+    fn picks_outside_the_viewport_are_ignored() {
+        let viewport = Rect::from_min_max(Pos2::new(400.0, 300.0), Pos2::new(800.0, 600.0));
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            viewport,
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        });
+        let mut targets = [Transform::default()];
+
+        // The gizmo is projected to the center of the viewport
+        let center = viewport.center();
+        let x_arrow = center + (X_ARROW - CENTER);
+        test_util::update(&mut gizmo, test_util::hover(center), &mut targets);
+        assert!(gizmo.world_to_screen(DVec3::ZERO).unwrap().distance(center) < 1e-3);
+
+        let upper_left = Pos2::new(100.0, 100.0);
+        assert!(test_util::drag(
+            &mut gizmo,
+            &[upper_left, upper_left + Vec2::X * 20.0],
+            &mut targets
+        )
+        .is_empty());
+        assert_eq!(gizmo.hovered((upper_left.x, upper_left.y)), None);
+
+        assert!(!test_util::drag(
+            &mut gizmo,
+            &[x_arrow, x_arrow + Vec2::X * 20.0],
+            &mut targets
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn combined_modes_pick_the_handle_under_the_cursor() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(6.0, 5.0, 8.0)).into(),
+            ..test_util::config(GizmoMode::all_translate() | GizmoMode::all_rotate())
+        });
+        let mut targets = [Transform::default()];
+        test_util::update(&mut gizmo, test_util::hover(CENTER), &mut targets);
+
+        let size = (gizmo.config.scale_factor * gizmo.config.gizmo_size) as f64;
+        let arrow = gizmo.world_to_screen(DVec3::X * size * 0.7).unwrap();
+        let ring = gizmo
+            .world_to_screen(DVec3::new(1.0, 1.0, 0.0).normalize() * size)
+            .unwrap();
+
+        assert_eq!(
+            gizmo.hovered((arrow.x, arrow.y)),
+            Some((
+                GizmoMode::TranslateX,
+                GizmoDirection::X,
+                TransformKind::Axis
+            ))
+        );
+        assert_eq!(
+            gizmo.hovered((ring.x, ring.y)),
+            Some((GizmoMode::RotateZ, GizmoDirection::Z, TransformKind::Axis))
+        );
+
+        let results = test_util::drag(&mut gizmo, &[arrow, arrow + Vec2::X * 10.0], &mut targets);
+        assert!(matches!(
+            results.last(),
+            Some(GizmoResult::Translation {
+                mode: GizmoMode::TranslateX,
+                ..
+            })
+        ));
+
+        targets = [Transform::default()];
+        let results = test_util::drag(&mut gizmo, &[ring, ring + Vec2::X * 10.0], &mut targets);
+        assert!(matches!(
+            results.last(),
+            Some(GizmoResult::Rotation {
+                mode: GizmoMode::RotateZ,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn orientation_override_applies_only_to_the_interaction() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform {
+            rotation: DQuat::from_rotation_z(std::f64::consts::FRAC_PI_2).into(),
+            ..Default::default()
+        }];
+
+        // In local orientation, the X arrow of the rotated target points up
+        let grab = CENTER - Vec2::new(0.0, X_ARROW.x - CENTER.x);
+        let offsets = [DVec3::Y * 0.15, DVec3::Y * 0.3];
+        let path = test_util::offset_path(&gizmo, grab, &offsets);
+        let mut results = Vec::new();
+        for (index, &pos) in path.iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                orientation_override: Some(GizmoOrientation::Local),
+                ..test_util::hover(pos)
+            };
+            results.extend(test_util::update(&mut gizmo, interaction, &mut targets));
+        }
+
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            translation.abs_diff_eq(DVec3::Y * 0.3, 1e-6),
+            "{translation}"
+        );
+        let total = test_util::translation_total(results.last().unwrap());
+        assert!(total.abs_diff_eq(DVec3::X * 0.3, 1e-6), "{total}");
+        assert_eq!(gizmo.config().orientation, GizmoOrientation::Global);
+    }
+
+    #[test]
+    fn hovered_reports_the_handle_without_dragging() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, -DVec3::Z).into(),
+            ..test_util::config(GizmoMode::all_rotate())
+        });
+        let mut targets = [Transform::default()];
+        test_util::update(&mut gizmo, test_util::hover(CENTER), &mut targets);
+
+        // The Y ring faces the camera
+        let ring = test_util::ring_path(&[0.0])[0];
+        let y_ring = Some((GizmoMode::RotateY, GizmoDirection::Y, TransformKind::Axis));
+        assert_eq!(gizmo.hovered((ring.x, ring.y)), y_ring);
+        assert!(gizmo.active_subgizmo_id.is_none());
+
+        // Hovering does not disturb a drag
+        let interaction = GizmoInteraction {
+            drag_started: true,
+            dragging: true,
+            ..test_util::hover(ring)
+        };
+        test_util::update(&mut gizmo, interaction, &mut targets);
+        let active_subgizmo_id = gizmo.active_subgizmo_id;
+        assert!(active_subgizmo_id.is_some());
+        assert_eq!(gizmo.hovered((CENTER.x, CENTER.y)), None);
+        assert_eq!(gizmo.active_subgizmo_id, active_subgizmo_id);
+    }
+
+    #[test]
+    fn screen_rect_contains_the_handles() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(6.0, 5.0, 8.0)).into(),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+        test_util::update(
+            &mut gizmo,
+            test_util::hover(Pos2::ZERO),
+            &mut [Transform::default()],
+        );
+
+        let rect = gizmo.screen_rect();
+        let size = (gizmo.config.scale_factor * gizmo.config.gizmo_size) as f64;
+        for point in [DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::Z] {
+            // Just inside the arrow tips
+            let pos = gizmo.world_to_screen(point * size * 0.95).unwrap();
+            assert!(rect.contains(pos), "{rect:?} {pos:?}");
+        }
+
+        let max_extent = gizmo.config.gizmo_size * 2.5;
+        assert!(
+            rect.width() < max_extent && rect.height() < max_extent,
+            "{rect:?}"
+        );
+    }
+
+    #[test]
+    fn screen_rays_start_from_the_camera() {
+        let view = test_util::view_matrix(test_util::CAMERA_POSITION);
+        let projection = test_util::projection_matrix();
+
+        // Through the center, from the near plane of the camera
+        let ray = Ray::from_screen(view, projection, test_util::VIEWPORT, CENTER);
+        assert!(
+            ray.direction.abs_diff_eq(-DVec3::Z, 1e-9),
+            "{}",
+            ray.direction
+        );
+        assert!(
+            ray.origin.abs_diff_eq(DVec3::Z * 9.9, 1e-5),
+            "{}",
+            ray.origin
+        );
+
+        // Through a projected point, on the line from the camera
+        let point = DVec3::new(1.0, 0.5, 0.0);
+        let pos = world_to_screen(test_util::VIEWPORT, projection * view, point).unwrap();
+        let ray = Ray::from_screen(view, projection, test_util::VIEWPORT, pos);
+        let to_point = (point - test_util::CAMERA_POSITION).normalize();
+        assert!(
+            ray.direction.abs_diff_eq(to_point, 1e-5),
+            "{}",
+            ray.direction
+        );
+        let to_origin = (ray.origin - test_util::CAMERA_POSITION).normalize();
+        assert!(to_origin.abs_diff_eq(to_point, 1e-5), "{to_origin}");
+
+        // All rays of an orthographic camera are parallel
+        let projection = DMat4::orthographic_rh(-4.0, 4.0, -3.0, 3.0, 0.1, 100.0);
+        let ray = Ray::from_screen(
+            view,
+            projection,
+            test_util::VIEWPORT,
+            Pos2::new(100.0, 50.0),
+        );
+        assert!(
+            ray.direction.abs_diff_eq(-DVec3::Z, 1e-9),
+            "{}",
+            ray.direction
+        );
+        assert!(
+            ray.origin.abs_diff_eq(DVec3::new(-3.0, 2.5, 9.9), 1e-5),
+            "{}",
+            ray.origin
+        );
+    }
+
+    #[test]
+    fn orthographic_drag_follows_the_cursor() {
+        // 8 world units across 800 pixels
+        let projection = DMat4::orthographic_rh(-4.0, 4.0, -3.0, 3.0, 0.1, 100.0);
+        let config = GizmoConfig {
+            projection_matrix: projection.into(),
+            ..test_util::config(GizmoMode::TranslateX | GizmoMode::TranslateY)
+        };
+
+        let cases = [
+            (X_ARROW, Vec2::new(40.0, 0.0), DVec3::X * 0.4),
+            (
+                Pos2::new(CENTER.x, CENTER.y - 50.0),
+                Vec2::new(0.0, -60.0),
+                DVec3::Y * 0.6,
+            ),
+        ];
+
+        for (grab, cursor_travel, expected) in cases {
+            let mut gizmo = Gizmo::new(config);
+            let mut targets = [Transform::default()];
+            let path = [grab, grab + cursor_travel * 0.5, grab + cursor_travel];
+            let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+            let total = test_util::translation_total(results.last().unwrap());
+            assert!(total.abs_diff_eq(expected, 1e-4), "{total}");
+            let translation = DVec3::from(targets[0].translation);
+            assert!(translation.abs_diff_eq(expected, 1e-4), "{translation}");
+        }
+    }
+
+    #[test]
+    fn relative_rotation_moves_other_objects_around_the_pivot() {
+        // Looking down, +X is right and +Z is down in the viewport
+        let config = GizmoConfig {
+            view_matrix: DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, DVec3::NEG_Z).into(),
+            ..test_util::config(EnumSet::only(GizmoMode::RotateY))
+        };
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+        let path = test_util::ring_path(&[0.0, -30.0, -60.0, -90.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let mut object = Transform {
+            translation: DVec3::X.into(),
+            ..Default::default()
+        };
+        for result in &results {
+            object = gizmo.relative_transform(result).unwrap().apply(&object);
+        }
+
+        let translation = DVec3::from(object.translation);
+        assert!(translation.abs_diff_eq(DVec3::NEG_Z, 1e-3), "{translation}");
+        let rotation = DQuat::from(object.rotation);
+        let expected = DQuat::from_rotation_y(std::f64::consts::FRAC_PI_2);
+        assert!(rotation.abs_diff_eq(expected, 1e-3), "{rotation}");
+    }
+
+    #[test]
+    fn active_target_pivot_is_the_last_target() {
+        let config = GizmoConfig {
+            pivot_point: TransformPivotPoint::ActiveTarget,
+            ..test_util::config(EnumSet::only(GizmoMode::RotateZ))
+        };
+        let mut gizmo = Gizmo::new(config);
+        let targets = [
+            Transform {
+                translation: DVec3::X.into(),
+                ..Default::default()
+            },
+            Transform::default(),
+        ];
+        gizmo.update(test_util::hover(CENTER), &targets);
+        assert_eq!(gizmo.config.translation, DVec3::ZERO);
+
+        let mut targets = targets;
+        let path = test_util::ring_path(&[0.0, 45.0, 90.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+        assert!(DVec3::from(targets[1].translation).abs_diff_eq(DVec3::ZERO, 1e-9));
+
+        let relative = gizmo.relative_transform(results.last().unwrap()).unwrap();
+        assert_eq!(relative.pivot, Some(DVec3::ZERO.into()));
+        let moved = DVec3::from(targets[0].translation);
+        assert!((moved.length() - 1.0).abs() < 1e-6, "{moved}");
+        assert!(moved.abs_diff_eq(DVec3::NEG_Y, 1e-3), "{moved}");
+    }
+
+    #[test]
+    fn depth_test_dims_the_occluded_half_of_an_axis() {
+        // Looking from the side, so that the X axis points away from the camera
+        let config = GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(-6.0, 0.0, 8.0)).into(),
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        };
+        let mut gizmo = Gizmo::new(config);
+        test_util::update(
+            &mut gizmo,
+            test_util::hover(Pos2::ZERO),
+            &mut [Transform::default()],
+        );
+        let visible = gizmo.draw();
+
+        // A wall halfway along the axis, hiding its far half
+        let axis_length = (gizmo.config.gizmo_size * gizmo.config.scale_factor) as f64;
+        let (wall_pos, wall_depth) = world_to_screen_depth(
+            gizmo.config.viewport,
+            gizmo.config.view_projection,
+            DVec3::X * axis_length * 0.5,
+        )
+        .unwrap();
+        gizmo.set_depth_test(Some(DepthTest::new(move |_| wall_depth)));
+        let dimmed = gizmo.draw();
+
+        assert_eq!(visible.vertices, dimmed.vertices);
+        let alpha = GizmoVisuals::default().occluded_alpha;
+        let (mut near, mut far) = (0, 0);
+        for ((vertex, visible), dimmed) in visible
+            .vertices
+            .iter()
+            .zip(&visible.colors)
+            .zip(&dimmed.colors)
+        {
+            if vertex[0] < wall_pos.x - 5.0 {
+                assert_eq!(dimmed, visible);
+                near += 1;
+            } else if vertex[0] > wall_pos.x + 5.0 {
+                assert!((dimmed[3] - visible[3] * alpha).abs() < 1e-6, "{dimmed:?}");
+                far += 1;
+            }
+        }
+        assert!(near > 0 && far > 0, "{near} {far}");
+    }
+
+    #[test]
+    fn draw_data_is_reused_while_nothing_changes() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all()));
+        let mut targets = [Transform::default()];
+        let config = *gizmo.config();
+
+        // Frames like the egui integration, which updates the config every frame
+        let mut frame = |gizmo: &mut Gizmo, config: GizmoConfig, pos: Pos2| {
+            gizmo.set_context_pixels_per_point(1.0);
+            gizmo.update_config(config);
+            test_util::update(gizmo, test_util::hover(pos), &mut targets);
+            gizmo.draw()
+        };
+
+        let first = frame(&mut gizmo, config, Pos2::ZERO);
+        let second = frame(&mut gizmo, config, Pos2::ZERO);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&second, &gizmo.draw()));
+
+        // Hovering changes the highlight
+        let hovered = frame(&mut gizmo, config, X_ARROW);
+        assert!(!Arc::ptr_eq(&first, &hovered));
+        assert_ne!(hovered.colors, first.colors);
+        let unhovered = frame(&mut gizmo, config, Pos2::ZERO);
+        assert!(!Arc::ptr_eq(&hovered, &unhovered));
+        assert_eq!(unhovered.colors, first.colors);
+
+        // Moving the camera changes the view matrix
+        let moved = GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(1.0, 2.0, 10.0)).into(),
+            ..config
+        };
+        let moved_camera = frame(&mut gizmo, moved, Pos2::ZERO);
+        assert!(!Arc::ptr_eq(&unhovered, &moved_camera));
+        assert!(Arc::ptr_eq(
+            &moved_camera,
+            &frame(&mut gizmo, moved, Pos2::ZERO)
+        ));
+
+        // Changing the visuals or the size
+        let visuals = GizmoConfig {
+            visuals: GizmoVisuals {
+                gizmo_size: 100.0,
+                ..config.visuals
+            },
+            ..moved
+        };
+        let resized = frame(&mut gizmo, visuals, Pos2::ZERO);
+        assert!(!Arc::ptr_eq(&moved_camera, &resized));
+
+        // Every frame of a drag is drawn from the dragged state
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let mut draws = Vec::new();
+        for (index, pos) in test_util::ring_path(&[0.0, 20.0, 40.0])
+            .into_iter()
+            .enumerate()
+        {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            test_util::update(&mut gizmo, interaction, &mut targets);
+            draws.push(gizmo.draw());
+        }
+        assert!(!Arc::ptr_eq(&draws[1], &draws[2]));
+    }
+
+    #[test]
+    fn picking_does_not_allocate() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all()));
+        let targets = [Transform::default()];
+        let positions = [CENTER, X_ARROW, Pos2::new(CENTER.x, CENTER.y - 75.0)];
+
+        // Hovering the handles for the first time may allocate, e.g. for the draw cache
+        for pos in positions {
+            gizmo.update(test_util::hover(pos), &targets);
+        }
+
+        let start = test_util::allocations();
+        for pos in positions {
+            assert!(gizmo.update(test_util::hover(pos), &targets).is_none());
+            assert!(gizmo.hovered((pos.x, pos.y)).is_some());
+        }
+        assert_eq!(test_util::allocations(), start);
+    }
+
+    #[test]
+    fn overlapping_handles_are_picked_deterministically() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all()));
+        let targets = [Transform::default()];
+        gizmo.update(test_util::hover(Pos2::ZERO), &targets);
+
+        // Near the center, the arrows, the planes and the view handles overlap
+        let pos = CENTER + Vec2::new(3.0, -3.0);
+        let first = gizmo.hovered((pos.x, pos.y));
+        assert!(first.is_some());
+        for _ in 0..10 {
+            gizmo.update(test_util::hover(pos), &targets);
+            assert_eq!(gizmo.hovered((pos.x, pos.y)), first);
+        }
+
+        // Exact ties are won by the subgizmo that was added first
+        let subgizmo = gizmo
+            .subgizmos
+            .iter()
+            .find(|subgizmo| subgizmo.handle().0 == GizmoMode::TranslateX)
+            .unwrap()
+            .clone();
+        let mut subgizmos = [subgizmo.clone(), subgizmo];
+        let picked: *const SubGizmo =
+            pick_subgizmo(&mut subgizmos, false, None, test_util::ray(X_ARROW)).unwrap();
+        assert!(std::ptr::eq(picked, &subgizmos[0]));
+    }
+
+    #[test]
+    fn touch_drags_start_from_the_touch_down_position() {
+        let config = GizmoConfig {
+            input_mode: InputMode::Touch,
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        };
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+
+        // Without a touch, nothing is focused even when the position is over a handle
+        assert!(test_util::update(&mut gizmo, test_util::hover(X_ARROW), &mut targets).is_none());
+        assert!(gizmo
+            .subgizmos
+            .iter()
+            .all(|subgizmo| !subgizmo.is_focused()));
+
+        // Beside the arrow, but within the touch pick tolerance
+        let touch = X_ARROW + Vec2::new(0.0, 12.0);
+        let path = test_util::offset_path(&gizmo, touch, &[DVec3::X * 0.2, DVec3::X * 0.4]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let phases: Vec<_> = results
+            .iter()
+            .map(|result| match *result {
+                GizmoResult::Translation { phase, .. } => phase,
+                _ => panic!("unexpected result {result:?}"),
+            })
+            .collect();
+        assert_eq!(
+            phases,
+            [
+                GizmoPhase::Begin,
+                GizmoPhase::Update,
+                GizmoPhase::Update,
+                GizmoPhase::End
+            ]
+        );
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            translation.abs_diff_eq(DVec3::X * 0.4, 1e-4),
+            "{translation}"
+        );
+    }
+
+    #[test]
+    fn drags_start_after_the_drag_threshold() {
+        let config = GizmoConfig {
+            drag_threshold: 5.0,
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        };
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+
+        let press = |pos: Pos2, drag_started| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            ..test_util::hover(pos)
+        };
+
+        let grab = gizmo.screen_to_world_on_plane(X_ARROW, DVec3::Z, DVec3::ZERO);
+        let grab = DVec3::from(grab.unwrap());
+
+        assert!(test_util::update(&mut gizmo, press(X_ARROW, true), &mut targets).is_none());
+        let jitter = X_ARROW + Vec2::new(1.0, 0.0);
+        assert!(test_util::update(&mut gizmo, press(jitter, false), &mut targets).is_none());
+        assert_eq!(DVec3::from(targets[0].translation), DVec3::ZERO);
+
+        // The translation is measured from the press position
+        let moved = X_ARROW + Vec2::new(6.0, 0.0);
+        let result = test_util::update(&mut gizmo, press(moved, false), &mut targets).unwrap();
+        let moved_point = gizmo.screen_to_world_on_plane(moved, DVec3::Z, DVec3::ZERO);
+        let expected = DVec3::from(moved_point.unwrap()) - grab;
+        let total = test_util::translation_total(&result);
+        assert!(total.abs_diff_eq(expected, 1e-6), "{total} {expected}");
+        assert!(total.x > 0.05, "{total}");
+    }
+
+    #[test]
+    fn snapping_and_precision_follow_the_interaction() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform::default()];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.234]);
+
+        let frame = |pos: Pos2, drag_started, snapping, precision| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            snapping,
+            precision,
+            ..test_util::hover(pos)
+        };
+
+        test_util::update(&mut gizmo, frame(path[0], true, false, false), &mut targets);
+
+        // A modifier key toggling snapping on and off during the drag
+        for (snapping, expected) in [(true, 0.2), (false, 0.234), (true, 0.2)] {
+            let interaction = frame(path[1], false, snapping, false);
+            let result = test_util::update(&mut gizmo, interaction, &mut targets).unwrap();
+            let total = test_util::translation_total(&result);
+            assert!((total.x - expected).abs() < 1e-4, "{snapping} {total}");
+        }
+
+        // Precision mode scales down the pointer movement
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform::default()];
+        test_util::update(&mut gizmo, frame(path[0], true, false, true), &mut targets);
+        let interaction = frame(path[1], false, false, true);
+        let result = test_util::update(&mut gizmo, interaction, &mut targets).unwrap();
+        let total = test_util::translation_total(&result);
+        assert!((total.x - 0.0234).abs() < 1e-4, "{total}");
+    }
+
+    #[test]
+    fn mode_kind_switches_only_when_idle() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all_translate()));
+        let mut targets = [Transform::default()];
+
+        assert!(gizmo.set_mode_kind(GizmoModeKind::Rotate));
+        assert_eq!(gizmo.config().modes, GizmoMode::all_rotate());
+        assert!(!gizmo.set_mode_kind(GizmoModeKind::Rotate));
+
+        assert!(gizmo.set_mode_kind(GizmoModeKind::Translate));
+        let pressed = GizmoInteraction {
+            drag_started: true,
+            dragging: true,
+            ..test_util::hover(X_ARROW)
+        };
+        test_util::update(&mut gizmo, pressed, &mut targets);
+        assert!(gizmo.active_subgizmo_id.is_some());
+
+        // Ignored during the drag
+        assert!(!gizmo.set_mode_kind(GizmoModeKind::Rotate));
+        assert_eq!(gizmo.config().modes, GizmoMode::all_translate());
+
+        test_util::update(&mut gizmo, test_util::hover(X_ARROW), &mut targets);
+        assert!(gizmo.set_mode_kind(GizmoModeKind::Rotate));
+        assert_eq!(gizmo.config().modes, GizmoMode::all_rotate());
+    }
+
+    #[test]
+    fn interaction_state_follows_hover_drag_and_release() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform::default()];
+        let handle = (
+            GizmoMode::TranslateX,
+            GizmoDirection::X,
+            TransformKind::Axis,
+        );
+        let hovered = InteractionState::Hovered {
+            mode: handle.0,
+            direction: handle.1,
+            kind: handle.2,
+        };
+        let dragging = InteractionState::Dragging {
+            mode: handle.0,
+            direction: handle.1,
+            kind: handle.2,
+        };
+
+        let mut state = |interaction| {
+            test_util::update(&mut gizmo, interaction, &mut targets);
+            (gizmo.interaction_state(), gizmo.is_capturing_pointer())
+        };
+        let drag = |pos, drag_started| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            ..test_util::hover(pos)
+        };
+        let far_away = Pos2::new(100.0, 100.0);
+
+        assert_eq!(
+            state(test_util::hover(far_away)),
+            (InteractionState::Idle, false)
+        );
+        assert_eq!(state(test_util::hover(X_ARROW)), (hovered, true));
+        assert_eq!(state(drag(X_ARROW, true)), (dragging, true));
+        // The drag continues away from the handle
+        assert_eq!(state(drag(far_away, false)), (dragging, true));
+        assert_eq!(
+            state(test_util::hover(far_away)),
+            (InteractionState::Idle, false)
+        );
+    }
+
+    #[test]
+    fn pointer_is_captured_for_the_whole_drag() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateY)));
+        let mut targets = [Transform::default()];
+        let y_arrow = Pos2::new(CENTER.x, CENTER.y - 50.0);
+        let drag = |pos, drag_started| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            ..test_util::hover(pos)
+        };
+
+        test_util::update(&mut gizmo, test_util::hover(CENTER), &mut targets);
+        assert!(!gizmo.is_capturing_pointer());
+
+        test_util::update(&mut gizmo, drag(y_arrow, true), &mut targets);
+        assert!(gizmo.is_capturing_pointer());
+
+        // Outside the handle, and outside the viewport
+        for pos in [Pos2::new(600.0, 100.0), Pos2::new(-50.0, -50.0)] {
+            test_util::update(&mut gizmo, drag(pos, false), &mut targets);
+            assert!(gizmo.is_capturing_pointer(), "{pos:?}");
+        }
+
+        assert!(gizmo.cancel_drag().is_some());
+        assert!(!gizmo.is_capturing_pointer());
+    }
+
+    #[test]
+    fn disabled_directions_are_dimmed_and_not_pickable() {
+        let config = test_util::config(GizmoMode::RotateZ | GizmoMode::TranslateX);
+        let mut enabled = Gizmo::new(config);
+        let mut disabled = Gizmo::new(GizmoConfig {
+            disabled_directions: EnumSet::only(GizmoDirection::Z),
+            ..config
+        });
+
+        let ring = test_util::ring_path(&[0.0])[0];
+        let z_ring = Some((GizmoMode::RotateZ, GizmoDirection::Z, TransformKind::Axis));
+        assert_eq!(enabled.hovered((ring.x, ring.y)), z_ring);
+        assert_eq!(disabled.hovered((ring.x, ring.y)), None);
+
+        // Dragging the ring does nothing, but the X arrow still works
+        let mut targets = [Transform::default()];
+        assert!(
+            test_util::drag(&mut disabled, &[ring, ring + Vec2::Y * 20.0], &mut targets).is_empty()
+        );
+        assert_eq!(targets[0], Transform::default());
+
+        let path = [X_ARROW, X_ARROW + Vec2::X * 20.0];
+        assert!(!test_util::drag(&mut disabled, &path, &mut targets).is_empty());
+        assert!(DVec3::from(targets[0].translation).x > 0.0);
+
+        // Only the ring is dimmed
+        for gizmo in [&mut enabled, &mut disabled] {
+            test_util::update(gizmo, test_util::hover(CENTER), &mut [Transform::default()]);
+        }
+        let alphas = |gizmo: &Gizmo| -> Vec<f32> {
+            gizmo.draw().colors.iter().map(|color| color[3]).collect()
+        };
+        let (enabled_alphas, disabled_alphas) = (alphas(&enabled), alphas(&disabled));
+        assert_eq!(enabled_alphas.len(), disabled_alphas.len());
+
+        let multiplier = config.visuals.disabled_alpha;
+        let mut dimmed = 0;
+        for (enabled_alpha, disabled_alpha) in enabled_alphas.iter().zip(&disabled_alphas) {
+            if (enabled_alpha - disabled_alpha).abs() > 1e-6 {
+                assert!((enabled_alpha * multiplier - disabled_alpha).abs() < 1e-6);
+                dimmed += 1;
+            }
+        }
+        assert!(dimmed > 0);
+        assert!(dimmed < enabled_alphas.len());
+    }
+
+    #[test]
+    fn unchanged_snapped_results_are_skipped_on_request() {
+        let snapped_drag = |skip_unchanged_results| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                skip_unchanged_results,
+                ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+            });
+            let mut targets = [Transform::default()];
+            // Slow drag in steps much smaller than the snap distance of 0.1
+            let offsets: Vec<_> = (1..=25)
+                .map(|step| DVec3::X * (0.005 + 0.02 * step as f64))
+                .collect();
+            let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+
+            let mut results = Vec::new();
+            for (index, &pos) in path.iter().enumerate() {
+                let interaction = GizmoInteraction {
+                    drag_started: index == 0,
+                    dragging: true,
+                    snapping: true,
+                    ..test_util::hover(pos)
+                };
+                results.extend(test_util::update(&mut gizmo, interaction, &mut targets));
+            }
+            (results, targets)
+        };
+
+        let (all_results, all_targets) = snapped_drag(false);
+        assert_eq!(all_results.len(), 26);
+
+        let (results, targets) = snapped_drag(true);
+        assert_eq!(targets, all_targets);
+        // One result per full step, at 0.1, 0.2, ..., 0.5
+        let totals: Vec<_> = results
+            .iter()
+            .map(|result| test_util::translation_total(result).x)
+            .collect();
+        assert_eq!(totals.len(), 5, "{totals:?}");
+        for (index, total) in totals.iter().enumerate() {
+            assert!(
+                (total - 0.1 * (index + 1) as f64).abs() < 1e-6,
+                "{totals:?}"
+            );
+        }
+        assert!(matches!(
+            results[0],
+            GizmoResult::Translation {
+                phase: GizmoPhase::Begin,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn cancelled_drag_restores_the_start_transforms() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all_rotate()));
+        let start = Transform {
+            rotation: DQuat::from_rotation_x(0.3).into(),
+            scale: DVec3::splat(2.0).into(),
+            ..Default::default()
+        };
+        let mut targets = [start];
+
+        assert!(gizmo.cancel_drag().is_none());
+
+        let path = test_util::ring_path(&[0.0, 30.0, 60.0]);
+        for (index, &pos) in path.iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            test_util::update(&mut gizmo, interaction, &mut targets);
+        }
+        assert_ne!(targets[0], start);
+
+        let (result, cancelled) = gizmo.cancel_drag().unwrap();
+        assert_eq!(cancelled, [start]);
+        match result {
+            GizmoResult::Rotation {
+                delta,
+                total,
+                phase,
+                ..
+            } => {
+                assert_eq!(phase, GizmoPhase::End);
+                assert_eq!(total, 0.0);
+                assert!((delta.abs() - 60f64.to_radians()).abs() < 0.05, "{delta}");
+            }
+            _ => panic!("expected a rotation result, got {result:?}"),
+        }
+
+        // Idle afterwards, even though the button is still held
+        assert!(gizmo.active_subgizmo_id.is_none());
+        let interaction = GizmoInteraction {
+            dragging: true,
+            ..test_util::hover(path[2] + Vec2::Y * 10.0)
+        };
+        assert!(test_util::update(&mut gizmo, interaction, &mut cancelled.clone()).is_none());
+    }
+
+    #[test]
+    fn translation_lands_exactly_on_reachable_snap_targets() {
+        let config = GizmoConfig {
+            snapping: true,
+            snap_distance: 0.25,
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        };
+        let snap_target = DVec3::new(0.9, 0.05, 0.0);
+
+        let drag = |config: GizmoConfig| {
+            let mut gizmo = Gizmo::new(config);
+            gizmo.set_snap_targets(&[snap_target.into()]);
+            let mut targets = [Transform::default()];
+            let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.83]);
+            test_util::drag(&mut gizmo, &path, &mut targets);
+            DVec3::from(targets[0].translation)
+        };
+
+        // Off the dragged axis, but within the snap distance
+        assert_eq!(drag(config), snap_target);
+
+        // Locked axes and bounds keep the gizmo on the grid instead
+        let locked = drag(GizmoConfig {
+            locked_axes: EnumSet::only(GizmoDirection::Y),
+            ..config
+        });
+        assert!(locked.abs_diff_eq(DVec3::X * 0.75, 1e-9), "{locked}");
+
+        let bounded = drag(GizmoConfig {
+            translation_bounds: Some((DVec3::splat(-1.0).into(), DVec3::new(0.8, 1.0, 1.0).into())),
+            ..config
+        });
+        assert!(bounded.abs_diff_eq(DVec3::X * 0.75, 1e-9), "{bounded}");
+    }
+
+    #[test]
+    fn world_and_screen_positions_round_trip() {
+        let gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(3.0, 4.0, 8.0)).into(),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+
+        // The camera looks at the origin
+        let origin = gizmo.world_to_screen(DVec3::ZERO).unwrap();
+        assert!((origin - CENTER).length() < 1e-3, "{origin:?}");
+
+        let normal = DVec3::new(0.0, 1.0, 0.2).normalize();
+        for point in [
+            DVec3::ZERO,
+            DVec3::new(1.0, -0.2, 1.0),
+            DVec3::new(-2.0, 0.4, -2.0),
+        ] {
+            let pos = gizmo.world_to_screen(point).unwrap();
+            let back = gizmo
+                .screen_to_world_on_plane(pos, normal, DVec3::ZERO)
+                .unwrap();
+            assert!(
+                DVec3::from(back).abs_diff_eq(point, 1e-3),
+                "{point} {back:?}"
+            );
+        }
+
+        // Behind the camera
+        assert_eq!(gizmo.world_to_screen(DVec3::new(6.0, 8.0, 16.0)), None);
+        // Plane behind the camera, facing it
+        let behind = DVec3::new(3.0, 4.0, 8.0) * 2.0;
+        assert_eq!(
+            gizmo.screen_to_world_on_plane(CENTER, behind.normalize(), behind),
+            None
+        );
+    }
+
+    #[test]
+    fn drag_state_is_owned_by_the_gizmo_instance() {
+        let config = test_util::config(EnumSet::only(GizmoMode::TranslateX));
+        let mut gizmo = Gizmo::new(config);
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.2, DVec3::X * 0.5]);
+        let frame = |pos, drag_started| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            ..test_util::hover(pos)
+        };
+
+        let mut targets = [Transform::default()];
+        test_util::update(&mut gizmo, frame(path[0], true), &mut targets);
+        test_util::update(&mut gizmo, frame(path[1], false), &mut targets);
+
+        // A copy continues the drag, while a new gizmo knows nothing about it
+        let mut copy = gizmo.clone();
+        let mut copy_targets = targets;
+        let mut fresh = Gizmo::new(config);
+        assert!(
+            test_util::update(&mut fresh, frame(path[2], false), &mut targets.clone()).is_none()
+        );
+
+        for (gizmo, targets) in [(&mut gizmo, &mut targets), (&mut copy, &mut copy_targets)] {
+            test_util::update(gizmo, frame(path[2], false), targets);
+            let result = test_util::update(gizmo, test_util::hover(path[2]), targets).unwrap();
+            assert!(test_util::translation_total(&result).abs_diff_eq(DVec3::X * 0.5, 1e-6));
+            assert!(gizmo.active_subgizmo_id.is_none());
+        }
+        assert_eq!(targets, copy_targets);
+        assert!(DVec3::from(targets[0].translation).abs_diff_eq(DVec3::X * 0.5, 1e-6));
+    }
+
+    #[test]
+    fn rotation_orbits_the_targets_around_the_rotation_pivot() {
+        let pivot = DVec3::new(2.0, 0.5, 0.0);
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            rotation_pivot: Some(pivot.into()),
+            ..test_util::config(EnumSet::only(GizmoMode::RotateZ))
+        });
+        let mut targets = [Transform::default()];
+
+        let path = test_util::ring_path(&[0.0, 45.0, 90.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let Some(&GizmoResult::Rotation {
+            world_axis, total, ..
+        }) = results.last()
+        else {
+            panic!("expected a rotation result, got {results:?}");
+        };
+        assert!((total.abs() - 90f64.to_radians()).abs() < 0.02, "{total}");
+
+        let rotation = DQuat::from_axis_angle(world_axis.into(), total);
+        let expected = pivot + rotation * -pivot;
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            translation.abs_diff_eq(expected, 1e-6),
+            "{translation} {expected}"
+        );
+        assert!((translation.distance(pivot) - pivot.length()).abs() < 1e-9);
+        assert!(DQuat::from(targets[0].rotation).abs_diff_eq(rotation, 1e-6));
+    }
+
+    #[test]
+    fn gizmo_behind_the_camera_is_not_drawn_or_picked() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all()));
+        let behind = Transform {
+            translation: DVec3::new(0.0, 0.0, 20.0).into(),
+            ..Default::default()
+        };
+        let mut targets = [behind];
+
+        for pos in [CENTER, X_ARROW, Pos2::new(10.0, 10.0)] {
+            let interaction = GizmoInteraction {
+                drag_started: true,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            assert!(test_util::update(&mut gizmo, interaction, &mut targets).is_none());
+            assert_eq!(gizmo.hovered((pos.x, pos.y)), None);
+            assert!(gizmo.active_subgizmo_id.is_none());
+        }
+        assert_eq!(targets, [behind]);
+
+        let draw_data = gizmo.draw();
+        assert!(draw_data.vertices.is_empty() && draw_data.indices.is_empty());
+
+        // In front of the camera again
+        test_util::update(
+            &mut gizmo,
+            test_util::hover(CENTER),
+            &mut [Transform::default()],
+        );
+        assert!(!gizmo.draw().vertices.is_empty());
+    }
+
+    #[test]
+    fn active_state_snapshot_reads_the_drag_without_changing_it() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [Transform::default()];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.4, DVec3::X * 0.6]);
+        let frame = |pos, drag_started| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            ..test_util::hover(pos)
+        };
+
+        assert_eq!(gizmo.active_state(), None);
+        test_util::update(&mut gizmo, frame(path[0], true), &mut targets);
+        test_util::update(&mut gizmo, frame(path[1], false), &mut targets);
+
+        let snapshot = gizmo.active_state().unwrap();
+        let ActiveStateSnapshot::Translation {
+            start_point,
+            last_point,
+            current_delta,
+        } = snapshot
+        else {
+            panic!("expected a translation snapshot, got {snapshot:?}");
+        };
+        let current_delta = DVec3::from(current_delta);
+        assert!(
+            current_delta.abs_diff_eq(DVec3::X * 0.4, 1e-6),
+            "{current_delta}"
+        );
+        assert!(
+            (DVec3::from(last_point) - DVec3::from(start_point)).abs_diff_eq(current_delta, 1e-9)
+        );
+        assert_eq!(gizmo.active_state(), Some(snapshot));
+
+        let result = test_util::update(&mut gizmo, frame(path[2], false), &mut targets).unwrap();
+        assert!(test_util::translation_total(&result).abs_diff_eq(DVec3::X * 0.6, 1e-6));
+        test_util::update(&mut gizmo, test_util::hover(path[2]), &mut targets);
+        assert_eq!(gizmo.active_state(), None);
+    }
+
+    #[test]
+    fn sticky_handles_prefer_the_last_dragged_handle() {
+        let config = GizmoConfig {
+            sticky_handles: true,
+            pick_tolerance: 20.0,
+            ..test_util::config(GizmoMode::TranslateX | GizmoMode::TranslateY)
+        };
+        let x_arrow = (
+            GizmoMode::TranslateX,
+            GizmoDirection::X,
+            TransformKind::Axis,
+        );
+        let y_arrow = (
+            GizmoMode::TranslateY,
+            GizmoDirection::Y,
+            TransformKind::Axis,
+        );
+        let y_arrow_pos = Pos2::new(CENTER.x, CENTER.y - 50.0);
+        // As close to the X arrow as to the Y arrow
+        let between = CENTER + Vec2::new(20.0, -20.0);
+
+        for (sticky_handles, used, used_pos) in [
+            (true, x_arrow, X_ARROW),
+            (true, y_arrow, y_arrow_pos),
+            (false, y_arrow, y_arrow_pos),
+        ] {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                sticky_handles,
+                ..config
+            });
+            let mut targets = [Transform::default()];
+            test_util::drag(&mut gizmo, &[used_pos, used_pos], &mut targets);
+
+            let picked = gizmo.hovered((between.x, between.y));
+            if sticky_handles {
+                assert_eq!(picked, Some(used));
+            } else {
+                // Without stickiness, the last dragged handle makes no difference
+                let mut fresh = Gizmo::new(*gizmo.config());
+                fresh.update(test_util::hover(Pos2::ZERO), &targets);
+                assert_eq!(picked, fresh.hovered((between.x, between.y)));
+                assert!(picked.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn context_pixels_per_point_is_used_without_storing_it() {
+        /// Largest distance of the drawn gizmo from its center, in physical pixels
+        fn pixel_extent(gizmo: &mut Gizmo) -> f32 {
+            gizmo.update(test_util::hover(Pos2::ZERO), &[Transform::default()]);
+            let extent = gizmo
+                .draw()
+                .vertices
+                .iter()
+                .map(|vertex| (Pos2::new(vertex[0], vertex[1]) - CENTER).length())
+                .fold(0.0, f32::max);
+            extent * gizmo.config.pixels_per_point()
+        }
+
+        let config = test_util::config(GizmoMode::all_translate());
+        let mut gizmo = Gizmo::new(config);
+        let single = pixel_extent(&mut gizmo);
+
+        gizmo.set_context_pixels_per_point(2.0);
+        gizmo.update_config(config);
+        assert_eq!(gizmo.config().pixels_per_point, None);
+        let double = pixel_extent(&mut gizmo);
+        assert!((double / single - 2.0).abs() < 0.05, "{single} {double}");
+
+        // The config takes precedence over the context
+        gizmo.update_config(GizmoConfig {
+            pixels_per_point: Some(1.0),
+            ..config
+        });
+        assert_eq!(gizmo.config.pixels_per_point(), 1.0);
+        assert!((pixel_extent(&mut gizmo) - single).abs() < 1e-3);
+    }
+
+    #[test]
+    fn invalid_matrices_are_reported_and_leave_the_targets_unchanged() {
+        let config = test_util::config(EnumSet::only(GizmoMode::TranslateX));
+        let mut gizmo = Gizmo::new(config);
+        let targets = [Transform::default()];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.5]);
+        let frame = |pos, drag_started| GizmoInteraction {
+            drag_started,
+            dragging: true,
+            ..test_util::hover(pos)
+        };
+
+        // Valid until the camera is broken in the middle of a drag
+        assert!(gizmo
+            .try_update(frame(path[0], true), &targets)
+            .unwrap()
+            .is_some());
+        gizmo.update_config(GizmoConfig {
+            projection_matrix: DMat4::ZERO.into(),
+            ..config
+        });
+        assert_eq!(
+            gizmo
+                .try_update(frame(path[1], false), &targets)
+                .unwrap_err(),
+            GizmoError::InvalidMatrices
+        );
+        assert!(gizmo.update(frame(path[1], false), &targets).is_none());
+
+        gizmo.update_config(GizmoConfig {
+            view_matrix: DMat4::from_cols_array(&[f64::NAN; 16]).into(),
+            ..config
+        });
+        assert_eq!(
+            gizmo
+                .try_update(frame(path[1], false), &targets)
+                .unwrap_err(),
+            GizmoError::InvalidMatrices
+        );
+
+        gizmo.update_config(GizmoConfig {
+            viewport: Rect::NOTHING,
+            ..config
+        });
+        assert_eq!(
+            gizmo
+                .try_update(frame(path[1], false), &targets)
+                .unwrap_err(),
+            GizmoError::InvalidViewport
+        );
+        assert!(gizmo.draw().vertices.is_empty());
+    }
+
+    #[test]
+    fn axis_labels_follow_the_visible_axes() {
+        let config = GizmoConfig {
+            view_matrix: test_util::view_matrix(DVec3::new(6.0, 5.0, 8.0)).into(),
+            visuals: GizmoVisuals {
+                show_axis_labels: true,
+                ..Default::default()
+            },
+            ..test_util::config(GizmoMode::all_translate())
+        };
+        let labels = |config: GizmoConfig, depth_test: Option<DepthTest>| {
+            let mut gizmo = Gizmo::new(config);
+            gizmo.set_depth_test(depth_test);
+            gizmo.update(test_util::hover(Pos2::ZERO), &[Transform::default()]);
+            (gizmo.axis_labels(), gizmo)
+        };
+
+        let (shown, gizmo) = labels(config, None);
+        let texts: Vec<_> = shown.iter().map(|label| label.text).collect();
+        assert_eq!(texts, ["X", "Y", "Z"]);
+        for label in &shown {
+            // Beyond the arrow tip, in the axis color
+            let size = (gizmo.config.scale_factor * gizmo.config.gizmo_size) as f64;
+            let tip = gizmo.world_to_screen(gizmo_normal(&gizmo.config, label.direction) * size);
+            let center = gizmo.world_to_screen(DVec3::ZERO).unwrap();
+            assert!((label.position - center).length() > (tip.unwrap() - center).length());
+            assert_eq!(
+                label.color,
+                gizmo_color(&gizmo.config, 0.0, label.direction)
+            );
+        }
+
+        let (without_z, _) = labels(
+            GizmoConfig {
+                disabled_directions: EnumSet::only(GizmoDirection::Z),
+                ..config
+            },
+            None,
+        );
+        assert_eq!(without_z.len(), 2);
+        assert!(without_z
+            .iter()
+            .all(|label| label.direction != GizmoDirection::Z));
+
+        // Everything is behind a wall at the near plane
+        let (occluded, _) = labels(config, Some(DepthTest::new(|_| 0.0)));
+        for (occluded, shown) in occluded.iter().zip(&shown) {
+            assert!(occluded.color.a() < shown.color.a());
+        }
+
+        let (hidden, _) = labels(
+            GizmoConfig {
+                visuals: GizmoVisuals::default(),
+                ..config
+            },
+            None,
+        );
+        assert!(hidden.is_empty());
+    }
+
+    #[test]
+    fn ground_plane_only_gizmo_slides_in_xz() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, DVec3::NEG_Z).into(),
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateXZ))
+        });
+        let mut targets = [Transform::default()];
+        test_util::update(&mut gizmo, test_util::hover(Pos2::ZERO), &mut targets);
+
+        let handles: Vec<_> = gizmo
+            .subgizmos
+            .iter()
+            .map(|subgizmo| subgizmo.handle())
+            .collect();
+        assert_eq!(
+            handles,
+            [(
+                GizmoMode::TranslateXZ,
+                GizmoDirection::Y,
+                TransformKind::Plane
+            )]
+        );
+
+        // The plane handle is between the +X and +Z axes, which point right and down
+        let grab = CENTER + Vec2::splat(gizmo.config.gizmo_size * 0.5);
+        assert_eq!(gizmo.hovered((grab.x, grab.y)), Some(handles[0]));
+        for pos in [X_ARROW, CENTER + Vec2::new(0.0, 50.0)] {
+            assert_eq!(gizmo.hovered((pos.x, pos.y)), None);
+        }
+
+        let grab_point = gizmo
+            .screen_to_world_on_plane(grab, DVec3::Y, DVec3::ZERO)
+            .unwrap();
+        let offset = DVec3::new(0.4, 0.0, -0.3);
+        let end = gizmo
+            .world_to_screen(DVec3::from(grab_point) + offset)
+            .unwrap();
+        test_util::drag(&mut gizmo, &[grab, end], &mut targets);
+
+        let translation = DVec3::from(targets[0].translation);
+        assert!(translation.abs_diff_eq(offset, 1e-4), "{translation}");
+    }
+
+    #[test]
+    fn results_carry_the_interaction_id_of_their_drag() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all_translate()));
+        let mut targets = [Transform::default()];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.2, DVec3::X * 0.4]);
+
+        let first = test_util::drag(&mut gizmo, &path, &mut targets);
+        assert!(!first.is_empty());
+        let first_id = first[0].interaction_id();
+        assert!(first
+            .iter()
+            .all(|result| result.interaction_id() == first_id));
+        assert!(matches!(
+            first.last(),
+            Some(GizmoResult::Translation {
+                phase: GizmoPhase::End,
+                ..
+            })
+        ));
+        assert_eq!(gizmo.interaction_id(), first_id);
+
+        // Hovering does not start a new interaction
+        targets = [Transform::default()];
+        test_util::update(&mut gizmo, test_util::hover(X_ARROW), &mut targets);
+        assert_eq!(gizmo.interaction_id(), first_id);
+
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.2, DVec3::X * 0.4]);
+        let second = test_util::drag(&mut gizmo, &path, &mut targets);
+        assert!(!second.is_empty());
+        let second_id = second[0].interaction_id();
+        assert!(second_id > first_id);
+        assert!(second
+            .iter()
+            .all(|result| result.interaction_id() == second_id));
+
+        // Cancelling keeps the id of the drag, and a nudge is an interaction of its own
+        targets = [Transform::default()];
+        test_util::update(&mut gizmo, test_util::hover(X_ARROW), &mut targets);
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.2]);
+        for (index, &pos) in path.iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            test_util::update(&mut gizmo, interaction, &mut targets);
+        }
+        let (cancel, _) = gizmo.cancel_drag().unwrap();
+        assert_eq!(cancel.interaction_id(), second_id + 1);
+
+        let (nudge, _) = gizmo.nudge(GizmoDirection::X, 1, &targets).unwrap();
+        assert_eq!(nudge.interaction_id(), second_id + 2);
+    }
+
+    #[test]
+    fn proportional_weights_fall_off_from_the_drag_start() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all_translate()));
+        let mut targets = [Transform::default()];
+        test_util::update(&mut gizmo, test_util::hover(X_ARROW), &mut targets);
+
+        let positions = [DVec3::ZERO, DVec3::X, DVec3::X * 3.0].map(mint::Vector3::from);
+        let weights = |gizmo: &Gizmo, falloff| gizmo.proportional_weights(&positions, 2.0, falloff);
+        assert_eq!(weights(&gizmo, Falloff::Smooth), [1.0, 0.5, 0.0]);
+        assert_eq!(weights(&gizmo, Falloff::Linear), [1.0, 0.5, 0.0]);
+        assert_eq!(weights(&gizmo, Falloff::Sharp), [1.0, 0.25, 0.0]);
+
+        // During a drag, the distances are measured from where the drag started
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.5]);
+        for (index, &pos) in path.iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            test_util::update(&mut gizmo, interaction, &mut targets);
+        }
+        assert!(DVec3::from(targets[0].translation).x > 0.4);
+        assert_eq!(weights(&gizmo, Falloff::Linear), [1.0, 0.5, 0.0]);
+
+        // Once released, they are measured from the moved gizmo
+        test_util::update(&mut gizmo, test_util::hover(path[1]), &mut targets);
+        assert_ne!(weights(&gizmo, Falloff::Linear), [1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn mirrored_targets_are_dragged_like_their_visible_axes() {
+        let mirrored = Transform {
+            scale: DVec3::new(-1.0, 1.0, 1.0).into(),
+            ..Default::default()
+        };
+        let local = |modes| GizmoConfig {
+            orientation: GizmoOrientation::Local,
+            ..test_util::config(modes)
+        };
+
+        // The X arrow points along the visible X axis, which is flipped
+        let mut gizmo = Gizmo::new(local(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [mirrored];
+        test_util::update(&mut gizmo, test_util::hover(CENTER), &mut targets);
+        let flipped_arrow = CENTER - Vec2::new(50.0, 0.0);
+        assert!(gizmo.hovered((X_ARROW.x, X_ARROW.y)).is_none());
+        assert!(matches!(
+            gizmo.hovered((flipped_arrow.x, flipped_arrow.y)),
+            Some((GizmoMode::TranslateX, GizmoDirection::X, _))
+        ));
+
+        // Dragging the arrow moves the target along with the pointer
+        let path = test_util::offset_path(&gizmo, flipped_arrow, &[DVec3::X * -0.3]);
+        test_util::drag(&mut gizmo, &path, &mut targets);
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            translation.abs_diff_eq(DVec3::X * -0.3, 1e-6),
+            "{translation}"
+        );
+        assert_eq!(DVec3::from(targets[0].scale), DVec3::new(-1.0, 1.0, 1.0));
+
+        // Rotating turns a mirrored target the same way on screen as a plain one
+        let rotate = |target: Transform| {
+            let mut gizmo = Gizmo::new(local(EnumSet::only(GizmoMode::RotateZ)));
+            let mut targets = [target];
+            test_util::drag(
+                &mut gizmo,
+                &test_util::ring_path(&[0.0, 30.0, 60.0]),
+                &mut targets,
+            );
+            DQuat::from(targets[0].rotation)
+        };
+        let plain_rotation = rotate(Transform::default());
+        let mirrored_rotation = rotate(mirrored);
+        assert!(plain_rotation.angle_between(DQuat::IDENTITY) > 0.5);
+        assert!(
+            mirrored_rotation.abs_diff_eq(plain_rotation, 1e-6),
+            "{mirrored_rotation} {plain_rotation}"
+        );
+    }
+}
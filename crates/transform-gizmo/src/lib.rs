@@ -12,6 +12,18 @@
 //! all you will need to do is give [`Gizmo::update`] sufficient
 //! information about user interaction, in the form of [`GizmoInteraction`].
 //!
+//! The gizmo does not depend on any UI framework or rendering context. Picking and dragging only
+//! need a [`GizmoConfig`] and [`GizmoInteraction`], so the gizmo can also be driven headlessly,
+//! for example from tests or a custom renderer.
+//!
+//! Matrices, vectors and quaternions are passed in and out as [`mint`] types,
+//! so any math library with `mint` conversions can be used without depending on `glam` directly.
+//!
+//! The handles follow the world axes, and everything that depends on the camera, such as the
+//! view handles and the arcball, is derived from the view matrix. The plane handles and the
+//! rotation rings are oriented for a Y-up world by default. For Z-up worlds, set
+//! [`GizmoConfig::world_up`].
+//!
 //! For rendering the gizmo, [`Gizmo::draw`] provides vertices in viewport coordinates that can be easily rendered
 //! with your favorite graphics APIs.
 //!
@@ -20,10 +32,15 @@
 
 mod shape;
 mod subgizmo;
+#[cfg(test)]
+mod test_util;
 
+pub mod builder;
 pub mod config;
 pub mod gizmo;
 pub mod math;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra_interop;
 
 pub mod prelude;
 
@@ -3,6 +3,9 @@ pub use glam::{DMat3, DMat4, DQuat, DVec2, DVec3, DVec4, Mat4, Quat, Vec3, Vec4S
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Transform {
+    /// Scale along each local axis. Mirrored transforms, with a negative determinant,
+    /// are represented by a negative scale on an odd number of axes. The rotation is
+    /// always a proper rotation.
     pub scale: mint::Vector3<f64>,
     pub rotation: mint::Quaternion<f64>,
     pub translation: mint::Vector3<f64>,
@@ -30,6 +33,66 @@ impl Transform {
             translation: translation.into(),
         }
     }
+
+    /// Decomposes a model matrix into scale, rotation and translation.
+    ///
+    /// Mirrored matrices (with a negative determinant) result in a negative X scale.
+    ///
+    /// ```
+    /// use transform_gizmo::math::{DMat4, DQuat, DVec3, Transform};
+    ///
+    /// let matrix = DMat4::from_scale_rotation_translation(
+    ///     DVec3::new(1.0, 2.0, 3.0),
+    ///     DQuat::from_rotation_y(1.0),
+    ///     DVec3::new(4.0, 5.0, 6.0),
+    /// );
+    /// let transform = Transform::from_model_matrix(matrix);
+    ///
+    /// assert!(DMat4::from(transform.model_matrix()).abs_diff_eq(matrix, 1e-10));
+    /// ```
+    pub fn from_model_matrix(matrix: impl Into<mint::ColumnMatrix4<f64>>) -> Self {
+        let (scale, rotation, translation) =
+            DMat4::from(matrix.into()).to_scale_rotation_translation();
+
+        Self::from_scale_rotation_translation(scale, rotation, translation)
+    }
+
+    /// Composes the scale, rotation and translation into a model matrix.
+    pub fn model_matrix(&self) -> mint::ColumnMatrix4<f64> {
+        DMat4::from_scale_rotation_translation(
+            self.scale.into(),
+            self.rotation.into(),
+            self.translation.into(),
+        )
+        .into()
+    }
+
+    /// Composes the scale, rotation and translation into a column-major `f32` matrix,
+    /// ready to be uploaded for rendering.
+    ///
+    /// The transforms returned by [`crate::Gizmo::update`] can be converted with this.
+    ///
+    /// ```
+    /// use transform_gizmo::math::{DQuat, DVec3, Mat4, Quat, Transform, Vec3};
+    ///
+    /// let transform = Transform::from_scale_rotation_translation(
+    ///     DVec3::new(1.0, 2.0, 3.0),
+    ///     DQuat::from_rotation_z(0.5),
+    ///     DVec3::new(-1.0, 0.0, 1.0),
+    /// );
+    ///
+    /// let expected = Mat4::from_translation(Vec3::new(-1.0, 0.0, 1.0))
+    ///     * Mat4::from_quat(Quat::from_rotation_z(0.5))
+    ///     * Mat4::from_scale(Vec3::new(1.0, 2.0, 3.0));
+    ///
+    /// let matrix = Mat4::from_cols_array_2d(&transform.model_matrix_f32());
+    /// assert!(matrix.abs_diff_eq(expected, 1e-6));
+    /// ```
+    pub fn model_matrix_f32(&self) -> [[f32; 4]; 4] {
+        DMat4::from(self.model_matrix())
+            .as_mat4()
+            .to_cols_array_2d()
+    }
 }
 
 /// Creates a matrix that represents rotation between two 3d vectors
@@ -53,28 +116,48 @@ pub(crate) fn rotation_align(from: DVec3, to: DVec3) -> DMat3 {
     ])
 }
 
+/// Rays whose directions have a cross product shorter than this are considered parallel.
+/// For unit directions this is the sine of the angle between them, roughly 0.57 degrees.
+pub const RAY_PARALLEL_EPSILON: f64 = 1e-2;
+
 /// Finds points on two rays that are closest to each other.
 /// This can be used to determine the shortest distance between those two rays.
 ///
+/// The rays are given by their origins and directions, which must be normalized.
+/// Returns the distances of the closest points along the first and second ray respectively.
+/// The distances are not limited to positive values, so the rays are treated as lines.
+///
+/// Returns [`None`] if the rays are nearly parallel (see [`RAY_PARALLEL_EPSILON`]),
+/// in which case the closest points are not well-defined.
+///
+/// ```
+/// use transform_gizmo::math::{ray_to_ray, DVec3};
+///
+/// // A ray along the X axis, and a ray along the Z axis passing above it at x = 2
+/// let (ta, tb) = ray_to_ray(DVec3::ZERO, DVec3::X, DVec3::new(2.0, 1.0, -3.0), DVec3::Z).unwrap();
+///
+/// assert!((ta - 2.0).abs() < 1e-10);
+/// assert!((tb - 3.0).abs() < 1e-10);
+///
+/// assert_eq!(ray_to_ray(DVec3::ZERO, DVec3::X, DVec3::Y, DVec3::X), None);
+/// ```
+///
 /// Credit: Practical Geometry Algorithms by Daniel Sunday: <http://geomalgorithms.com/code.html>
-pub(crate) fn ray_to_ray(a1: DVec3, adir: DVec3, b1: DVec3, bdir: DVec3) -> (f64, f64) {
+pub fn ray_to_ray(a1: DVec3, adir: DVec3, b1: DVec3, bdir: DVec3) -> Option<(f64, f64)> {
+    if adir.cross(bdir).length() < RAY_PARALLEL_EPSILON {
+        return None;
+    }
+
     let b = adir.dot(bdir);
     let w = a1 - b1;
     let d = adir.dot(w);
     let e = bdir.dot(w);
     let dot = 1.0 - b * b;
-    let ta;
-    let tb;
 
-    if dot < 1e-8 {
-        ta = 0.0;
-        tb = e;
-    } else {
-        ta = (b * e - d) / dot;
-        tb = (e - b * d) / dot;
-    }
+    let ta = (b * e - d) / dot;
+    let tb = (e - b * d) / dot;
 
-    (ta, tb)
+    Some((ta, tb))
 }
 
 /// Finds points on two segments that are closest to each other.
@@ -144,8 +227,25 @@ pub(crate) fn segment_to_segment(a1: DVec3, a2: DVec3, b1: DVec3, b2: DVec3) ->
     (ta, tb)
 }
 
-/// Finds the intersection point of a ray and a plane
-pub(crate) fn intersect_plane(
+/// Finds the intersection point of a ray and a plane.
+///
+/// The plane is given by its normal and any point on it. The intersection is
+/// `ray_origin + ray_dir * t`, so `t` is measured in multiples of the ray direction's length.
+/// Returns true and writes `t` if the ray hits the plane in front of its origin.
+/// Rays that are parallel to the plane do not hit it. See also [`ray_to_plane`].
+///
+/// ```
+/// use transform_gizmo::math::{intersect_plane, DVec3};
+///
+/// let ray_origin = DVec3::new(0.0, 2.0, 0.0);
+///
+/// let mut t = 0.0;
+/// let hit = intersect_plane(DVec3::Y, DVec3::ZERO, ray_origin, DVec3::NEG_Y, &mut t);
+///
+/// assert!(hit);
+/// assert_eq!(t, 2.0);
+/// ```
+pub fn intersect_plane(
     plane_normal: DVec3,
     plane_origin: DVec3,
     ray_origin: DVec3,
@@ -162,6 +262,34 @@ pub(crate) fn intersect_plane(
     }
 }
 
+/// Finds the intersection of a ray and a plane, like [`intersect_plane`].
+///
+/// Returns the distance along the ray in multiples of the ray direction's length,
+/// and the intersection point. [`None`] is returned if the ray does not hit the plane
+/// in front of its origin.
+///
+/// ```
+/// use transform_gizmo::math::{ray_to_plane, DVec3};
+///
+/// let ray_origin = DVec3::new(1.0, 2.0, 3.0);
+///
+/// let (t, point) = ray_to_plane(DVec3::Y, DVec3::ZERO, ray_origin, DVec3::NEG_Y).unwrap();
+/// assert_eq!(t, 2.0);
+/// assert_eq!(point, DVec3::new(1.0, 0.0, 3.0));
+///
+/// assert_eq!(ray_to_plane(DVec3::Y, DVec3::ZERO, ray_origin, DVec3::Y), None);
+/// ```
+pub fn ray_to_plane(
+    plane_normal: DVec3,
+    plane_origin: DVec3,
+    ray_origin: DVec3,
+    ray_dir: DVec3,
+) -> Option<(f64, DVec3)> {
+    let mut t = 0.0;
+    intersect_plane(plane_normal, plane_origin, ray_origin, ray_dir, &mut t)
+        .then(|| (t, ray_origin + ray_dir * t))
+}
+
 /// Finds the intersection point of a ray and a plane
 /// and distance from the intersection to the plane origin
 pub(crate) fn ray_to_plane_origin(
@@ -181,13 +309,49 @@ pub(crate) fn ray_to_plane_origin(
     }
 }
 
-/// Rounds given value to the nearest interval
-pub(crate) fn round_to_interval(val: f64, interval: f64) -> f64 {
+/// Rounds given value to the nearest multiple of the interval.
+///
+/// Values exactly halfway between two multiples are rounded away from zero,
+/// so rounding is symmetric around zero. A zero interval returns the value unchanged.
+///
+/// ```
+/// use transform_gizmo::math::round_to_interval;
+///
+/// assert_eq!(round_to_interval(0.9, 0.5), 1.0);
+/// assert_eq!(round_to_interval(17.0, 5.0), 15.0);
+///
+/// assert_eq!(round_to_interval(0.25, 0.5), 0.5);
+/// assert_eq!(round_to_interval(-0.25, 0.5), -0.5);
+/// assert_eq!(round_to_interval(-0.2, 0.5), 0.0);
+///
+/// assert_eq!(round_to_interval(0.3, 0.0), 0.3);
+/// assert!((round_to_interval(0.3, 1e-9) - 0.3).abs() < 1e-9);
+/// ```
+pub fn round_to_interval(val: f64, interval: f64) -> f64 {
+    if interval == 0.0 {
+        return val;
+    }
+
     (val / interval).round() * interval
 }
 
+/// Rounds given value towards zero to a multiple of the interval.
+/// A zero interval returns the value unchanged.
+pub(crate) fn truncate_to_interval(val: f64, interval: f64) -> f64 {
+    if interval == 0.0 {
+        return val;
+    }
+
+    (val / interval).trunc() * interval
+}
+
 /// Calculates 2d screen coordinates from 3d world coordinates
 pub(crate) fn world_to_screen(viewport: Rect, mvp: DMat4, pos: DVec3) -> Option<Pos2> {
+    world_to_screen_depth(viewport, mvp, pos).map(|(pos, _)| pos)
+}
+
+/// Calculates 2d screen coordinates and normalized device depth from 3d world coordinates
+pub(crate) fn world_to_screen_depth(viewport: Rect, mvp: DMat4, pos: DVec3) -> Option<(Pos2, f32)> {
     let mut pos = mvp * DVec4::from((pos, 1.0));
 
     if pos.w < 1e-10 {
@@ -199,9 +363,12 @@ pub(crate) fn world_to_screen(viewport: Rect, mvp: DMat4, pos: DVec3) -> Option<
 
     let center = viewport.center();
 
-    Some(Pos2::new(
-        (center.x as f64 + pos.x * viewport.width() as f64 / 2.0) as f32,
-        (center.y as f64 + pos.y * viewport.height() as f64 / 2.0) as f32,
+    Some((
+        Pos2::new(
+            (center.x as f64 + pos.x * viewport.width() as f64 / 2.0) as f32,
+            (center.y as f64 + pos.y * viewport.height() as f64 / 2.0) as f32,
+        ),
+        pos.z as f32,
     ))
 }
 
@@ -221,3 +388,175 @@ pub(crate) fn screen_to_world(viewport: Rect, mat: DMat4, pos: Pos2, z: f64) ->
 
     world_pos.xyz()
 }
+
+#[cfg(test)]
+mod tests {
+    use enumset::EnumSet;
+
+    use super::*;
+    use crate::test_util::{self, X_ARROW};
+    use crate::{Gizmo, GizmoConfig, GizmoMode, GizmoOrientation};
+
+    #[test]
+    fn model_matrix_round_trips_through_a_drag() {
+        let matrix = DMat4::from_scale_rotation_translation(
+            DVec3::new(1.0, 2.0, 0.5),
+            DQuat::from_rotation_y(0.3),
+            DVec3::ZERO,
+        );
+        let transform = Transform::from_model_matrix(matrix);
+        assert!(DVec3::from(transform.scale).abs_diff_eq(DVec3::new(1.0, 2.0, 0.5), 1e-10));
+        assert!(DMat4::from(transform.model_matrix()).abs_diff_eq(matrix, 1e-10));
+
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut targets = [transform];
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.5]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let total = test_util::translation_total(results.last().unwrap());
+        let expected = DMat4::from_translation(total) * matrix;
+        assert!(DMat4::from(targets[0].model_matrix()).abs_diff_eq(expected, 1e-9));
+    }
+
+    #[test]
+    fn mirrored_model_matrix_round_trips() {
+        let matrix = DMat4::from_scale_rotation_translation(
+            DVec3::new(-1.0, 2.0, 3.0),
+            DQuat::from_rotation_x(0.7),
+            DVec3::new(4.0, 5.0, 6.0),
+        );
+        let transform = Transform::from_model_matrix(matrix);
+
+        let scale = DVec3::from(transform.scale);
+        assert!(scale.x < 0.0 && scale.y > 0.0 && scale.z > 0.0, "{scale}");
+        assert!(DQuat::from(transform.rotation).is_normalized());
+        assert!(DMat4::from(transform.model_matrix()).abs_diff_eq(matrix, 1e-10));
+    }
+
+    #[test]
+    fn model_matrix_f32_composes_in_trs_order() {
+        let scale = DVec3::new(1.0, 2.0, 3.0);
+        let rotation = DQuat::from_rotation_z(0.4);
+        let config = GizmoConfig {
+            orientation: GizmoOrientation::Local,
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        };
+
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::from_scale_rotation_translation(
+            scale,
+            rotation,
+            DVec3::ZERO,
+        )];
+        let grab = test_util::CENTER + Vec2::angled(-0.4) * 50.0;
+        let path = test_util::offset_path(&gizmo, grab, &[rotation * DVec3::X * 0.5]);
+        test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let target = targets[0];
+        let translation = DVec3::from(target.translation);
+        assert!(
+            translation.abs_diff_eq(rotation * DVec3::X * 0.5, 1e-4),
+            "{translation}"
+        );
+
+        let expected = Mat4::from_translation(translation.as_vec3())
+            * Mat4::from_quat(DQuat::from(target.rotation).as_quat())
+            * Mat4::from_scale(DVec3::from(target.scale).as_vec3());
+        let matrix = Mat4::from_cols_array_2d(&target.model_matrix_f32());
+        assert!(matrix.abs_diff_eq(expected, 1e-6));
+        assert!(matrix.abs_diff_eq(DMat4::from(target.model_matrix()).as_mat4(), 1e-6));
+    }
+
+    #[test]
+    fn public_intersection_helpers_agree() {
+        let (normal, origin) = (
+            DVec3::new(0.0, 1.0, 1.0).normalize(),
+            DVec3::new(0.0, 1.0, 0.0),
+        );
+        let ray_origin = DVec3::new(2.0, 5.0, 1.0);
+
+        // Directions are not normalized, so distances are in multiples of their length
+        let ray_dir = DVec3::new(0.5, -2.0, -1.0);
+        let (t, point) = ray_to_plane(normal, origin, ray_origin, ray_dir).unwrap();
+        let mut intersect_t = 0.0;
+        assert!(intersect_plane(
+            normal,
+            origin,
+            ray_origin,
+            ray_dir,
+            &mut intersect_t
+        ));
+        assert_eq!(t, intersect_t);
+        assert!(point.abs_diff_eq(ray_origin + ray_dir * t, 1e-12));
+        assert!((point - origin).dot(normal).abs() < 1e-12);
+
+        // Planes behind the ray origin are not hit
+        assert_eq!(ray_to_plane(normal, origin, ray_origin, -ray_dir), None);
+        assert!(!intersect_plane(
+            normal,
+            origin,
+            ray_origin,
+            -ray_dir,
+            &mut intersect_t
+        ));
+
+        // The closest points of two skew lines are connected perpendicularly to both
+        let (a, a_dir) = (
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.0, 1.0, 0.0).normalize(),
+        );
+        let (b, b_dir) = (
+            DVec3::new(0.0, 0.0, 3.0),
+            DVec3::new(0.0, 1.0, -1.0).normalize(),
+        );
+        let (ta, tb) = ray_to_ray(a, a_dir, b, b_dir).unwrap();
+        let connection = (b + b_dir * tb) - (a + a_dir * ta);
+        assert!(connection.dot(a_dir).abs() < 1e-10);
+        assert!(connection.dot(b_dir).abs() < 1e-10);
+
+        let nearly_parallel = (a_dir + DVec3::Z * RAY_PARALLEL_EPSILON * 0.5).normalize();
+        assert_eq!(ray_to_ray(a, a_dir, b, nearly_parallel), None);
+
+        assert_eq!(round_to_interval(-0.74, 0.5), -0.5);
+        assert_eq!(round_to_interval(0.74, 0.0), 0.74);
+    }
+
+    #[test]
+    fn interval_rounding_is_symmetric_around_zero() {
+        // Exact half steps round away from zero on both sides
+        for (val, expected) in [
+            (0.25, 0.5),
+            (0.75, 1.0),
+            (1.25, 1.5),
+            (0.2, 0.0),
+            (1.3, 1.5),
+        ] {
+            assert_eq!(round_to_interval(val, 0.5), expected, "{val}");
+            assert_eq!(round_to_interval(-val, 0.5), -expected, "{val}");
+        }
+
+        for (val, expected) in [(-17.0, -15.0), (-18.0, -20.0), (-12.5, -15.0), (-2.4, 0.0)] {
+            assert_eq!(round_to_interval(val, 5.0), expected, "{val}");
+        }
+
+        for val in [0.3, -7.5, 0.0, f64::MAX] {
+            assert_eq!(round_to_interval(val, 0.0), val);
+        }
+
+        for val in [0.3, -7.5, 0.0] {
+            assert!(round_to_interval(val, 1e-12).is_finite());
+            assert!((round_to_interval(val, 1e-9) - val).abs() <= 0.5e-9);
+            assert!((round_to_interval(val, 1e-300) - val).abs() <= 1e-15);
+        }
+    }
+
+    #[test]
+    fn interval_truncation_is_symmetric_around_zero() {
+        for (val, expected) in [(0.25, 0.0), (0.75, 0.5), (1.0, 1.0), (1.49, 1.0)] {
+            assert_eq!(truncate_to_interval(val, 0.5), expected, "{val}");
+            assert_eq!(truncate_to_interval(-val, 0.5), -expected, "{val}");
+        }
+
+        assert_eq!(truncate_to_interval(-0.3, 0.0), -0.3);
+    }
+}
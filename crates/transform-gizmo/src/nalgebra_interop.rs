@@ -0,0 +1,131 @@
+//! Conversions between [`nalgebra`] types and the types used by the gizmo.
+//!
+//! The gizmo uses `glam` internally, and `mint` types in its public interface.
+//! These helpers avoid the intermediate conversions at the gizmo boundary.
+
+use nalgebra::{Isometry3, Matrix4, Quaternion, Scale3, UnitQuaternion, Vector3};
+
+use crate::math::Transform;
+use crate::GizmoResult;
+
+/// Converts a matrix to the format used by [`crate::GizmoConfig::view_matrix`]
+/// and [`crate::GizmoConfig::projection_matrix`].
+pub fn to_gizmo_matrix(matrix: &Matrix4<f32>) -> mint::RowMatrix4<f64> {
+    mint::ColumnMatrix4::from(matrix.cast::<f64>()).into()
+}
+
+/// Converts a config matrix back to an `nalgebra` matrix.
+pub fn from_gizmo_matrix(matrix: mint::RowMatrix4<f64>) -> Matrix4<f32> {
+    Matrix4::<f64>::from(mint::ColumnMatrix4::from(matrix)).cast()
+}
+
+/// Converts an isometry, such as a camera view transform, to a config matrix.
+pub fn isometry_to_gizmo_matrix(isometry: &Isometry3<f32>) -> mint::RowMatrix4<f64> {
+    to_gizmo_matrix(&isometry.to_homogeneous())
+}
+
+impl From<Isometry3<f64>> for Transform {
+    fn from(isometry: Isometry3<f64>) -> Self {
+        Self {
+            scale: Vector3::repeat(1.0).into(),
+            rotation: isometry.rotation.into(),
+            translation: isometry.translation.vector.into(),
+        }
+    }
+}
+
+impl From<Transform> for Isometry3<f64> {
+    /// Scale of the transform is discarded.
+    fn from(transform: Transform) -> Self {
+        Self::from_parts(
+            Vector3::from(transform.translation).into(),
+            UnitQuaternion::new_normalize(Quaternion::from(transform.rotation)),
+        )
+    }
+}
+
+impl GizmoResult {
+    /// Total translation of a translation result.
+    pub fn nalgebra_translation(&self) -> Option<Vector3<f64>> {
+        match *self {
+            Self::Translation { total, .. } => Some(total.into()),
+            _ => None,
+        }
+    }
+
+    /// Total rotation of a rotation or arcball result.
+    pub fn nalgebra_rotation(&self) -> Option<UnitQuaternion<f64>> {
+        match *self {
+            Self::Rotation { axis, total, .. } => Some(UnitQuaternion::from_axis_angle(
+                &nalgebra::Unit::new_normalize(Vector3::from(axis)),
+                total,
+            )),
+            Self::Arcball { total, .. } => {
+                Some(UnitQuaternion::new_normalize(Quaternion::from(total)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Total multiplicative scale of a scale result.
+    pub fn nalgebra_scale(&self) -> Option<Scale3<f64>> {
+        match *self {
+            Self::Scale { total, .. } => Some(Vector3::from(total).into()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use enumset::EnumSet;
+    use glam::{DMat4, DQuat, DVec3};
+
+    use super::*;
+    use crate::test_util;
+    use crate::{Gizmo, GizmoMode};
+
+    #[test]
+    fn view_matrix_round_trips() {
+        let view = Isometry3::look_at_rh(
+            &[0.0, 0.0, 10.0].into(),
+            &[0.0, 0.0, 0.0].into(),
+            &Vector3::y(),
+        );
+
+        let matrix = isometry_to_gizmo_matrix(&view);
+        let expected = test_util::view_matrix(test_util::CAMERA_POSITION);
+        assert!(DMat4::from(matrix).abs_diff_eq(expected, 1e-6));
+        assert!(from_gizmo_matrix(matrix).relative_eq(&view.to_homogeneous(), 1e-6, 1e-6));
+    }
+
+    #[test]
+    fn result_rotation_round_trips() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let mut targets = [Transform::default()];
+        let path = test_util::ring_path(&[0.0, 30.0, 60.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+        let result = results.last().unwrap();
+
+        let GizmoResult::Rotation { axis, total, .. } = *result else {
+            panic!("expected a rotation result, got {result:?}");
+        };
+        let expected = DQuat::from_axis_angle(DVec3::from(axis), total);
+        let rotation = result.nalgebra_rotation().unwrap();
+        assert!(DQuat::from(mint::Quaternion::from(rotation)).abs_diff_eq(expected, 1e-9));
+        assert!(
+            rotation.angle_to(&UnitQuaternion::new_normalize(Quaternion::from(
+                targets[0].rotation
+            ))) < 1e-4
+        );
+
+        assert!(result.nalgebra_translation().is_none());
+        assert!(result.nalgebra_scale().is_none());
+
+        let isometry = Isometry3::from(targets[0]);
+        let transform = Transform::from(isometry);
+        let rotation = DQuat::from(transform.rotation);
+        assert!(rotation.abs_diff_eq(targets[0].rotation.into(), 1e-9));
+        assert_eq!(transform.translation, targets[0].translation);
+    }
+}
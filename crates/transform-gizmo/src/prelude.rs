@@ -1,5 +1,12 @@
-pub use crate::config::{GizmoConfig, GizmoDirection, GizmoMode, GizmoOrientation, GizmoVisuals};
-pub use crate::gizmo::{Gizmo, GizmoDrawData, GizmoInteraction, GizmoResult};
+pub use crate::builder::{GizmoBuildError, GizmoBuilder};
+pub use crate::config::{
+    Falloff, GizmoConfig, GizmoDirection, GizmoMode, GizmoOrientation, GizmoSizeMode, GizmoVisuals,
+    Handedness, InputMode, StrokeCap, TransformKind,
+};
+pub use crate::gizmo::{
+    ActiveStateSnapshot, AxisLabel, DepthTest, Gizmo, GizmoDrawData, GizmoError, GizmoInteraction,
+    GizmoPhase, GizmoResult, InteractionState, Ray, RelativeTransform, SnapStepCallback,
+};
 
 pub use enumset::{enum_set, EnumSet};
 
@@ -6,14 +6,30 @@ use epaint::{Mesh, TessellationOptions, Tessellator, TextureId};
 pub(crate) use epaint::{PathStroke, Shape, Stroke};
 use glam::{DMat4, DVec3};
 
-use crate::math::world_to_screen;
-
-const STEPS_PER_RAD: f64 = 20.0;
-
+use crate::math::world_to_screen_depth;
+use crate::GizmoDrawData;
+
+/// Number of segments in a full circle, if the size of the circle on screen is not known
+const DEFAULT_CIRCLE_SEGMENTS: f64 = 128.0;
+/// Limits for the number of segments in a full circle, when chosen from its size on screen
+const MIN_CIRCLE_SEGMENTS: f64 = 16.0;
+const MAX_CIRCLE_SEGMENTS: f64 = 512.0;
+/// Maximum distance between the circle segments and the true circle, in pixels.
+/// Rings of the default size get about 136 segments.
+const CIRCLE_TOLERANCE: f64 = 0.02;
+
+/// Builds meshes for gizmo shapes given in gizmo local space.
+///
+/// Points are projected to the viewport before tessellation, so lines are built in
+/// screen space. Stroke widths are in points and stay constant along a line,
+/// regardless of perspective foreshortening.
 pub(crate) struct ShapeBuidler {
     mvp: DMat4,
     viewport: Rect,
     pixels_per_point: f32,
+    circle_segments: Option<usize>,
+    /// Size of a viewport point in gizmo local units, used for choosing the circle segments
+    scale_factor: f32,
 }
 
 impl ShapeBuidler {
@@ -22,10 +38,59 @@ impl ShapeBuidler {
             mvp,
             viewport,
             pixels_per_point,
+            circle_segments: None,
+            scale_factor: 0.0,
         }
     }
 
-    fn tessellate_shape(&self, shape: Shape) -> Mesh {
+    /// Sets the number of segments in full circles. If not given, the number is chosen
+    /// from the size of each circle on screen, using the size of a viewport point in
+    /// gizmo local units.
+    pub(crate) fn with_circle_segments(
+        mut self,
+        circle_segments: Option<usize>,
+        scale_factor: f32,
+    ) -> Self {
+        self.circle_segments = circle_segments;
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// Number of segments in a full circle of given radius
+    fn circle_segments(&self, radius: f64) -> f64 {
+        if let Some(segments) = self.circle_segments {
+            return segments.max(3) as f64;
+        }
+
+        if self.scale_factor <= 0.0 {
+            return DEFAULT_CIRCLE_SEGMENTS;
+        }
+
+        let radius_pixels = radius.abs() / self.scale_factor as f64 * self.pixels_per_point as f64;
+
+        // Segment angle at which the segments stay within the tolerance from the circle
+        let max_angle = 2.0 * (1.0 - CIRCLE_TOLERANCE / radius_pixels).max(-1.0).acos();
+
+        (TAU / max_angle)
+            .ceil()
+            .clamp(MIN_CIRCLE_SEGMENTS, MAX_CIRCLE_SEGMENTS)
+    }
+
+    /// Number of points in an arc of given radius and angle
+    fn steps(&self, radius: f64, angle: f64) -> usize {
+        (self.circle_segments(radius) * angle.abs() / TAU).ceil() as usize + 1
+    }
+
+    /// Tessellates the shape into draw data. The shape is outlined by the given
+    /// projected points, and the depth of each vertex is interpolated along the
+    /// nearest edge of the outline.
+    fn tessellate_shape(
+        &self,
+        shape: Shape,
+        points: &[Pos2],
+        depths: &[f32],
+        closed: bool,
+    ) -> GizmoDrawData {
         let mut tessellator = Tessellator::new(
             self.pixels_per_point,
             TessellationOptions {
@@ -36,32 +101,51 @@ impl ShapeBuidler {
             Default::default(),
         );
 
-        let mut mesh = Mesh::default();
+        let mut mesh = GizmoDrawData::default();
         tessellator.tessellate_shape(shape, &mut mesh);
 
         mesh.texture_id = TextureId::default();
-        mesh
+
+        let mut draw_data = GizmoDrawData::from(mesh);
+        for (depth, vertex) in draw_data.depths.iter_mut().zip(&draw_data.vertices) {
+            *depth = outline_depth(Pos2::new(vertex[0], vertex[1]), points, depths, closed);
+        }
+
+        draw_data
     }
 
-    fn arc_points(&self, radius: f64, start_angle: f64, end_angle: f64) -> Vec<Pos2> {
-        let angle = f64::clamp(end_angle - start_angle, -TAU, TAU);
+    /// Projects points to screen space, along with their depths.
+    /// Points behind the camera are left out.
+    ///
+    /// The points are projected as they are iterated, so no intermediate buffer is needed.
+    fn project_points(&self, points: impl IntoIterator<Item = DVec3>) -> (Vec<Pos2>, Vec<f32>) {
+        let points = points.into_iter();
+        let capacity = points.size_hint().0;
+        let mut projected = (Vec::with_capacity(capacity), Vec::with_capacity(capacity));
+
+        for point in points {
+            if let Some((pos, depth)) = world_to_screen_depth(self.viewport, self.mvp, point) {
+                projected.0.push(pos);
+                projected.1.push(depth);
+            }
+        }
 
-        let step_count = steps(angle);
-        let mut points = Vec::with_capacity(step_count);
+        projected
+    }
 
+    fn arc_points(&self, radius: f64, start_angle: f64, end_angle: f64) -> (Vec<Pos2>, Vec<f32>) {
+        let angle = f64::clamp(end_angle - start_angle, -TAU, TAU);
+
+        let step_count = self.steps(radius, angle);
         let step_size = angle / (step_count - 1) as f64;
 
-        for step in (0..step_count).map(|i| step_size * i as f64) {
+        self.project_points((0..step_count).map(|i| {
+            let step = step_size * i as f64;
             let x = f64::cos(start_angle + step) * radius;
             let z = f64::sin(start_angle + step) * radius;
 
-            points.push(DVec3::new(x, 0.0, z));
-        }
-
-        points
-            .into_iter()
-            .filter_map(|point| self.vec3_to_pos2(point))
-            .collect::<Vec<_>>()
+            DVec3::new(x, 0.0, z)
+        }))
     }
 
     pub(crate) fn arc(
@@ -70,8 +154,8 @@ impl ShapeBuidler {
         start_angle: f64,
         end_angle: f64,
         stroke: impl Into<PathStroke>,
-    ) -> Mesh {
-        let mut points = self.arc_points(radius, start_angle, end_angle);
+    ) -> GizmoDrawData {
+        let (mut points, mut depths) = self.arc_points(radius, start_angle, end_angle);
 
         let closed = points
             .first()
@@ -79,15 +163,18 @@ impl ShapeBuidler {
             .filter(|(first, last)| first.distance(**last) < 1e-2)
             .is_some();
 
-        self.tessellate_shape(if closed {
+        if closed {
             points.pop();
-            Shape::closed_line(points, stroke.into())
+            depths.pop();
+            let shape = Shape::closed_line(points.clone(), stroke.into());
+            self.tessellate_shape(shape, &points, &depths, true)
         } else {
-            Shape::line(points, stroke.into())
-        })
+            let shape = Shape::line(points.clone(), stroke.into());
+            self.tessellate_shape(shape, &points, &depths, false)
+        }
     }
 
-    pub(crate) fn circle(&self, radius: f64, stroke: impl Into<PathStroke>) -> Mesh {
+    pub(crate) fn circle(&self, radius: f64, stroke: impl Into<PathStroke>) -> GizmoDrawData {
         self.arc(radius, 0.0, TAU, stroke)
     }
 
@@ -96,11 +183,13 @@ impl ShapeBuidler {
         radius: f64,
         color: Color32,
         stroke: impl Into<PathStroke>,
-    ) -> Mesh {
-        let mut points = self.arc_points(radius, 0.0, TAU);
+    ) -> GizmoDrawData {
+        let (mut points, mut depths) = self.arc_points(radius, 0.0, TAU);
         points.pop();
+        depths.pop();
 
-        self.tessellate_shape(Shape::convex_polygon(points, color, stroke.into()))
+        let shape = Shape::convex_polygon(points.clone(), color, stroke.into());
+        self.tessellate_shape(shape, &points, &depths, true)
     }
 
     pub(crate) fn line_segment(
@@ -108,39 +197,67 @@ impl ShapeBuidler {
         from: DVec3,
         to: DVec3,
         stroke: impl Into<PathStroke>,
-    ) -> Mesh {
+    ) -> GizmoDrawData {
         let mut points: [Pos2; 2] = Default::default();
+        let mut depths = [0.0; 2];
 
         for (i, point) in points.iter_mut().enumerate() {
-            if let Some(pos) = world_to_screen(self.viewport, self.mvp, [from, to][i]) {
+            if let Some((pos, depth)) =
+                world_to_screen_depth(self.viewport, self.mvp, [from, to][i])
+            {
                 *point = pos;
+                depths[i] = depth;
             } else {
-                return Mesh::default();
+                return GizmoDrawData::default();
             }
         }
 
-        self.tessellate_shape(Shape::LineSegment {
-            points,
-            stroke: stroke.into(),
-        })
+        self.tessellate_shape(
+            Shape::LineSegment {
+                points,
+                stroke: stroke.into(),
+            },
+            &points,
+            &depths,
+            false,
+        )
+    }
+
+    /// Draws a filled circle with the stroke width as diameter, for rounding the end of a stroke.
+    pub(crate) fn round_cap(&self, point: DVec3, stroke: impl Into<Stroke>) -> GizmoDrawData {
+        let stroke = stroke.into();
+
+        if let Some((center, depth)) = world_to_screen_depth(self.viewport, self.mvp, point) {
+            self.tessellate_shape(
+                Shape::circle_filled(center, stroke.width / 2.0, stroke.color),
+                &[center],
+                &[depth],
+                true,
+            )
+        } else {
+            GizmoDrawData::default()
+        }
     }
 
-    pub(crate) fn arrow(&self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) -> Mesh {
+    pub(crate) fn arrow(&self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) -> GizmoDrawData {
         let stroke = stroke.into();
-        let arrow_start = world_to_screen(self.viewport, self.mvp, from);
-        let arrow_end = world_to_screen(self.viewport, self.mvp, to);
+        let arrow_start = world_to_screen_depth(self.viewport, self.mvp, from);
+        let arrow_end = world_to_screen_depth(self.viewport, self.mvp, to);
 
-        self.tessellate_shape(if let Some((start, end)) = arrow_start.zip(arrow_end) {
+        if let Some(((start, start_depth), (end, end_depth))) = arrow_start.zip(arrow_end) {
             let cross = (end - start).normalized().rot90() * stroke.width / 2.0;
 
-            Shape::convex_polygon(
-                vec![start - cross, start + cross, end],
-                stroke.color,
-                PathStroke::NONE,
+            let points = [start - cross, start + cross, end];
+
+            self.tessellate_shape(
+                Shape::convex_polygon(points.to_vec(), stroke.color, PathStroke::NONE),
+                &points,
+                &[start_depth, start_depth, end_depth],
+                true,
             )
         } else {
-            Shape::Noop
-        })
+            GizmoDrawData::default()
+        }
     }
 
     pub(crate) fn polygon(
@@ -148,30 +265,30 @@ impl ShapeBuidler {
         points: &[DVec3],
         fill: impl Into<Color32>,
         stroke: impl Into<PathStroke>,
-    ) -> Mesh {
-        let points = points
-            .iter()
-            .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos))
-            .collect::<Vec<_>>();
-
-        self.tessellate_shape(if points.len() > 2 {
-            Shape::convex_polygon(points, fill, stroke)
+    ) -> GizmoDrawData {
+        let (points, depths) = self.project_points(points.iter().copied());
+
+        if points.len() > 2 {
+            let shape = Shape::convex_polygon(points.clone(), fill, stroke);
+            self.tessellate_shape(shape, &points, &depths, true)
         } else {
-            Shape::Noop
-        })
+            GizmoDrawData::default()
+        }
     }
 
-    pub(crate) fn polyline(&self, points: &[DVec3], stroke: impl Into<PathStroke>) -> Mesh {
-        let points = points
-            .iter()
-            .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos))
-            .collect::<Vec<_>>();
+    pub(crate) fn polyline(
+        &self,
+        points: &[DVec3],
+        stroke: impl Into<PathStroke>,
+    ) -> GizmoDrawData {
+        let (points, depths) = self.project_points(points.iter().copied());
 
-        self.tessellate_shape(if points.len() > 1 {
-            Shape::line(points, stroke)
+        if points.len() > 1 {
+            let shape = Shape::line(points.clone(), stroke);
+            self.tessellate_shape(shape, &points, &depths, false)
         } else {
-            Shape::Noop
-        })
+            GizmoDrawData::default()
+        }
     }
 
     pub(crate) fn sector(
@@ -181,12 +298,12 @@ impl ShapeBuidler {
         end_angle: f64,
         fill: impl Into<Color32>,
         stroke: impl Into<PathStroke>,
-    ) -> Mesh {
+    ) -> GizmoDrawData {
         let angle_delta = end_angle - start_angle;
-        let step_count = steps(angle_delta.abs());
+        let step_count = self.steps(radius, angle_delta);
 
         if step_count < 2 {
-            return Mesh::default();
+            return GizmoDrawData::default();
         }
 
         let mut points = Vec::with_capacity(step_count + 1);
@@ -215,19 +332,115 @@ impl ShapeBuidler {
             cos_angle = new_cos;
         }
 
-        let points = points
-            .into_iter()
-            .filter_map(|point| self.vec3_to_pos2(point))
-            .collect::<Vec<_>>();
+        let (points, depths) = self.project_points(points);
 
-        self.tessellate_shape(Shape::convex_polygon(points, fill, stroke))
+        let shape = Shape::convex_polygon(points.clone(), fill, stroke);
+        self.tessellate_shape(shape, &points, &depths, true)
     }
+}
 
-    fn vec3_to_pos2(&self, vec: DVec3) -> Option<Pos2> {
-        world_to_screen(self.viewport, self.mvp, vec)
+/// Depth at a viewport position, interpolated along the nearest edge of an outline.
+/// Normalized device depth is linear in viewport space along straight edges.
+fn outline_depth(pos: Pos2, points: &[Pos2], depths: &[f32], closed: bool) -> f32 {
+    let edge_count = if closed || points.len() < 2 {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+
+    let mut nearest_distance = f32::INFINITY;
+    let mut nearest_depth = depths.first().copied().unwrap_or_default();
+
+    for start in 0..edge_count {
+        let end = (start + 1) % points.len();
+        let edge = points[end] - points[start];
+
+        let t = if edge.length_sq() > 0.0 {
+            ((pos - points[start]).dot(edge) / edge.length_sq()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let distance = pos.distance_sq(points[start] + edge * t);
+        if distance < nearest_distance {
+            nearest_distance = distance;
+            nearest_depth = depths[start] + (depths[end] - depths[start]) * t;
+        }
     }
+
+    nearest_depth
 }
 
-fn steps(angle: f64) -> usize {
-    (STEPS_PER_RAD * angle.abs()).ceil().max(1.0) as usize
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::world_to_screen;
+    use crate::test_util;
+
+    #[test]
+    fn line_width_is_constant_along_a_receding_line() {
+        let view = test_util::view_matrix(DVec3::new(5.0, 2.0, 10.0));
+        let mvp = test_util::projection_matrix() * view;
+        let builder = ShapeBuidler::new(mvp, test_util::VIEWPORT, 1.0);
+
+        let (from, to) = (DVec3::Z * 5.0, DVec3::Z * -50.0);
+        let draw_data = builder.line_segment(from, to, (4.0, Color32::WHITE));
+
+        let start = world_to_screen(test_util::VIEWPORT, mvp, from).unwrap();
+        let end = world_to_screen(test_util::VIEWPORT, mvp, to).unwrap();
+        let direction = (end - start).normalized();
+
+        // Half widths at the near and the far end of the line
+        let mut half_widths = [0.0f32; 2];
+        for [x, y] in &draw_data.vertices {
+            let offset = Pos2::new(*x, *y) - start;
+            let along = offset.dot(direction) / start.distance(end);
+            let across = offset.dot(direction.rot90()).abs();
+            let end_index = usize::from(along > 0.5);
+            half_widths[end_index] = half_widths[end_index].max(across);
+        }
+
+        assert!(half_widths[0] >= 2.0, "{half_widths:?}");
+        assert!(
+            (half_widths[0] - half_widths[1]).abs() < 1e-3,
+            "{half_widths:?}"
+        );
+        assert!(draw_data.depths[0] != draw_data.depths[draw_data.depths.len() - 1]);
+    }
+
+    #[test]
+    fn circle_segments_follow_the_size_on_screen() {
+        let mvp =
+            test_util::projection_matrix() * test_util::view_matrix(test_util::CAMERA_POSITION);
+        // A point is 0.01 local units, so the default ring radius is 75 pixels
+        let builder =
+            ShapeBuidler::new(mvp, test_util::VIEWPORT, 1.0).with_circle_segments(None, 0.01);
+
+        let ring = builder.circle_segments(0.75);
+        assert!((128.0..=160.0).contains(&ring), "{ring}");
+        assert_eq!(builder.circle_segments(0.01), MIN_CIRCLE_SEGMENTS);
+        assert!(builder.circle_segments(1.5) > ring);
+
+        // High DPI screens need more segments at the same size in points
+        let high_dpi =
+            ShapeBuidler::new(mvp, test_util::VIEWPORT, 2.0).with_circle_segments(None, 0.01);
+        assert!(high_dpi.circle_segments(0.75) > ring);
+    }
+
+    #[test]
+    fn more_circle_segments_emit_more_vertices() {
+        let mvp =
+            test_util::projection_matrix() * test_util::view_matrix(test_util::CAMERA_POSITION);
+        let vertex_count = |segments| {
+            ShapeBuidler::new(mvp, test_util::VIEWPORT, 1.0)
+                .with_circle_segments(Some(segments), 0.01)
+                .circle(0.75, (4.0, Color32::WHITE))
+                .vertices
+                .len() as f32
+        };
+
+        let (coarse, fine) = (vertex_count(32), vertex_count(128));
+        let ratio = fine / coarse;
+        assert!((3.5..=4.5).contains(&ratio), "{coarse} {fine}");
+    }
 }
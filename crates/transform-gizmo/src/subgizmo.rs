@@ -5,7 +5,11 @@ use std::ops::Deref;
 
 use enum_dispatch::enum_dispatch;
 
-use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDrawData, GizmoResult};
+use crate::{
+    config::{GizmoDirection, GizmoMode, PreparedGizmoConfig, TransformKind},
+    gizmo::{ActiveStateSnapshot, Ray},
+    GizmoDrawData, GizmoResult,
+};
 
 pub(crate) use arcball::ArcballSubGizmo;
 pub(crate) use rotation::RotationSubGizmo;
@@ -42,11 +46,27 @@ pub(crate) trait SubGizmoControl {
     fn is_focused(&self) -> bool;
     /// Returns true if this subgizmo is currently active.
     fn is_active(&self) -> bool;
+    /// Moves the highlight amount towards the current focus state.
+    fn update_highlight(&mut self, delta_time: f32);
+    /// Mode, direction and kind of the handle this subgizmo draws.
+    fn handle(&self) -> (GizmoMode, GizmoDirection, TransformKind);
+    /// Returns true if the direction of this subgizmo is disabled in the configuration.
+    fn is_disabled(&self) -> bool;
+    /// Highlight and opacity of this subgizmo, which affect how it is drawn.
+    fn draw_state(&self) -> (f32, f32);
     /// Pick the subgizmo based on pointer ray. If it is close enough to
     /// the mouse pointer, distance from camera to the subgizmo is returned.
     fn pick(&mut self, ray: Ray) -> Option<f64>;
     /// Update the subgizmo based on pointer ray and interaction.
     fn update(&mut self, ray: Ray) -> Option<GizmoResult>;
+    /// Reset the transformation controlled by this subgizmo, if supported.
+    fn reset(&self) -> Option<GizmoResult>;
+    /// Scale the targets by the given factor without dragging, if supported.
+    fn scroll(&self, factor: f64) -> Option<GizmoResult>;
+    /// Signed number of snapping steps crossed since this was last called.
+    fn take_snap_steps(&mut self) -> i32;
+    /// Copy of the implementation-specific state, for inspection.
+    fn state_snapshot(&self) -> ActiveStateSnapshot;
     /// Draw the subgizmo.
     fn draw(&self) -> GizmoDrawData;
 }
@@ -64,6 +84,30 @@ pub(crate) trait SubGizmoKind: 'static {
     fn draw(subgizmo: &SubGizmoConfig<Self>) -> GizmoDrawData
     where
         Self: Sized;
+    fn handle(subgizmo: &SubGizmoConfig<Self>) -> (GizmoMode, GizmoDirection, TransformKind)
+    where
+        Self: Sized;
+    fn state_snapshot(subgizmo: &SubGizmoConfig<Self>) -> ActiveStateSnapshot
+    where
+        Self: Sized;
+    fn reset(_subgizmo: &SubGizmoConfig<Self>) -> Option<GizmoResult>
+    where
+        Self: Sized,
+    {
+        None
+    }
+    fn scroll(_subgizmo: &SubGizmoConfig<Self>, _factor: f64) -> Option<GizmoResult>
+    where
+        Self: Sized,
+    {
+        None
+    }
+    fn take_snap_steps(_subgizmo: &mut SubGizmoConfig<Self>) -> i32
+    where
+        Self: Sized,
+    {
+        0
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -78,6 +122,8 @@ pub(crate) struct SubGizmoConfig<T: SubGizmoKind> {
     pub(crate) focused: bool,
     /// Whether this subgizmo is active this frame
     pub(crate) active: bool,
+    /// How highlighted this subgizmo is drawn, between 0.0 and 1.0.
+    pub(crate) highlight: f32,
     /// Opacity of the subgizmo for this frame.
     /// A fully invisible subgizmo cannot be interacted with.
     pub(crate) opacity: f32,
@@ -103,15 +149,18 @@ where
         params.hash(&mut hasher);
         let id = hasher.finish();
 
-        Self {
+        let mut subgizmo = Self {
             id,
             params,
             config,
             focused: false,
             active: false,
+            highlight: 0.0,
             opacity: 0.0,
             state: Default::default(),
-        }
+        };
+        subgizmo.update_config(config);
+        subgizmo
     }
 }
 
@@ -124,6 +173,9 @@ where
     }
     fn update_config(&mut self, config: PreparedGizmoConfig) {
         self.config = config;
+        // Each mode is drawn and picked at its own size.
+        let (mode, _, _) = T::handle(self);
+        self.config.gizmo_size *= config.visuals.size_mul(mode);
     }
 
     fn set_focused(&mut self, focused: bool) {
@@ -142,6 +194,31 @@ where
         self.active
     }
 
+    fn update_highlight(&mut self, delta_time: f32) {
+        let target = if self.focused { 1.0 } else { 0.0 };
+        let speed = self.config.visuals.opacity_animation_speed;
+
+        if speed <= 0.0 || delta_time <= 0.0 {
+            self.highlight = target;
+        } else {
+            let t = 1.0 - (-speed * delta_time).exp();
+            self.highlight += (target - self.highlight) * t;
+        }
+    }
+
+    fn handle(&self) -> (GizmoMode, GizmoDirection, TransformKind) {
+        T::handle(self)
+    }
+
+    fn is_disabled(&self) -> bool {
+        let (_, direction, _) = T::handle(self);
+        self.config.disabled_directions.contains(direction)
+    }
+
+    fn draw_state(&self) -> (f32, f32) {
+        (self.highlight, self.opacity)
+    }
+
     fn pick(&mut self, ray: Ray) -> Option<f64> {
         T::pick(self, ray)
     }
@@ -150,7 +227,96 @@ where
         T::update(self, ray)
     }
 
+    fn reset(&self) -> Option<GizmoResult> {
+        T::reset(self)
+    }
+
+    fn scroll(&self, factor: f64) -> Option<GizmoResult> {
+        T::scroll(self, factor)
+    }
+
+    fn take_snap_steps(&mut self) -> i32 {
+        T::take_snap_steps(self)
+    }
+
+    fn state_snapshot(&self) -> ActiveStateSnapshot {
+        T::state_snapshot(self)
+    }
+
     fn draw(&self) -> GizmoDrawData {
         T::draw(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{DVec3, Vec2};
+    use crate::subgizmo::translation::TranslationParams;
+    use crate::test_util::{self, CENTER, X_ARROW};
+    use crate::{GizmoConfig, GizmoVisuals};
+
+    #[test]
+    fn highlight_eases_towards_the_focus_state() {
+        let config = test_util::prepared_config(GizmoConfig {
+            visuals: GizmoVisuals {
+                opacity_animation_speed: 10.0,
+                ..Default::default()
+            },
+            ..test_util::config(GizmoMode::all_translate())
+        });
+        let params = |mode, direction| TranslationParams {
+            mode,
+            direction,
+            transform_kind: TransformKind::Axis,
+            custom_axis: None,
+        };
+        let mut hovered =
+            TranslationSubGizmo::new(config, params(GizmoMode::TranslateX, GizmoDirection::X));
+        let mut other =
+            TranslationSubGizmo::new(config, params(GizmoMode::TranslateY, GizmoDirection::Y));
+        hovered.set_focused(true);
+
+        let mut last = hovered.highlight;
+        for _ in 0..10 {
+            hovered.update_highlight(1.0 / 60.0);
+            other.update_highlight(1.0 / 60.0);
+            assert!(hovered.highlight > last && hovered.highlight < 1.0);
+            last = hovered.highlight;
+        }
+        assert!(other.highlight.abs() < f32::EPSILON);
+
+        for _ in 0..100 {
+            hovered.update_highlight(1.0 / 60.0);
+        }
+        assert!(hovered.highlight > 0.99);
+    }
+
+    #[test]
+    fn subgizmos_pick_and_update_from_plain_rays() {
+        // Only matrices, a viewport and rays, without a Gizmo or any UI
+        let config = test_util::prepared_config(test_util::config(GizmoMode::all_translate()));
+        let mut subgizmo = TranslationSubGizmo::new(
+            config,
+            TranslationParams {
+                mode: GizmoMode::TranslateX,
+                direction: GizmoDirection::X,
+                transform_kind: TransformKind::Axis,
+                custom_axis: None,
+            },
+        );
+
+        assert_eq!(
+            subgizmo.pick(test_util::ray(CENTER + Vec2::new(0.0, 60.0))),
+            None
+        );
+        let t = subgizmo.pick(test_util::ray(X_ARROW)).unwrap();
+        assert!(t.is_finite() && t > 0.0, "{t}");
+
+        subgizmo.set_active(true);
+        let result = subgizmo.update(test_util::ray(X_ARROW + Vec2::new(40.0, 10.0)));
+        let total = test_util::translation_total(&result.unwrap());
+        assert!(total.x > 0.0, "{total}");
+        assert!(total.abs_diff_eq(DVec3::X * total.x, 1e-9), "{total}");
+    }
+}
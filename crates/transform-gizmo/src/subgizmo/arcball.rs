@@ -1,14 +1,21 @@
-use crate::math::{screen_to_world, DQuat, Pos2};
+use crate::math::{world_to_screen, DQuat, DVec3, Pos2, Vec2};
 use crate::subgizmo::common::{draw_circle, pick_circle};
-use crate::subgizmo::{SubGizmoConfig, SubGizmoKind};
-use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDrawData, GizmoResult};
+use crate::subgizmo::{common::TransformKind, SubGizmoConfig, SubGizmoKind};
+use crate::{
+    config::PreparedGizmoConfig,
+    gizmo::{ActiveStateSnapshot, Ray},
+    GizmoDirection, GizmoDrawData, GizmoMode, GizmoPhase, GizmoResult,
+};
 use ecolor::Color32;
 
 pub(crate) type ArcballSubGizmo = SubGizmoConfig<Arcball>;
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct ArcballState {
-    last_pos: Pos2,
+    /// Screen position where the drag started
+    start_pos: Pos2,
+    /// Point on the virtual sphere where the drag started
+    start_point: DVec3,
     total_rotation: DQuat,
 }
 
@@ -27,7 +34,10 @@ impl SubGizmoKind for Arcball {
             true,
         );
 
-        subgizmo.state.last_pos = ray.screen_pos;
+        subgizmo.state.start_pos = ray.screen_pos;
+        subgizmo.state.start_point =
+            sphere_point(&subgizmo.config, ray.screen_pos).unwrap_or(DVec3::ZERO);
+        subgizmo.state.total_rotation = DQuat::IDENTITY;
 
         if !pick_result.picked {
             return None;
@@ -37,35 +47,46 @@ impl SubGizmoKind for Arcball {
     }
 
     fn update(subgizmo: &mut ArcballSubGizmo, ray: Ray) -> Option<GizmoResult> {
-        let dir = ray.screen_pos - subgizmo.state.last_pos;
-
-        let rotation_delta = if dir.length_sq() > f32::EPSILON {
-            let mat = subgizmo.config.view_projection.inverse();
-            let a = screen_to_world(subgizmo.config.viewport, mat, ray.screen_pos, 0.0);
-            let b = screen_to_world(subgizmo.config.viewport, mat, subgizmo.state.last_pos, 0.0);
-
-            let origin = subgizmo.config.view_forward();
-            let a = (a - origin).normalize();
-            let b = (b - origin).normalize();
-
-            DQuat::from_axis_angle(a.cross(b).normalize(), a.dot(b).acos() * 10.0)
-        } else {
+        let total_rotation = if subgizmo.config.orbit_rotation {
+            orbit_rotation(&subgizmo.config, ray.screen_pos - subgizmo.state.start_pos)
+        } else if subgizmo.state.start_point == DVec3::ZERO {
             DQuat::IDENTITY
+        } else {
+            let point = sphere_point(&subgizmo.config, ray.screen_pos)?;
+            DQuat::from_rotation_arc(subgizmo.state.start_point, point)
         };
+        let rotation_delta = total_rotation * subgizmo.state.total_rotation.inverse();
 
-        subgizmo.state.last_pos = ray.screen_pos;
-        subgizmo.state.total_rotation = rotation_delta.mul_quat(subgizmo.state.total_rotation);
+        subgizmo.state.total_rotation = total_rotation;
 
         Some(GizmoResult::Arcball {
             delta: rotation_delta.into(),
-            total: subgizmo.state.total_rotation.into(),
+            total: total_rotation.into(),
+            mode: GizmoMode::Arcball,
+            phase: GizmoPhase::Update,
+            interaction_id: 0,
         })
     }
 
+    fn handle(_subgizmo: &ArcballSubGizmo) -> (GizmoMode, GizmoDirection, TransformKind) {
+        (
+            GizmoMode::Arcball,
+            GizmoDirection::View,
+            TransformKind::Axis,
+        )
+    }
+
+    fn state_snapshot(subgizmo: &ArcballSubGizmo) -> ActiveStateSnapshot {
+        ActiveStateSnapshot::Arcball {
+            start_pos: subgizmo.state.start_pos,
+            total_rotation: subgizmo.state.total_rotation.into(),
+        }
+    }
+
     fn draw(subgizmo: &ArcballSubGizmo) -> GizmoDrawData {
         draw_circle(
             &subgizmo.config,
-            Color32::WHITE.gamma_multiply(if subgizmo.focused { 0.10 } else { 0.0 }),
+            Color32::WHITE.gamma_multiply(0.10 * subgizmo.highlight),
             arcball_radius(&subgizmo.config),
             true,
         )
@@ -74,5 +95,112 @@ impl SubGizmoKind for Arcball {
 
 /// Radius to use for outer circle subgizmos
 pub(crate) fn arcball_radius(config: &PreparedGizmoConfig) -> f64 {
-    (config.scale_factor * (config.visuals.gizmo_size + config.visuals.stroke_width - 5.0)) as f64
+    (config.scale_factor * (config.gizmo_size + config.visuals.stroke_width - 5.0)) as f64
+}
+
+/// Radius of the arcball in viewport units
+fn arcball_screen_radius(config: &PreparedGizmoConfig) -> f64 {
+    (config.gizmo_size + config.visuals.stroke_width - 5.0) as f64
+}
+
+/// Rotates around the camera's up axis based on horizontal pointer movement,
+/// and around the camera's right axis based on vertical pointer movement.
+/// Moving the pointer by the arcball radius rotates by one radian.
+fn orbit_rotation(config: &PreparedGizmoConfig, pointer_delta: Vec2) -> DQuat {
+    let radius = arcball_screen_radius(config);
+
+    let yaw = DQuat::from_axis_angle(config.view_up(), pointer_delta.x as f64 / radius);
+    let pitch = DQuat::from_axis_angle(config.view_right(), pointer_delta.y as f64 / radius);
+
+    yaw * pitch
+}
+
+/// Projects a screen position onto a virtual sphere around the gizmo,
+/// returning the point on the unit sphere in world space.
+/// Positions outside the sphere are mapped to its silhouette.
+fn sphere_point(config: &PreparedGizmoConfig, screen_pos: Pos2) -> Option<DVec3> {
+    let center = world_to_screen(config.viewport, config.mvp, DVec3::ZERO)?;
+    let radius = arcball_screen_radius(config);
+
+    let x = (screen_pos.x - center.x) as f64 / radius;
+    // Screen y axis points down
+    let y = (center.y - screen_pos.y) as f64 / radius;
+
+    let length_squared = x * x + y * y;
+    let (x, y, z) = if length_squared <= 1.0 {
+        (x, y, (1.0 - length_squared).sqrt())
+    } else {
+        let length = length_squared.sqrt();
+        (x / length, y / length, 0.0)
+    };
+
+    let mut towards_camera = config.view_forward();
+    if config.left_handed {
+        towards_camera *= -1.0;
+    }
+
+    Some(config.view_right() * x + config.view_up() * y + towards_camera * z)
+}
+
+#[cfg(test)]
+mod tests {
+    use enumset::EnumSet;
+
+    use super::*;
+    use crate::math::Transform;
+    use crate::test_util::{self, CENTER};
+    use crate::{Gizmo, GizmoConfig};
+
+    #[test]
+    fn arcball_rotates_between_sphere_points() {
+        let config =
+            test_util::prepared_config(test_util::config(EnumSet::only(GizmoMode::Arcball)));
+        let radius = arcball_screen_radius(&config) as f32;
+        let end = CENTER + Vec2::new(0.6 * radius, 0.0);
+
+        // The center of the arcball is the point closest to the camera
+        let start_point = sphere_point(&config, CENTER).unwrap();
+        let end_point = sphere_point(&config, end).unwrap();
+        assert!(start_point.abs_diff_eq(DVec3::Z, 1e-6), "{start_point}");
+        assert!(
+            end_point.abs_diff_eq(DVec3::new(0.6, 0.0, 0.8), 1e-6),
+            "{end_point}"
+        );
+
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::Arcball)));
+        let mut targets = [Transform::default()];
+        let results = test_util::drag(&mut gizmo, &[CENTER, end], &mut targets);
+
+        let Some(&GizmoResult::Arcball { total, .. }) = results.last() else {
+            panic!("expected an arcball result, got {results:?}");
+        };
+
+        // Rotates the start point to the end point, about the view up axis
+        let expected = DQuat::from_axis_angle(DVec3::Y, 0.6f64.atan2(0.8));
+        assert!(DQuat::from(total).abs_diff_eq(expected, 1e-6), "{total:?}");
+        assert!(DQuat::from(targets[0].rotation).abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn horizontal_orbit_rotates_about_view_up() {
+        let config = GizmoConfig {
+            orbit_rotation: true,
+            ..test_util::config(EnumSet::only(GizmoMode::Arcball))
+        };
+        let radius = arcball_screen_radius(&test_util::prepared_config(config));
+
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+        let path = [
+            CENTER,
+            CENTER + Vec2::new(15.0, 0.0),
+            CENTER + Vec2::new(30.0, 0.0),
+        ];
+        test_util::drag(&mut gizmo, &path, &mut targets);
+
+        // The view up axis of the test camera is Y
+        let (axis, angle) = DQuat::from(targets[0].rotation).to_axis_angle();
+        assert!(axis.abs_diff_eq(DVec3::Y, 1e-6), "{axis}");
+        assert!((angle - 30.0 / radius).abs() < 1e-6, "{angle}");
+    }
 }
@@ -1,20 +1,38 @@
 use crate::math::{ray_to_plane_origin, segment_to_segment};
 use crate::GizmoMode;
-use ecolor::Color32;
+use ecolor::{Color32, Rgba};
 use enumset::EnumSet;
 use std::ops::{Add, RangeInclusive};
 
 use crate::shape::ShapeBuidler;
-use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDirection, GizmoDrawData};
+use crate::{
+    config::{PreparedGizmoConfig, StrokeCap},
+    gizmo::Ray,
+    GizmoDirection, GizmoDrawData,
+};
 use glam::{DMat3, DMat4, DQuat, DVec3};
 
 const ARROW_FADE: RangeInclusive<f64> = 0.95..=0.99;
 const PLANE_FADE: RangeInclusive<f64> = 0.70..=0.86;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-pub(crate) enum TransformKind {
-    Axis,
-    Plane,
+pub(crate) use crate::config::TransformKind;
+
+/// Whether the snapping interval can be snapped to.
+/// Zero, negative and non-finite intervals disable snapping, instead of producing NaN values.
+pub(crate) fn valid_snap_interval(interval: f64) -> bool {
+    interval.is_finite() && interval > 0.0
+}
+
+/// Moves the snapping step index to the step of the given snapped value, or clears it
+/// if the value was not snapped. Returns the signed number of steps crossed.
+pub(crate) fn update_snap_index(index: &mut Option<i64>, value: Option<f64>, interval: f64) -> i32 {
+    let new_index = value.map(|value| (value / interval).round() as i64);
+    let steps = match (*index, new_index) {
+        (Some(old), Some(new)) => new - old,
+        _ => 0,
+    };
+    *index = new_index;
+    steps as i32
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -46,7 +64,7 @@ fn arrow_params(config: &PreparedGizmoConfig, direction: DVec3, mode: GizmoMode)
 
     let (start, length) = if mode.is_translate() && arrow_modes_overlapping(mode, config.modes) {
         // Modes contain both translate and scale. Use a bit different translate arrow, so the modes do not overlap.
-        let length = (config.scale_factor * config.visuals.gizmo_size) as f64;
+        let length = (config.scale_factor * config.gizmo_size) as f64;
         let start = direction * (length + (width * 3.0));
 
         let length = length * 0.2 + width;
@@ -54,7 +72,7 @@ fn arrow_params(config: &PreparedGizmoConfig, direction: DVec3, mode: GizmoMode)
         (start, length)
     } else {
         let start = direction * (width * 0.5 + inner_circle_radius(config));
-        let mut length = (config.scale_factor * config.visuals.gizmo_size) as f64 - start.length();
+        let mut length = (config.scale_factor * config.gizmo_size) as f64 - start.length();
 
         if config.modes.len() > 1 {
             length -= width * 2.0;
@@ -76,10 +94,20 @@ pub(crate) fn pick_arrow(
     ray: Ray,
     direction: GizmoDirection,
     mode: GizmoMode,
+) -> PickResult {
+    pick_arrow_along(config, ray, gizmo_local_normal(config, direction), mode)
+}
+
+/// Picks an arrow that points in the given direction, in gizmo local space
+pub(crate) fn pick_arrow_along(
+    config: &PreparedGizmoConfig,
+    ray: Ray,
+    local_direction: DVec3,
+    mode: GizmoMode,
 ) -> PickResult {
     let ray_length = 1e+14;
 
-    let direction = gizmo_normal(config, direction);
+    let direction = local_to_global_direction(config, local_direction);
 
     let mut arrow_params = arrow_params(config, direction, mode);
     arrow_params.start += config.translation;
@@ -171,18 +199,67 @@ pub(crate) fn pick_circle(
     }
 }
 
+/// Picks a square that faces the camera, centered at the gizmo origin
+pub(crate) fn pick_view_square(
+    config: &PreparedGizmoConfig,
+    ray: Ray,
+    half_size: f64,
+) -> PickResult {
+    let origin = config.translation;
+    let normal = -config.view_forward();
+
+    let (t, _) = ray_to_plane_origin(normal, origin, ray.origin, ray.direction);
+
+    let hit_pos = ray.origin + ray.direction * t;
+    let offset = hit_pos - origin;
+
+    let dist = f64::max(
+        offset.dot(config.view_right()).abs(),
+        offset.dot(config.view_up()).abs(),
+    );
+
+    let picked = t.is_finite() && dist <= half_size + config.focus_distance as f64;
+
+    PickResult {
+        subgizmo_point: hit_pos,
+        visibility: 1.0,
+        picked,
+        t,
+    }
+}
+
 pub(crate) fn draw_arrow(
     config: &PreparedGizmoConfig,
     opacity: f32,
-    focused: bool,
+    highlight: f32,
+    direction: GizmoDirection,
+    mode: GizmoMode,
+) -> GizmoDrawData {
+    draw_arrow_along(
+        config,
+        opacity,
+        highlight,
+        direction,
+        gizmo_local_normal(config, direction),
+        mode,
+    )
+}
+
+/// Draws an arrow that points in the given direction, in gizmo local space.
+/// The arrow is colored according to `direction`.
+pub(crate) fn draw_arrow_along(
+    config: &PreparedGizmoConfig,
+    opacity: f32,
+    highlight: f32,
     direction: GizmoDirection,
+    local_direction: DVec3,
     mode: GizmoMode,
 ) -> GizmoDrawData {
     if opacity <= 1e-4 {
         return GizmoDrawData::default();
     }
 
-    let color = gizmo_color(config, focused, direction).gamma_multiply(opacity);
+    let color = gizmo_color(config, highlight, direction).gamma_multiply(opacity);
 
     let transform = if config.local_space() {
         DMat4::from_rotation_translation(config.rotation, config.translation)
@@ -193,41 +270,41 @@ pub(crate) fn draw_arrow(
     let shape_builder = ShapeBuidler::new(
         config.view_projection * transform,
         config.viewport,
-        config.pixels_per_point,
+        config.pixels_per_point(),
     );
 
-    let direction = gizmo_local_normal(config, direction);
-
-    let arrow_params = arrow_params(config, direction, mode);
+    let arrow_params = arrow_params(config, local_direction, mode);
 
-    let tip_stroke_width = 2.4 * config.visuals.stroke_width;
-    let tip_length = (tip_stroke_width * config.scale_factor) as f64;
+    let tip_stroke_width = config.visuals.arrowhead_width * config.gizmo_size;
+    let tip_length =
+        (config.visuals.arrowhead_length * config.gizmo_size * config.scale_factor) as f64;
 
     let tip_start = arrow_params.end - arrow_params.direction * tip_length;
 
     let mut draw_data = GizmoDrawData::default();
-    draw_data = draw_data.add(
-        shape_builder
-            .line_segment(
-                arrow_params.start,
-                tip_start,
-                (config.visuals.stroke_width, color),
-            )
-            .into(),
-    );
+    draw_data = draw_data.add(shape_builder.line_segment(
+        arrow_params.start,
+        tip_start,
+        (config.visuals.stroke_width, color),
+    ));
+
+    if config.visuals.stroke_cap == StrokeCap::Round {
+        draw_data = draw_data
+            .add(shape_builder.round_cap(arrow_params.start, (config.visuals.stroke_width, color)));
+    }
 
     if mode.is_scale() {
-        draw_data = draw_data.add(
-            shape_builder
-                .line_segment(tip_start, arrow_params.end, (tip_stroke_width, color))
-                .into(),
-        );
+        draw_data = draw_data.add(shape_builder.line_segment(
+            tip_start,
+            arrow_params.end,
+            (tip_stroke_width, color),
+        ));
     } else if mode.is_translate() {
-        draw_data = draw_data.add(
-            shape_builder
-                .arrow(tip_start, arrow_params.end, (tip_stroke_width, color))
-                .into(),
-        );
+        draw_data = draw_data.add(shape_builder.arrow(
+            tip_start,
+            arrow_params.end,
+            (tip_stroke_width, color),
+        ));
     }
 
     draw_data
@@ -236,14 +313,15 @@ pub(crate) fn draw_arrow(
 pub(crate) fn draw_plane(
     config: &PreparedGizmoConfig,
     opacity: f32,
-    focused: bool,
+    highlight: f32,
     direction: GizmoDirection,
 ) -> GizmoDrawData {
     if opacity <= 1e-4 {
         return GizmoDrawData::default();
     }
 
-    let color = gizmo_color(config, focused, direction).gamma_multiply(opacity);
+    let color = gizmo_color(config, highlight, direction)
+        .gamma_multiply(opacity * config.visuals.plane_alpha);
 
     let transform = if config.local_space() {
         DMat4::from_rotation_translation(config.rotation, config.translation)
@@ -254,29 +332,26 @@ pub(crate) fn draw_plane(
     let shape_builder = ShapeBuidler::new(
         config.view_projection * transform,
         config.viewport,
-        config.pixels_per_point,
+        config.pixels_per_point(),
     );
 
     let scale = plane_size(config) * 0.5;
-    let a = plane_bitangent(direction) * scale;
-    let b = plane_tangent(direction) * scale;
+    let (a, b) = plane_axes(config, direction);
+    let a = a * scale;
+    let b = b * scale;
     let origin = plane_local_origin(config, direction);
 
     let mut draw_data = GizmoDrawData::default();
-    draw_data = draw_data.add(
-        shape_builder
-            .polygon(
-                &[
-                    origin - b - a,
-                    origin + b - a,
-                    origin + b + a,
-                    origin - b + a,
-                ],
-                color,
-                (0.0, Color32::TRANSPARENT),
-            )
-            .into(),
-    );
+    draw_data = draw_data.add(shape_builder.polygon(
+        &[
+            origin - b - a,
+            origin + b - a,
+            origin + b + a,
+            origin - b + a,
+        ],
+        color,
+        (0.0, Color32::TRANSPARENT),
+    ));
     draw_data
 }
 
@@ -290,67 +365,220 @@ pub(crate) fn draw_circle(
         return GizmoDrawData::default();
     }
 
-    let rotation = {
-        let forward = config.view_forward();
-        let right = config.view_right();
-        let up = config.view_up();
-
-        DQuat::from_mat3(&DMat3::from_cols(up, -forward, -right))
-    };
-
-    let transform = DMat4::from_rotation_translation(rotation, config.translation);
+    let transform = DMat4::from_rotation_translation(view_rotation(config), config.translation);
 
     let shape_builder = ShapeBuidler::new(
         config.view_projection * transform,
         config.viewport,
-        config.pixels_per_point,
-    );
+        config.pixels_per_point(),
+    )
+    .with_circle_segments(config.visuals.circle_segments, config.scale_factor);
 
     let mut draw_data = GizmoDrawData::default();
     if filled {
-        draw_data = draw_data.add(
-            shape_builder
-                .filled_circle(radius, color, (0.0, Color32::TRANSPARENT))
-                .into(),
-        );
+        draw_data =
+            draw_data.add(shape_builder.filled_circle(radius, color, (0.0, Color32::TRANSPARENT)));
     } else {
-        draw_data = draw_data.add(
-            shape_builder
-                .circle(radius, (config.visuals.stroke_width, color))
-                .into(),
-        );
+        draw_data =
+            draw_data.add(shape_builder.circle(radius, (config.visuals.stroke_width, color)));
     }
     draw_data
 }
 
+/// Draws a world space line segment, such as the translation rail.
+pub(crate) fn draw_line_segment(
+    config: &PreparedGizmoConfig,
+    color: Color32,
+    from: DVec3,
+    to: DVec3,
+) -> GizmoDrawData {
+    if color.a() == 0 {
+        return GizmoDrawData::default();
+    }
+
+    let shape_builder = ShapeBuidler::new(
+        config.view_projection,
+        config.viewport,
+        config.pixels_per_point(),
+    );
+
+    shape_builder.line_segment(from, to, (config.visuals.stroke_width, color))
+}
+
+pub(crate) fn draw_view_square(
+    config: &PreparedGizmoConfig,
+    color: Color32,
+    half_size: f64,
+) -> GizmoDrawData {
+    if color.a() == 0 {
+        return GizmoDrawData::default();
+    }
+
+    let transform = DMat4::from_rotation_translation(view_rotation(config), config.translation);
+
+    let shape_builder = ShapeBuidler::new(
+        config.view_projection * transform,
+        config.viewport,
+        config.pixels_per_point(),
+    );
+
+    shape_builder.polygon(
+        &[
+            DVec3::new(-half_size, 0.0, -half_size),
+            DVec3::new(half_size, 0.0, -half_size),
+            DVec3::new(half_size, 0.0, half_size),
+            DVec3::new(-half_size, 0.0, half_size),
+        ],
+        Color32::TRANSPARENT,
+        (config.visuals.stroke_width, color),
+    )
+}
+
+/// Rotation that aligns the local XZ plane with the view plane
+fn view_rotation(config: &PreparedGizmoConfig) -> DQuat {
+    let forward = config.view_forward();
+    let right = config.view_right();
+    let up = config.view_up();
+
+    DQuat::from_mat3(&DMat3::from_cols(up, -forward, -right))
+}
+
+/// First in-plane axis of the plane with the given normal direction.
+///
+/// Together with [`plane_tangent`] and the normal, this forms an orthonormal basis
+/// for each of the X, Y and Z planes. The axes are picked from the fixed local axes
+/// instead of derived from the normal, so they cannot degenerate.
 pub(crate) const fn plane_bitangent(direction: GizmoDirection) -> DVec3 {
     match direction {
         GizmoDirection::X => DVec3::Y,
         GizmoDirection::Y => DVec3::Z,
         GizmoDirection::Z => DVec3::X,
-        GizmoDirection::View => DVec3::ZERO, // Unused
+        GizmoDirection::View | GizmoDirection::Custom => DVec3::ZERO, // Unused
     }
 }
 
+/// Second in-plane axis of the plane with the given normal direction.
+/// See [`plane_bitangent`].
 pub(crate) const fn plane_tangent(direction: GizmoDirection) -> DVec3 {
     match direction {
         GizmoDirection::X => DVec3::Z,
         GizmoDirection::Y => DVec3::X,
         GizmoDirection::Z => DVec3::Y,
-        GizmoDirection::View => DVec3::ZERO, // Unused
+        GizmoDirection::View | GizmoDirection::Custom => DVec3::ZERO, // Unused
+    }
+}
+
+/// Direction in a Y-up world corresponding to the given direction in the world of
+/// [`crate::GizmoConfig::world_up`], and the sign of its axis.
+pub(crate) fn y_up_direction(
+    config: &PreparedGizmoConfig,
+    direction: GizmoDirection,
+) -> (GizmoDirection, f64) {
+    let axis = match direction {
+        GizmoDirection::X => DVec3::X,
+        GizmoDirection::Y => DVec3::Y,
+        GizmoDirection::Z => DVec3::Z,
+        GizmoDirection::View | GizmoDirection::Custom => return (direction, 1.0),
+    };
+
+    let axis = (config.world_up_rotation().inverse() * axis).round();
+    if axis.x != 0.0 {
+        (GizmoDirection::X, axis.x)
+    } else if axis.y != 0.0 {
+        (GizmoDirection::Y, axis.y)
+    } else {
+        (GizmoDirection::Z, axis.z)
+    }
+}
+
+/// [`plane_bitangent`] and [`plane_tangent`] of the plane with the given normal direction,
+/// rotated from a Y-up world to the world of [`crate::GizmoConfig::world_up`].
+pub(crate) fn world_plane_axes(
+    config: &PreparedGizmoConfig,
+    direction: GizmoDirection,
+) -> (DVec3, DVec3) {
+    let rotation = config.world_up_rotation();
+    if rotation == DQuat::IDENTITY {
+        return (plane_bitangent(direction), plane_tangent(direction));
+    }
+
+    let (y_up_direction, sign) = y_up_direction(config, direction);
+    let bitangent = (rotation * plane_bitangent(y_up_direction)).round();
+    let tangent = (rotation * plane_tangent(y_up_direction)).round();
+
+    // Keep the normal of the basis along the positive axis
+    if sign < 0.0 {
+        (tangent, bitangent)
+    } else {
+        (bitangent, tangent)
+    }
+}
+
+/// In-plane axes of the plane handle with the given normal direction, in gizmo local space.
+///
+/// These are [`world_plane_axes`], unless a reference frame is set in the config,
+/// in which case the axes of the frame are projected onto the plane.
+///
+/// The cross product of the axes is along the normal in the handedness of the config.
+/// In a left-handed coordinate system, the axes are swapped, so the right-handed cross
+/// product points against the normal. The plane handle itself does not change.
+pub(crate) fn plane_axes(
+    config: &PreparedGizmoConfig,
+    direction: GizmoDirection,
+) -> (DVec3, DVec3) {
+    let (bitangent, tangent) = right_handed_plane_axes(config, direction);
+    if config.left_handed {
+        (tangent, bitangent)
+    } else {
+        (bitangent, tangent)
+    }
+}
+
+/// [`plane_axes`] of a right-handed coordinate system
+fn right_handed_plane_axes(
+    config: &PreparedGizmoConfig,
+    direction: GizmoDirection,
+) -> (DVec3, DVec3) {
+    let (bitangent, tangent) = world_plane_axes(config, direction);
+
+    let Some(frame) = config.reference_frame else {
+        if config.local_space() && direction != GizmoDirection::View {
+            // Keep the plane handles in the visible quadrant of mirrored targets
+            let signs = config.axis_signs();
+            return (bitangent * signs, tangent * signs);
+        }
+        return (bitangent, tangent);
+    };
+
+    if direction == GizmoDirection::View {
+        return (bitangent, tangent);
+    }
+
+    let frame = DMat3::from(frame);
+    let normal = bitangent.cross(tangent);
+    let project = |axis: DVec3| (axis - normal * axis.dot(normal)).normalize_or_zero();
+
+    // If the frame axis is perpendicular to the plane, derive it from the other axis.
+    let mut frame_bitangent = project(frame * bitangent);
+    if frame_bitangent == DVec3::ZERO {
+        frame_bitangent = project(frame * tangent).cross(normal);
+    }
+
+    if frame_bitangent == DVec3::ZERO {
+        return (bitangent, tangent);
     }
+
+    (frame_bitangent, normal.cross(frame_bitangent))
 }
 
 pub(crate) fn plane_size(config: &PreparedGizmoConfig) -> f64 {
-    (config.scale_factor * (config.visuals.gizmo_size * 0.1 + config.visuals.stroke_width * 2.0))
-        as f64
+    (config.scale_factor * (config.gizmo_size * 0.1 + config.visuals.stroke_width * 2.0)) as f64
 }
 
 pub(crate) fn plane_local_origin(config: &PreparedGizmoConfig, direction: GizmoDirection) -> DVec3 {
-    let offset = config.scale_factor * config.visuals.gizmo_size * 0.5;
+    let offset = config.scale_factor * config.gizmo_size * 0.5;
 
-    let a = plane_bitangent(direction);
-    let b = plane_tangent(direction);
+    let (a, b) = plane_axes(config, direction);
     (a + b) * offset as f64
 }
 
@@ -367,20 +595,42 @@ pub(crate) fn plane_global_origin(
 
 /// Radius to use for inner circle subgizmos
 pub(crate) fn inner_circle_radius(config: &PreparedGizmoConfig) -> f64 {
-    (config.scale_factor * config.visuals.gizmo_size) as f64 * 0.2
+    (config.scale_factor * config.gizmo_size) as f64 * 0.2
+}
+
+/// Half of the side length of the view plane translation square
+pub(crate) fn view_square_half_size(config: &PreparedGizmoConfig) -> f64 {
+    inner_circle_radius(config) * std::f64::consts::FRAC_1_SQRT_2
 }
 
 /// Radius to use for outer circle subgizmos
 pub(crate) fn outer_circle_radius(config: &PreparedGizmoConfig) -> f64 {
-    (config.scale_factor * (config.visuals.gizmo_size + config.visuals.stroke_width + 5.0)) as f64
+    (config.scale_factor * (config.gizmo_size + config.visuals.stroke_width + 5.0)) as f64
 }
 
 pub(crate) fn gizmo_local_normal(config: &PreparedGizmoConfig, direction: GizmoDirection) -> DVec3 {
-    match direction {
+    let normal = match direction {
         GizmoDirection::X => DVec3::X,
         GizmoDirection::Y => DVec3::Y,
         GizmoDirection::Z => DVec3::Z,
-        GizmoDirection::View => -config.view_forward(),
+        GizmoDirection::View => return -config.view_forward(),
+        // Custom axes are given by the subgizmo itself
+        GizmoDirection::Custom => return DVec3::ZERO,
+    };
+
+    if config.local_space() {
+        normal * config.axis_signs()
+    } else {
+        normal
+    }
+}
+
+/// Transforms a direction from gizmo local space to world space
+pub(crate) fn local_to_global_direction(config: &PreparedGizmoConfig, direction: DVec3) -> DVec3 {
+    if config.local_space() {
+        config.rotation * direction
+    } else {
+        direction
     }
 }
 
@@ -394,29 +644,313 @@ pub(crate) fn gizmo_normal(config: &PreparedGizmoConfig, direction: GizmoDirecti
     normal
 }
 
+/// Color of the subgizmo in given direction.
+/// Highlight is between 0.0 (inactive) and 1.0 (focused or active).
 pub(crate) fn gizmo_color(
     config: &PreparedGizmoConfig,
-    focused: bool,
+    highlight: f32,
     direction: GizmoDirection,
 ) -> Color32 {
     let color = match direction {
         GizmoDirection::X => config.visuals.x_color,
         GizmoDirection::Y => config.visuals.y_color,
         GizmoDirection::Z => config.visuals.z_color,
-        GizmoDirection::View => config.visuals.s_color,
+        GizmoDirection::View | GizmoDirection::Custom => config.visuals.s_color,
     };
 
-    let color = if focused {
-        config.visuals.highlight_color.unwrap_or(color)
-    } else {
-        color
+    let color = match config.visuals.highlight_color {
+        Some(highlight_color) if highlight >= 1.0 => highlight_color,
+        Some(highlight_color) if highlight > 0.0 => Color32::from(
+            Rgba::from(color) * (1.0 - highlight) + Rgba::from(highlight_color) * highlight,
+        ),
+        _ => color,
     };
 
-    let alpha = if focused {
-        config.visuals.highlight_alpha
-    } else {
-        config.visuals.inactive_alpha
-    };
+    let alpha = config.visuals.inactive_alpha
+        + (config.visuals.highlight_alpha - config.visuals.inactive_alpha) * highlight;
 
     color.linear_multiply(alpha)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Pos2;
+    use crate::test_util;
+    use crate::{Gizmo, GizmoConfig, GizmoVisuals};
+
+    fn draw(
+        modes: EnumSet<GizmoMode>,
+        visuals: GizmoVisuals,
+    ) -> std::sync::Arc<crate::GizmoDrawData> {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            visuals,
+            ..test_util::config(modes)
+        });
+        test_util::update(
+            &mut gizmo,
+            test_util::hover(Pos2::ZERO),
+            &mut [crate::math::Transform::default()],
+        );
+        gizmo.draw()
+    }
+
+    #[test]
+    fn shapes_use_the_visuals_colors() {
+        let draw_data = draw(
+            EnumSet::only(GizmoMode::TranslateX),
+            GizmoVisuals {
+                x_color: Color32::from_rgb(0, 255, 0),
+                ..Default::default()
+            },
+        );
+
+        let visible: Vec<_> = draw_data
+            .colors
+            .iter()
+            .filter(|color| color[3] > 0.0)
+            .collect();
+        assert!(!visible.is_empty());
+        assert!(visible
+            .iter()
+            .all(|color| color[0] == 0.0 && color[2] == 0.0 && color[1] > 0.0));
+
+        let max_alpha = |plane_alpha| {
+            let visuals = GizmoVisuals {
+                plane_alpha,
+                ..Default::default()
+            };
+            draw(EnumSet::only(GizmoMode::TranslateXY), visuals)
+                .colors
+                .iter()
+                .map(|color| color[3])
+                .fold(0.0, f32::max)
+        };
+        let ratio = max_alpha(0.5) / max_alpha(1.0);
+        assert!((ratio - 0.5).abs() < 0.02, "{ratio}");
+    }
+
+    #[test]
+    fn plane_axes_flip_with_handedness() {
+        let config = |handedness| {
+            test_util::prepared_config(GizmoConfig {
+                handedness: Some(handedness),
+                ..test_util::config(GizmoMode::all_translate())
+            })
+        };
+        let right_handed = config(crate::Handedness::RightHanded);
+        let left_handed = config(crate::Handedness::LeftHanded);
+
+        for direction in [GizmoDirection::X, GizmoDirection::Y, GizmoDirection::Z] {
+            let normal = gizmo_local_normal(&right_handed, direction);
+            let (bitangent, tangent) = plane_axes(&right_handed, direction);
+            assert_eq!(bitangent.cross(tangent), normal);
+
+            // The cross product follows the handedness, so the sign flips
+            let (left_bitangent, left_tangent) = plane_axes(&left_handed, direction);
+            assert_eq!(left_bitangent.cross(left_tangent), -normal);
+            assert_eq!((left_bitangent, left_tangent), (tangent, bitangent));
+
+            // The handle stays in the same place
+            assert_eq!(
+                plane_local_origin(&left_handed, direction),
+                plane_local_origin(&right_handed, direction)
+            );
+        }
+    }
+
+    #[test]
+    fn arrowheads_scale_with_the_visuals() {
+        // Screen extent of the arrowhead, which is wider than the shaft
+        let arrowhead = |visuals: GizmoVisuals| {
+            let draw_data = draw(EnumSet::only(GizmoMode::TranslateX), visuals);
+            let half_width = visuals.stroke_width / 2.0 + 1.0;
+            draw_data
+                .vertices
+                .iter()
+                .filter(|[_, y]| (y - test_util::CENTER.y).abs() > half_width)
+                .fold(
+                    (f32::MAX, f32::MIN, 0.0f32),
+                    |(min_x, max_x, max_y), [x, y]| {
+                        (
+                            min_x.min(*x),
+                            max_x.max(*x),
+                            max_y.max((y - test_util::CENTER.y).abs()),
+                        )
+                    },
+                )
+        };
+
+        let short = arrowhead(GizmoVisuals::default());
+        let long = arrowhead(GizmoVisuals {
+            arrowhead_length: 0.4,
+            ..Default::default()
+        });
+        let gizmo_size = GizmoVisuals::default().gizmo_size;
+        assert!((short.1 - long.1).abs() < 1.0, "{short:?} {long:?}");
+        let growth = short.0 - long.0;
+        assert!(
+            (growth - 0.27 * gizmo_size).abs() < 2.0,
+            "{short:?} {long:?}"
+        );
+
+        let wide = arrowhead(GizmoVisuals {
+            arrowhead_width: 0.4,
+            ..Default::default()
+        });
+        assert!(wide.2 > short.2 * 2.0, "{short:?} {wide:?}");
+
+        let vertices = |stroke_cap| {
+            draw(
+                EnumSet::only(GizmoMode::TranslateX),
+                GizmoVisuals {
+                    stroke_cap,
+                    ..Default::default()
+                },
+            )
+            .vertices
+            .len()
+        };
+        assert!(vertices(StrokeCap::Round) > vertices(StrokeCap::Butt));
+    }
+
+    #[test]
+    fn plane_axes_are_orthonormal() {
+        let normals = [
+            (GizmoDirection::X, DVec3::X),
+            (GizmoDirection::Y, DVec3::Y),
+            (GizmoDirection::Z, DVec3::Z),
+        ];
+        for (direction, normal) in normals {
+            let (bitangent, tangent) = (plane_bitangent(direction), plane_tangent(direction));
+            assert_eq!(bitangent.length(), 1.0);
+            assert_eq!(tangent.length(), 1.0);
+            assert_eq!(bitangent.dot(tangent), 0.0);
+            assert_eq!(bitangent.cross(tangent), normal);
+        }
+
+        // Reference frames with axes perpendicular to some of the planes
+        let frames = [
+            DMat3::IDENTITY,
+            DMat3::from_rotation_x(std::f64::consts::FRAC_PI_2),
+            DMat3::from_rotation_z(std::f64::consts::FRAC_PI_2),
+            DMat3::from_quat(DQuat::from_rotation_arc(
+                DVec3::X,
+                DVec3::new(1.0, 1.0, 1.0).normalize(),
+            )),
+        ];
+        for frame in frames {
+            let config = test_util::prepared_config(GizmoConfig {
+                reference_frame: Some(frame.into()),
+                ..test_util::config(GizmoMode::all_translate())
+            });
+            for (direction, normal) in normals {
+                let (bitangent, tangent) = plane_axes(&config, direction);
+                assert!(
+                    (bitangent.length() - 1.0).abs() < 1e-9,
+                    "{frame} {direction:?}"
+                );
+                assert!(
+                    (tangent.length() - 1.0).abs() < 1e-9,
+                    "{frame} {direction:?}"
+                );
+                assert!(bitangent.dot(tangent).abs() < 1e-9, "{frame} {direction:?}");
+                assert!(
+                    bitangent.cross(tangent).abs_diff_eq(normal, 1e-9),
+                    "{frame} {direction:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn plane_handles_follow_the_reference_frame() {
+        let rotation = DQuat::from_rotation_z(30f64.to_radians());
+        let config = test_util::prepared_config(GizmoConfig {
+            reference_frame: Some(DMat3::from_quat(rotation).into()),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+        let unaligned = test_util::prepared_config(test_util::config(GizmoMode::all_translate()));
+
+        // The Z plane is rotated within the plane by the frame
+        let (bitangent, tangent) = plane_axes(&config, GizmoDirection::Z);
+        assert!(
+            bitangent.abs_diff_eq(rotation * DVec3::X, 1e-9),
+            "{bitangent}"
+        );
+        assert!(tangent.abs_diff_eq(rotation * DVec3::Y, 1e-9), "{tangent}");
+        let origin = plane_local_origin(&config, GizmoDirection::Z);
+        let unaligned_origin = plane_local_origin(&unaligned, GizmoDirection::Z);
+        assert!(
+            origin.abs_diff_eq(rotation * unaligned_origin, 1e-9),
+            "{origin}"
+        );
+
+        // The frame Y axis projected onto the X plane is still Y
+        let (bitangent, tangent) = plane_axes(&config, GizmoDirection::X);
+        assert!(bitangent.abs_diff_eq(DVec3::Y, 1e-9), "{bitangent}");
+        assert!(tangent.abs_diff_eq(DVec3::Z, 1e-9), "{tangent}");
+    }
+
+    #[test]
+    fn plane_handles_follow_the_world_up_axis() {
+        let z_up = test_util::prepared_config(GizmoConfig {
+            world_up: DVec3::Z.into(),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+
+        // A Y-up world rotated so that Y points along Z, and Z along -Y
+        assert_eq!(plane_axes(&z_up, GizmoDirection::X), (DVec3::Z, -DVec3::Y));
+        assert_eq!(plane_axes(&z_up, GizmoDirection::Y), (DVec3::Z, DVec3::X));
+        assert_eq!(plane_axes(&z_up, GizmoDirection::Z), (-DVec3::Y, DVec3::X));
+
+        // The ground plane handle faces a front camera looking along +Y
+        let origin = plane_local_origin(&z_up, GizmoDirection::Z);
+        assert!(
+            origin.x > 0.0 && origin.y < 0.0 && origin.z == 0.0,
+            "{origin}"
+        );
+
+        // Snapped to the nearest axis, and the default stays Y-up
+        let tilted = test_util::prepared_config(GizmoConfig {
+            world_up: DVec3::new(0.1, 0.2, 0.9).into(),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+        let y_up = test_util::prepared_config(test_util::config(GizmoMode::all_translate()));
+        for direction in [GizmoDirection::X, GizmoDirection::Y, GizmoDirection::Z] {
+            assert_eq!(plane_axes(&tilted, direction), plane_axes(&z_up, direction));
+            assert_eq!(
+                plane_axes(&y_up, direction),
+                (plane_bitangent(direction), plane_tangent(direction))
+            );
+        }
+    }
+
+    #[test]
+    fn z_up_front_view_matches_y_up_front_view() {
+        let modes = GizmoMode::TranslateXY | GizmoMode::TranslateXZ | GizmoMode::TranslateYZ;
+        let draw = |world_up: DVec3, eye: DVec3| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                view_matrix: DMat4::look_at_rh(eye, DVec3::ZERO, world_up).into(),
+                world_up: world_up.into(),
+                ..test_util::config(modes)
+            });
+            gizmo.update(
+                crate::GizmoInteraction::default(),
+                &[crate::math::Transform::default()],
+            );
+            gizmo.draw().vertices.clone()
+        };
+
+        let y_up = draw(DVec3::Y, DVec3::Z * 10.0);
+        let z_up = draw(DVec3::Z, -DVec3::Y * 10.0);
+        assert_eq!(y_up.len(), z_up.len());
+        for vertex in &y_up {
+            assert!(
+                z_up.iter()
+                    .any(|other| (Pos2::from(*other) - Pos2::from(*vertex)).length() < 1e-3),
+                "{vertex:?}"
+            );
+        }
+    }
+}
@@ -7,9 +7,15 @@ use crate::math::{
     DVec2, DVec3, Pos2,
 };
 use crate::shape::ShapeBuidler;
-use crate::subgizmo::common::{gizmo_color, gizmo_local_normal, gizmo_normal, outer_circle_radius};
-use crate::subgizmo::{SubGizmoConfig, SubGizmoKind};
-use crate::{gizmo::Ray, GizmoDirection, GizmoDrawData, GizmoResult};
+use crate::subgizmo::common::{
+    gizmo_color, gizmo_local_normal, gizmo_normal, outer_circle_radius, update_snap_index,
+    valid_snap_interval, y_up_direction,
+};
+use crate::subgizmo::{common::TransformKind, SubGizmoConfig, SubGizmoKind};
+use crate::{
+    gizmo::{ActiveStateSnapshot, Ray},
+    GizmoDirection, GizmoDrawData, GizmoMode, GizmoPhase, GizmoResult,
+};
 
 pub(crate) type RotationSubGizmo = SubGizmoConfig<Rotation>;
 
@@ -21,9 +27,22 @@ pub(crate) struct RotationParams {
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct RotationState {
     start_axis_angle: f64,
-    start_rotation_angle: f64,
+    /// Sign of the screen space rotation angle, captured when the drag started.
+    /// This keeps the rotation direction consistent if the camera moves
+    /// to the other side of the ring plane during the drag.
+    angle_sign: f64,
     last_rotation_angle: f64,
+    /// Total angle the pointer has rotated around the gizmo during the drag,
+    /// multiplied by the rotation and precision sensitivities. This is not snapped.
+    drag_angle: f64,
+    /// Rotation of the target around the rotation axis when the drag started
+    start_twist_angle: f64,
+    /// Total rotation angle of the drag, not wrapped to a single turn
     current_delta: f64,
+    /// Index of the current snapping step, if snapping
+    snap_index: Option<i64>,
+    /// Snapping steps crossed since they were last taken
+    snap_steps: i32,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
@@ -60,11 +79,14 @@ impl SubGizmoKind for Rotation {
             f64::atan2(offset.cross(forward).dot(normal), offset.dot(forward))
         };
 
+        subgizmo.state.angle_sign = view_angle_sign(subgizmo);
         let rotation_angle = rotation_angle(subgizmo, ray.screen_pos).unwrap_or(0.0);
         subgizmo.state.start_axis_angle = angle;
-        subgizmo.state.start_rotation_angle = rotation_angle;
         subgizmo.state.last_rotation_angle = rotation_angle;
+        subgizmo.state.drag_angle = 0.0;
+        subgizmo.state.start_twist_angle = twist_angle(config.rotation, normal);
         subgizmo.state.current_delta = 0.0;
+        subgizmo.state.snap_index = Some(0);
 
         if dist_from_gizmo_edge <= config.focus_distance as f64 && angle.abs() < arc_angle(subgizmo)
         {
@@ -77,36 +99,78 @@ impl SubGizmoKind for Rotation {
     fn update(subgizmo: &mut RotationSubGizmo, ray: Ray) -> Option<GizmoResult> {
         let config = subgizmo.config;
 
-        let mut rotation_angle = rotation_angle(subgizmo, ray.screen_pos)?;
-        if config.snapping {
-            rotation_angle = round_to_interval(
-                rotation_angle - subgizmo.state.start_rotation_angle,
-                config.snap_angle as f64,
-            ) + subgizmo.state.start_rotation_angle;
-        }
+        let rotation_angle = rotation_angle(subgizmo, ray.screen_pos)?;
 
-        let mut angle_delta = rotation_angle - subgizmo.state.last_rotation_angle;
+        // Always take the smallest angle, e.g. -10° instead of 350°.
+        // The total angle is accumulated from these, so it keeps growing
+        // past full turns instead of wrapping around at ±180°.
+        let pointer_delta =
+            (rotation_angle - subgizmo.state.last_rotation_angle + PI).rem_euclid(TAU) - PI;
 
-        // Always take the smallest angle, e.g. -10° instead of 350°
-        if angle_delta > PI {
-            angle_delta -= TAU;
-        } else if angle_delta < -PI {
-            angle_delta += TAU;
+        subgizmo.state.last_rotation_angle = rotation_angle;
+        subgizmo.state.drag_angle +=
+            pointer_delta * config.rotation_sensitivity * (1.0 - config.precision_skip());
+
+        let snap_angle = snap_angle(subgizmo);
+        let snapping = config.snapping() && valid_snap_interval(snap_angle);
+
+        let mut total_angle = subgizmo.state.drag_angle;
+        if snapping && config.snap_absolute && subgizmo.direction != GizmoDirection::View {
+            // The applied rotation is the negated drag angle. Snap the
+            // resulting orientation around the axis instead of the drag amount.
+            let twist = subgizmo.state.start_twist_angle;
+            total_angle = twist - round_to_interval(twist - total_angle, snap_angle);
+        } else if snapping {
+            total_angle = round_to_interval(total_angle, snap_angle);
         }
 
-        subgizmo.state.last_rotation_angle = rotation_angle;
-        subgizmo.state.current_delta += angle_delta;
+        // The applied rotation is the negated angle.
+        subgizmo.state.snap_steps -= update_snap_index(
+            &mut subgizmo.state.snap_index,
+            snapping.then_some(total_angle),
+            snap_angle,
+        );
+
+        let angle_delta = total_angle - subgizmo.state.current_delta;
+        subgizmo.state.current_delta = total_angle;
 
         let normal = gizmo_local_normal(&subgizmo.config, subgizmo.direction);
 
         Some(GizmoResult::Rotation {
             axis: normal.into(),
+            world_axis: gizmo_normal(&subgizmo.config, subgizmo.direction).into(),
             delta: -angle_delta,
-            total: subgizmo.state.current_delta,
+            total: -subgizmo.state.current_delta,
+            revolutions: (-subgizmo.state.current_delta / TAU).trunc() as i32,
             is_view_axis: subgizmo.direction == GizmoDirection::View,
+            mode: rotation_mode(subgizmo.direction),
+            direction: subgizmo.direction,
+            kind: TransformKind::Axis,
+            phase: GizmoPhase::Update,
+            interaction_id: 0,
         })
     }
 
+    fn state_snapshot(subgizmo: &RotationSubGizmo) -> ActiveStateSnapshot {
+        ActiveStateSnapshot::Rotation {
+            // The applied rotation is the negated angle.
+            drag_angle: -subgizmo.state.drag_angle,
+            current_delta: -subgizmo.state.current_delta,
+        }
+    }
+
+    fn take_snap_steps(subgizmo: &mut RotationSubGizmo) -> i32 {
+        std::mem::take(&mut subgizmo.state.snap_steps)
+    }
+
+    fn handle(subgizmo: &RotationSubGizmo) -> (GizmoMode, GizmoDirection, TransformKind) {
+        (
+            rotation_mode(subgizmo.direction),
+            subgizmo.direction,
+            TransformKind::Axis,
+        )
+    }
+
     fn draw(subgizmo: &RotationSubGizmo) -> GizmoDrawData {
         let config = subgizmo.config;
 
@@ -114,10 +178,11 @@ impl SubGizmoKind for Rotation {
         let shape_builder = ShapeBuidler::new(
             config.view_projection * transform,
             config.viewport,
-            config.pixels_per_point,
-        );
+            config.pixels_per_point(),
+        )
+        .with_circle_segments(config.visuals.circle_segments, config.scale_factor);
 
-        let color = gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction);
+        let color = gizmo_color(&subgizmo.config, subgizmo.highlight, subgizmo.direction);
         let stroke = (config.visuals.stroke_width, color);
 
         let radius = arc_radius(subgizmo);
@@ -126,9 +191,7 @@ impl SubGizmoKind for Rotation {
 
         if !subgizmo.active {
             let angle = arc_angle(subgizmo);
-            draw_data += shape_builder
-                .arc(radius, FRAC_PI_2 - angle, FRAC_PI_2 + angle, stroke)
-                .into();
+            draw_data += shape_builder.arc(radius, FRAC_PI_2 - angle, FRAC_PI_2 + angle, stroke);
         } else {
             let mut start_angle = subgizmo.state.start_axis_angle + FRAC_PI_2;
             let mut end_angle = start_angle + subgizmo.state.current_delta;
@@ -162,54 +225,47 @@ impl SubGizmoKind for Rotation {
                 std::mem::swap(&mut start_angle_2, &mut end_angle_2);
             }
 
-            draw_data += shape_builder
-                .polyline(
-                    &[
-                        DVec3::new(start_angle.cos() * radius, 0.0, start_angle.sin() * radius),
-                        DVec3::new(0.0, 0.0, 0.0),
-                        DVec3::new(end_angle.cos() * radius, 0.0, end_angle.sin() * radius),
-                    ],
-                    stroke,
-                )
-                .into();
+            draw_data += shape_builder.polyline(
+                &[
+                    DVec3::new(start_angle.cos() * radius, 0.0, start_angle.sin() * radius),
+                    DVec3::new(0.0, 0.0, 0.0),
+                    DVec3::new(end_angle.cos() * radius, 0.0, end_angle.sin() * radius),
+                ],
+                stroke,
+            );
 
             if full_circles > 0 {
-                draw_data += shape_builder
-                    .sector(
-                        radius,
-                        start_angle_2,
-                        end_angle_2,
-                        color.linear_multiply((0.25 * full_circles as f32).min(1.0)),
-                        (0.0, Color32::TRANSPARENT),
-                    )
-                    .into();
-            }
-
-            draw_data += shape_builder
-                .sector(
+                draw_data += shape_builder.sector(
                     radius,
-                    start_angle,
-                    end_angle,
-                    color.linear_multiply((0.25 * (full_circles + 1) as f32).min(1.0)),
+                    start_angle_2,
+                    end_angle_2,
+                    color.linear_multiply((0.25 * full_circles as f32).min(1.0)),
                     (0.0, Color32::TRANSPARENT),
-                )
-                .into();
+                );
+            }
+
+            draw_data += shape_builder.sector(
+                radius,
+                start_angle,
+                end_angle,
+                color.linear_multiply((0.25 * (full_circles + 1) as f32).min(1.0)),
+                (0.0, Color32::TRANSPARENT),
+            );
 
-            draw_data += shape_builder.circle(radius, stroke).into();
+            draw_data += shape_builder.circle(radius, stroke);
 
             // Draw snapping ticks
-            if config.snapping {
+            let snap_angle = snap_angle(subgizmo);
+            if config.snapping() && valid_snap_interval(snap_angle) {
                 let stroke_width = stroke.0 / 2.0;
-                for i in 0..((TAU / config.snap_angle as f64) as usize + 1) {
-                    let angle = i as f64 * config.snap_angle as f64 + end_angle;
+                for i in 0..((TAU / snap_angle) as usize + 1) {
+                    let angle = i as f64 * snap_angle + end_angle;
                     let pos = DVec3::new(angle.cos(), 0.0, angle.sin());
-                    draw_data += shape_builder
-                        .line_segment(
-                            pos * radius * 1.1,
-                            pos * radius * 1.2,
-                            (stroke_width, stroke.1),
-                        )
-                        .into();
+                    draw_data += shape_builder.line_segment(
+                        pos * radius * 1.1,
+                        pos * radius * 1.2,
+                        (stroke_width, stroke.1),
+                    );
                 }
             }
         }
@@ -220,8 +276,12 @@ impl SubGizmoKind for Rotation {
 
 /// Calculates angle of the rotation axis arc.
 /// The arc is a semicircle, which turns into a full circle when viewed
-/// directly from the front.
+/// directly from the front, or when ring culling is disabled.
 fn arc_angle(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
+    if !subgizmo.config.visuals.cull_rotation_rings {
+        return PI;
+    }
+
     let dot = gizmo_normal(&subgizmo.config, subgizmo.direction)
         .dot(subgizmo.config.view_forward())
         .abs();
@@ -285,24 +345,53 @@ fn rotation_angle(subgizmo: &SubGizmoConfig<Rotation>, cursor_pos: Pos2) -> Opti
         return None;
     }
 
-    let mut angle = f64::atan2(delta.y, delta.x);
+    Some(f64::atan2(delta.y, delta.x) * subgizmo.state.angle_sign)
+}
+
+/// Sign of the screen space rotation angle, based on which side
+/// of the ring plane the camera is on.
+fn view_angle_sign(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
     if subgizmo
         .config
         .view_forward()
         .dot(gizmo_normal(&subgizmo.config, subgizmo.direction))
         < 0.0
     {
-        angle *= -1.0;
+        -1.0
+    } else {
+        1.0
     }
+}
 
-    Some(angle)
+/// Snapping angle of the ring, in radians
+fn snap_angle(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
+    let snap_angles = subgizmo.config.snap_angles;
+    let snap_angle = match (snap_angles, subgizmo.direction) {
+        (Some([x, _, _]), GizmoDirection::X) => x,
+        (Some([_, y, _]), GizmoDirection::Y) => y,
+        (Some([_, _, z]), GizmoDirection::Z) => z,
+        _ => subgizmo.config.snap_angle,
+    };
+    snap_angle as f64
+}
+
+/// Calculates the angle of the twist component of the rotation around given axis
+fn twist_angle(rotation: DQuat, axis: DVec3) -> f64 {
+    let projection = DVec3::new(rotation.x, rotation.y, rotation.z).dot(axis);
+    let angle = 2.0 * f64::atan2(projection, rotation.w);
+
+    // Keep the angle in range -180°..180°
+    (angle + PI).rem_euclid(TAU) - PI
 }
 
 fn tangent(subgizmo: &SubGizmoConfig<Rotation>) -> DVec3 {
-    let mut tangent = match subgizmo.direction {
-        GizmoDirection::X | GizmoDirection::Y => DVec3::Z,
-        GizmoDirection::Z => -DVec3::Y,
-        GizmoDirection::View => -subgizmo.config.view_right(),
+    // The tangents are given for a Y-up world, and rotated to the world up axis
+    let world_up = subgizmo.config.world_up_rotation();
+    let mut tangent = match y_up_direction(&subgizmo.config, subgizmo.direction).0 {
+        GizmoDirection::X | GizmoDirection::Y => (world_up * DVec3::Z).round(),
+        GizmoDirection::Z => (world_up * -DVec3::Y).round(),
+        // Rotation rings are never built for custom axes
+        GizmoDirection::View | GizmoDirection::Custom => -subgizmo.config.view_right(),
     };
 
     if subgizmo.config.local_space() && subgizmo.direction != GizmoDirection::View {
@@ -316,6 +405,401 @@ fn arc_radius(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
     if subgizmo.direction == GizmoDirection::View {
         outer_circle_radius(&subgizmo.config)
     } else {
-        (subgizmo.config.scale_factor * subgizmo.config.visuals.gizmo_size) as f64
+        (subgizmo.config.scale_factor * subgizmo.config.gizmo_size) as f64
+    }
+}
+
+/// Rotation mode matching the given direction.
+fn rotation_mode(direction: GizmoDirection) -> GizmoMode {
+    match direction {
+        GizmoDirection::X => GizmoMode::RotateX,
+        GizmoDirection::Y => GizmoMode::RotateY,
+        GizmoDirection::Z => GizmoMode::RotateZ,
+        GizmoDirection::View | GizmoDirection::Custom => GizmoMode::RotateView,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use enumset::EnumSet;
+
+    use super::*;
+    use crate::math::Transform;
+    use crate::test_util::{self, CENTER};
+    use crate::{Gizmo, GizmoConfig, GizmoInteraction, GizmoVisuals, Handedness};
+
+    #[test]
+    fn rotated_sector_spans_the_rotation() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let mut targets = [Transform::default()];
+
+        let path = test_util::ring_path(&[0.0, 45.0, 90.0]);
+        for (index, &pos) in path.iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            test_util::update(&mut gizmo, interaction, &mut targets);
+        }
+
+        let draw_data = gizmo.draw();
+        let max_alpha = draw_data
+            .colors
+            .iter()
+            .map(|color| color[3])
+            .fold(0.0, f32::max);
+
+        // The sector is filled with a translucent color, and its rim is on the ring
+        let radius = GizmoVisuals::default().gizmo_size;
+        let angles: Vec<f32> = draw_data
+            .vertices
+            .iter()
+            .zip(&draw_data.colors)
+            .filter(|(_, color)| color[3] > 0.01 && color[3] < max_alpha * 0.5)
+            .map(|(vertex, _)| Pos2::new(vertex[0], vertex[1]) - CENTER)
+            .filter(|offset| offset.length() > radius * 0.5)
+            .map(|offset| offset.y.atan2(offset.x).to_degrees())
+            .collect();
+
+        let min = angles.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = angles.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert!(angles.len() > 4, "{angles:?}");
+        assert!((max - min - 90.0).abs() < 3.0, "{min}..{max}");
+    }
+
+    #[test]
+    fn total_accumulates_past_full_turns() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let angles: Vec<f32> = (0..=15).map(|step| step as f32 * 30.0).collect();
+        let path = test_util::ring_path(&angles);
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+
+        let Some(&GizmoResult::Rotation {
+            total, revolutions, ..
+        }) = results.last()
+        else {
+            panic!("expected a rotation result, got {results:?}");
+        };
+
+        // 450°, instead of the 90° it wraps to
+        assert!((total.abs() - 450f64.to_radians()).abs() < 0.05, "{total}");
+        assert_eq!(revolutions.abs(), 1);
+    }
+
+    #[test]
+    fn state_snapshot_has_the_sign_of_the_result() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let path = test_util::ring_path(&[0.0, 20.0, 40.0]);
+        let mut targets = [Transform::default()];
+
+        let mut result = None;
+        for (index, &pos) in path.iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: index == 0,
+                dragging: true,
+                ..test_util::hover(pos)
+            };
+            result = test_util::update(&mut gizmo, interaction, &mut targets);
+        }
+
+        let Some(GizmoResult::Rotation { total, .. }) = result else {
+            panic!("expected a rotation result, got {result:?}");
+        };
+        let Some(ActiveStateSnapshot::Rotation {
+            drag_angle,
+            current_delta,
+        }) = gizmo.active_state()
+        else {
+            panic!("expected a rotation snapshot");
+        };
+
+        assert!(total.abs() > 0.5, "{total}");
+        assert_eq!(current_delta, total);
+        assert!((drag_angle - total).abs() < 1e-9, "{drag_angle} {total}");
+    }
+
+    #[test]
+    fn view_ring_rotates_about_the_view_axis() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateView)));
+        let mut targets = [Transform::default()];
+
+        // The view ring is just outside the other rings
+        let visuals = GizmoVisuals::default();
+        let radius = visuals.gizmo_size + visuals.stroke_width + 5.0;
+        let path: Vec<_> = [0f32, 30.0, 60.0, 90.0]
+            .iter()
+            .map(|angle| CENTER + crate::math::Vec2::angled(angle.to_radians()) * radius)
+            .collect();
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let Some(&GizmoResult::Rotation {
+            world_axis,
+            is_view_axis,
+            mode,
+            ..
+        }) = results.last()
+        else {
+            panic!("expected a rotation result, got {results:?}");
+        };
+
+        assert!(is_view_axis);
+        assert_eq!(mode, GizmoMode::RotateView);
+        // The camera looks down -Z
+        assert!(DVec3::from(world_axis).dot(DVec3::Z).abs() > 1.0 - 1e-6);
+
+        let (axis, angle) = DQuat::from(targets[0].rotation).to_axis_angle();
+        assert!(axis.dot(DVec3::Z).abs() > 1.0 - 1e-6, "{axis}");
+        assert!((angle - FRAC_PI_2).abs() < 0.05, "{angle}");
+    }
+
+    #[test]
+    fn absolute_snapping_lands_on_world_angles() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            snapping: true,
+            snap_absolute: true,
+            snap_angle: 15f32.to_radians(),
+            ..test_util::config(EnumSet::only(GizmoMode::RotateZ))
+        });
+        let mut targets = [Transform {
+            rotation: DQuat::from_rotation_z(13f64.to_radians()).into(),
+            ..Default::default()
+        }];
+
+        let path = test_util::ring_path(&[0.0, 2.0, 4.0]);
+        test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let (_, _, angle) = DQuat::from(targets[0].rotation).to_euler(glam::EulerRot::XYZ);
+        assert!(
+            (angle.to_degrees() - 15.0).abs() < 1e-6,
+            "{}",
+            angle.to_degrees()
+        );
+    }
+
+    #[test]
+    fn result_has_right_handed_axis_and_angle() {
+        // Looking down from +Y, with +X right and +Z down in the viewport
+        let view_matrix = DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, -DVec3::Z);
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            view_matrix: view_matrix.into(),
+            ..test_util::config(EnumSet::only(GizmoMode::RotateY))
+        });
+        let mut targets = [Transform::default()];
+
+        // Clockwise in the viewport, so clockwise when looking down the Y axis
+        let path = test_util::ring_path(&[0.0, 15.0, 30.0]);
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let Some(&GizmoResult::Rotation {
+            axis,
+            world_axis,
+            total,
+            ..
+        }) = results.last()
+        else {
+            panic!("expected a rotation result, got {results:?}");
+        };
+
+        assert!(DVec3::from(axis).abs_diff_eq(DVec3::Y, 1e-9), "{axis:?}");
+        assert!(DVec3::from(world_axis).abs_diff_eq(DVec3::Y, 1e-9));
+        assert!((total + 30f64.to_radians()).abs() < 0.01, "{total}");
+
+        let expected = DQuat::from_rotation_y(total);
+        assert!(DQuat::from(targets[0].rotation).abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn angle_scales_with_sensitivity() {
+        let total = |rotation_sensitivity| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                rotation_sensitivity,
+                ..test_util::config(EnumSet::only(GizmoMode::RotateZ))
+            });
+            let path = test_util::ring_path(&[0.0, 10.0, 20.0]);
+            let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+            match results.last() {
+                Some(&GizmoResult::Rotation { total, .. }) => total,
+                _ => panic!("expected a rotation result, got {results:?}"),
+            }
+        };
+
+        let base = total(1.0);
+        assert!((base.abs() - 20f64.to_radians()).abs() < 0.01, "{base}");
+        assert!((total(0.5) - base * 0.5).abs() < 1e-6);
+        assert!((total(2.0) - base * 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn culling_draws_half_of_the_ring() {
+        let vertex_count = |cull_rotation_rings| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                visuals: GizmoVisuals {
+                    cull_rotation_rings,
+                    ..Default::default()
+                },
+                ..test_util::config(EnumSet::only(GizmoMode::RotateX))
+            });
+            test_util::update(
+                &mut gizmo,
+                test_util::hover(Pos2::ZERO),
+                &mut [Transform::default()],
+            );
+            gizmo.draw().vertices.len() as f32
+        };
+
+        // The X ring is seen from the side, with the camera on the Z axis
+        let ratio = vertex_count(true) / vertex_count(false);
+        assert!((0.4..0.6).contains(&ratio), "{ratio}");
+    }
+
+    #[test]
+    fn rotation_sign_is_kept_when_camera_crosses_ring_plane() {
+        let prepared_config = |eye| {
+            test_util::prepared_config(GizmoConfig {
+                view_matrix: test_util::view_matrix(eye).into(),
+                ..test_util::config(EnumSet::only(GizmoMode::RotateY))
+            })
+        };
+        let above = prepared_config(DVec3::new(0.0, 3.0, 10.0));
+        let below = prepared_config(DVec3::new(0.0, -3.0, 10.0));
+
+        let mut subgizmo = RotationSubGizmo::new(
+            above,
+            RotationParams {
+                direction: GizmoDirection::Y,
+            },
+        );
+
+        let path = test_util::ring_path(&[0.0, 10.0, 20.0]);
+        Rotation::pick(&mut subgizmo, test_util::ray(path[0]));
+
+        let Some(GizmoResult::Rotation { delta: first, .. }) =
+            Rotation::update(&mut subgizmo, test_util::ray(path[1]))
+        else {
+            panic!("expected a rotation result");
+        };
+
+        subgizmo.config = below;
+        let Some(GizmoResult::Rotation { delta: second, .. }) =
+            Rotation::update(&mut subgizmo, test_util::ray(path[2]))
+        else {
+            panic!("expected a rotation result");
+        };
+
+        assert!(first.abs() > 0.1, "{first}");
+        assert!(first * second > 0.0, "{first} {second}");
+    }
+
+    #[test]
+    fn each_ring_snaps_to_its_own_angle() {
+        let snapped_total = |view_matrix: DMat4, mode| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                view_matrix: view_matrix.into(),
+                snapping: true,
+                snap_angles: Some([25f32.to_radians(), 15f32.to_radians(), 5f32.to_radians()]),
+                ..test_util::config(EnumSet::only(mode))
+            });
+            // Each ring faces the camera
+            let path = test_util::ring_path(&[0.0, 20.0, 40.0]);
+            let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+            match results.last() {
+                Some(&GizmoResult::Rotation { total, .. }) => total.abs().to_degrees(),
+                _ => panic!("expected a rotation result, got {results:?}"),
+            }
+        };
+
+        let x_total = snapped_total(test_util::view_matrix(DVec3::X * 10.0), GizmoMode::RotateX);
+        let y_view = DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, -DVec3::Z);
+        let y_total = snapped_total(y_view, GizmoMode::RotateY);
+
+        // 40° is snapped to the nearest multiple of each ring's angle
+        assert!((x_total - 50.0).abs() < 1e-4, "{x_total}");
+        assert!((y_total - 45.0).abs() < 1e-4, "{y_total}");
+    }
+
+    #[test]
+    fn revolutions_count_signed_full_turns() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::RotateZ)));
+        let revolutions = |gizmo: &mut Gizmo, end_angle: i32| {
+            let angles: Vec<f32> = (0..=end_angle / 30)
+                .map(|step| step as f32 * 30.0)
+                .collect();
+            let results = test_util::drag(
+                gizmo,
+                &test_util::ring_path(&angles),
+                &mut [Transform::default()],
+            );
+            match results.last() {
+                Some(&GizmoResult::Rotation { revolutions, .. }) => revolutions,
+                _ => panic!("expected a rotation result, got {results:?}"),
+            }
+        };
+
+        // Clockwise in the viewport is clockwise around Z, which points towards the camera
+        assert_eq!(revolutions(&mut gizmo, 900), -2);
+        // Counting starts again for each drag
+        assert_eq!(revolutions(&mut gizmo, 90), 0);
+    }
+
+    #[test]
+    fn ring_follows_the_cursor_in_both_handedness() {
+        let left_handed = GizmoConfig {
+            view_matrix: DMat4::look_at_lh(DVec3::Z * -10.0, DVec3::ZERO, DVec3::Y).into(),
+            projection_matrix: DMat4::perspective_lh(
+                45f64.to_radians(),
+                (test_util::VIEWPORT.width() / test_util::VIEWPORT.height()) as f64,
+                0.1,
+                1000.0,
+            )
+            .into(),
+            handedness: Some(Handedness::LeftHanded),
+            ..test_util::config(EnumSet::only(GizmoMode::RotateZ))
+        };
+        let right_handed = test_util::config(EnumSet::only(GizmoMode::RotateZ));
+
+        for config in [right_handed, left_handed] {
+            let mut gizmo = Gizmo::new(config);
+            let mut targets = [Transform::default()];
+            let path = test_util::ring_path(&[0.0, 15.0, 30.0]);
+            let grabbed = DVec3::from(
+                gizmo
+                    .screen_to_world_on_plane(path[0], DVec3::Z, DVec3::ZERO)
+                    .unwrap(),
+            );
+            test_util::drag(&mut gizmo, &path, &mut targets);
+
+            // The grabbed point of the ring is rotated to the cursor
+            let rotated = DQuat::from(targets[0].rotation) * grabbed;
+            let pos = gizmo.world_to_screen(rotated).unwrap();
+            assert!(
+                pos.distance(path[2]) < 2.0,
+                "{:?}: {pos:?}",
+                config.handedness
+            );
+        }
+    }
+
+    #[test]
+    fn snap_step_callback_counts_each_crossed_rotation_step() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            snapping: true,
+            snap_angle: 10f32.to_radians(),
+            ..test_util::config(EnumSet::only(GizmoMode::RotateZ))
+        });
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&calls);
+        gizmo.set_snap_step_callback(Some(crate::SnapStepCallback::new(move |mode, steps| {
+            recorded.lock().unwrap().push((mode, steps));
+        })));
+
+        // Several steps per frame, and back past the start
+        let path = test_util::ring_path(&[0.0, 34.0, 57.0, -23.0]);
+        test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+
+        let calls = calls.lock().unwrap();
+        let steps: Vec<i32> = calls.iter().map(|&(_, steps)| steps).collect();
+        assert!(steps == [3, 3, -8] || steps == [-3, -3, 8], "{steps:?}");
+        assert!(calls.iter().all(|&(mode, _)| mode == GizmoMode::RotateZ));
     }
 }
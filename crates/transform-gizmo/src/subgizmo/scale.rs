@@ -1,13 +1,17 @@
 use glam::DVec3;
 
-use crate::math::{round_to_interval, world_to_screen, Pos2};
+use crate::math::{round_to_interval, world_to_screen, Pos2, Vec2};
 
 use crate::subgizmo::common::{
-    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_local_normal, outer_circle_radius,
-    pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_tangent,
+    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_local_normal, inner_circle_radius,
+    outer_circle_radius, pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_tangent,
+    update_snap_index, valid_snap_interval,
 };
 use crate::subgizmo::{common::TransformKind, SubGizmoConfig, SubGizmoKind};
-use crate::{gizmo::Ray, GizmoDirection, GizmoDrawData, GizmoMode, GizmoResult};
+use crate::{
+    gizmo::{ActiveStateSnapshot, Ray},
+    GizmoDirection, GizmoDrawData, GizmoMode, GizmoPhase, GizmoResult,
+};
 
 pub(crate) type ScaleSubGizmo = SubGizmoConfig<Scale>;
 
@@ -16,11 +20,28 @@ pub(crate) struct ScaleParams {
     pub mode: GizmoMode,
     pub direction: GizmoDirection,
     pub transform_kind: TransformKind,
+    /// Whether the uniform scale handle is at the center of the gizmo,
+    /// instead of being the outer circle.
+    pub centered: bool,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct ScaleState {
     start_delta: f64,
+    /// Distance of the cursor from the gizmo center when the drag started,
+    /// normalized by the gizmo size. Only used by centered handles.
+    start_distance: f64,
+    /// Offset of the cursor from the gizmo center when the drag started
+    start_offset: Vec2,
+    last_scale: DVec3,
+    /// Scale factor given by the pointer, before snapping
+    unsnapped_delta: f64,
+    /// Part of the scale factor that was skipped in precision mode
+    precision_offset: f64,
+    /// Index of the current snapping step, if snapping
+    snap_index: Option<i64>,
+    /// Snapping steps crossed since they were last taken
+    snap_steps: i32,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
@@ -35,8 +56,8 @@ impl SubGizmoKind for Scale {
             (TransformKind::Plane, GizmoDirection::View) => pick_circle(
                 &subgizmo.config,
                 ray,
-                outer_circle_radius(&subgizmo.config),
-                false,
+                uniform_handle_radius(subgizmo),
+                subgizmo.centered,
             ),
             (TransformKind::Plane, _) => pick_plane(&subgizmo.config, ray, subgizmo.direction),
             (TransformKind::Axis, _) => {
@@ -44,11 +65,18 @@ impl SubGizmoKind for Scale {
             }
         };
 
-        let start_delta = distance_from_origin_2d(subgizmo, ray.screen_pos)?;
+        let start_offset = offset_from_origin_2d(subgizmo, ray.screen_pos)?;
+        let start_delta = start_offset.length() as f64;
 
         subgizmo.opacity = pick_result.visibility as _;
 
+        subgizmo.state.start_offset = start_offset;
         subgizmo.state.start_delta = start_delta;
+        subgizmo.state.start_distance = start_delta / subgizmo.config.gizmo_size as f64;
+        subgizmo.state.last_scale = DVec3::ONE;
+        subgizmo.state.unsnapped_delta = 1.0;
+        subgizmo.state.precision_offset = 0.0;
+        subgizmo.state.snap_index = Some(0);
 
         if pick_result.picked {
             Some(pick_result.t)
@@ -58,60 +86,389 @@ impl SubGizmoKind for Scale {
     }
 
     fn update(subgizmo: &mut ScaleSubGizmo, ray: Ray) -> Option<GizmoResult> {
-        let mut delta = distance_from_origin_2d(subgizmo, ray.screen_pos)?;
-        delta /= subgizmo.state.start_delta;
-
-        if subgizmo.config.snapping {
-            delta = round_to_interval(delta, subgizmo.config.snap_scale as f64);
+        let offset = offset_from_origin_2d(subgizmo, ray.screen_pos)?;
+        let mut distance = offset.length() as f64;
+        if subgizmo.config.allow_negative_scale && offset.dot(subgizmo.state.start_offset) < 0.0 {
+            // The cursor has moved past the gizmo center
+            distance = -distance;
         }
-        delta = delta.max(1e-4) - 1.0;
-
-        let direction = match (subgizmo.transform_kind, subgizmo.direction) {
-            (TransformKind::Axis, _) => gizmo_local_normal(&subgizmo.config, subgizmo.direction),
-            (TransformKind::Plane, GizmoDirection::View) => DVec3::ONE,
-            (TransformKind::Plane, _) => (plane_bitangent(subgizmo.direction)
-                + plane_tangent(subgizmo.direction))
-            .normalize(),
+
+        let mut delta = if subgizmo.centered {
+            // Grabbing the center would make the distance ratio very sensitive,
+            // so scale by the distance moved relative to the gizmo size instead.
+            1.0 + distance / subgizmo.config.gizmo_size as f64 - subgizmo.state.start_distance
+        } else {
+            distance / subgizmo.state.start_delta
         };
 
-        let scale = DVec3::ONE + (direction * delta);
+        // In precision mode, only a fraction of the pointer movement is applied.
+        delta -= subgizmo.state.precision_offset;
+        let skipped = (delta - subgizmo.state.unsnapped_delta) * subgizmo.config.precision_skip();
+        subgizmo.state.precision_offset += skipped;
+        delta -= skipped;
+        subgizmo.state.unsnapped_delta = delta;
+
+        let snap_scale = subgizmo.config.snap_scale as f64;
+        let snapping = subgizmo.config.snapping() && valid_snap_interval(snap_scale);
+        if snapping {
+            delta = round_to_interval(delta, snap_scale);
+        }
+        delta -= 1.0;
+
+        subgizmo.state.snap_steps += update_snap_index(
+            &mut subgizmo.state.snap_index,
+            snapping.then_some(delta),
+            snap_scale,
+        );
+
+        let direction = scale_direction(subgizmo);
+        let scale = clamp_scale(subgizmo, DVec3::ONE + (direction * delta));
+
+        // No factor changes a zero scale, so axes that were scaled to zero have no delta.
+        let last_scale = subgizmo.state.last_scale;
+        let scale_delta = DVec3::select(
+            last_scale.cmpeq(DVec3::ZERO),
+            DVec3::ONE,
+            scale / last_scale,
+        );
+
+        subgizmo.state.last_scale = scale;
 
         Some(GizmoResult::Scale {
+            delta: scale_delta.into(),
             total: scale.into(),
+            mode: subgizmo.mode,
+            direction: subgizmo.direction,
+            kind: subgizmo.transform_kind,
+            phase: GizmoPhase::Update,
+            interaction_id: 0,
         })
     }
 
+    fn scroll(subgizmo: &ScaleSubGizmo, factor: f64) -> Option<GizmoResult> {
+        let scale = clamp_scale(
+            subgizmo,
+            DVec3::ONE + scale_direction(subgizmo) * (factor - 1.0),
+        );
+
+        Some(GizmoResult::Scale {
+            delta: scale.into(),
+            total: scale.into(),
+            mode: subgizmo.mode,
+            direction: subgizmo.direction,
+            kind: subgizmo.transform_kind,
+            phase: GizmoPhase::End,
+            interaction_id: 0,
+        })
+    }
+
+    fn state_snapshot(subgizmo: &ScaleSubGizmo) -> ActiveStateSnapshot {
+        ActiveStateSnapshot::Scale {
+            last_scale: subgizmo.state.last_scale.into(),
+            unsnapped_delta: subgizmo.state.unsnapped_delta,
+        }
+    }
+
+    fn take_snap_steps(subgizmo: &mut ScaleSubGizmo) -> i32 {
+        std::mem::take(&mut subgizmo.state.snap_steps)
+    }
+
+    fn handle(subgizmo: &ScaleSubGizmo) -> (GizmoMode, GizmoDirection, TransformKind) {
+        (subgizmo.mode, subgizmo.direction, subgizmo.transform_kind)
+    }
+
     fn draw(subgizmo: &ScaleSubGizmo) -> GizmoDrawData {
         match (subgizmo.transform_kind, subgizmo.direction) {
             (TransformKind::Axis, _) => draw_arrow(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
                 subgizmo.mode,
             ),
             (TransformKind::Plane, GizmoDirection::View) => draw_circle(
                 &subgizmo.config,
-                gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction),
-                outer_circle_radius(&subgizmo.config),
-                false,
+                gizmo_color(&subgizmo.config, subgizmo.highlight, subgizmo.direction),
+                uniform_handle_radius(subgizmo),
+                subgizmo.centered,
             ),
             (TransformKind::Plane, _) => draw_plane(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
             ),
         }
     }
 }
 
-fn distance_from_origin_2d<T: SubGizmoKind>(
+/// Radius of the uniform scale handle
+fn uniform_handle_radius(subgizmo: &ScaleSubGizmo) -> f64 {
+    if subgizmo.centered {
+        inner_circle_radius(&subgizmo.config) * 0.5
+    } else {
+        outer_circle_radius(&subgizmo.config)
+    }
+}
+
+/// Axes scaled by the subgizmo, in gizmo local space
+fn scale_direction(subgizmo: &ScaleSubGizmo) -> DVec3 {
+    match (subgizmo.transform_kind, subgizmo.direction) {
+        _ if subgizmo.config.uniform_scale => DVec3::ONE,
+        // The handle axis is flipped for mirrored targets, but the scaled axis is the same
+        (TransformKind::Axis, _) => gizmo_local_normal(&subgizmo.config, subgizmo.direction).abs(),
+        (TransformKind::Plane, GizmoDirection::View) => DVec3::ONE,
+        // Both axes of the plane are scaled by the same factor
+        (TransformKind::Plane, _) => {
+            plane_bitangent(subgizmo.direction) + plane_tangent(subgizmo.direction)
+        }
+    }
+}
+
+/// Applies [`crate::GizmoConfig::min_scale`], unless negative scale is allowed
+fn clamp_scale(subgizmo: &ScaleSubGizmo, scale: DVec3) -> DVec3 {
+    if subgizmo.config.allow_negative_scale {
+        scale
+    } else {
+        scale.max(DVec3::splat(subgizmo.config.min_scale as f64))
+    }
+}
+
+fn offset_from_origin_2d<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     cursor_pos: Pos2,
-) -> Option<f64> {
+) -> Option<Vec2> {
     let viewport = subgizmo.config.viewport;
     let gizmo_pos = world_to_screen(viewport, subgizmo.config.mvp, DVec3::new(0.0, 0.0, 0.0))?;
 
-    Some(cursor_pos.distance(gizmo_pos) as f64)
+    Some(cursor_pos - gizmo_pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use enumset::EnumSet;
+
+    use super::*;
+    use crate::math::Transform;
+    use crate::subgizmo::common::plane_global_origin;
+    use crate::test_util::{self, CENTER, X_ARROW};
+    use crate::{Gizmo, GizmoConfig, GizmoInteraction, GizmoVisuals};
+
+    #[test]
+    fn uniform_handle_scales_all_axes_equally() {
+        let mut gizmo = Gizmo::new(test_util::config(GizmoMode::all_scale()));
+        let mut targets = [Transform::default()];
+
+        // Dragging the center handle outward by half of the gizmo size
+        let grab = CENTER + Vec2::splat(3.0);
+        let outward = Vec2::splat(1.0).normalized() * GizmoVisuals::default().gizmo_size * 0.5;
+        let results = test_util::drag(&mut gizmo, &[grab, grab + outward], &mut targets);
+
+        let Some(&GizmoResult::Scale { total, mode, .. }) = results.last() else {
+            panic!("expected a scale result, got {results:?}");
+        };
+
+        assert_eq!(mode, GizmoMode::ScaleUniform);
+        let total = DVec3::from(total);
+        assert!(total.abs_diff_eq(DVec3::splat(1.5), 1e-3), "{total}");
+        assert!(DVec3::from(targets[0].scale).abs_diff_eq(total, 1e-9));
+    }
+
+    #[test]
+    fn scale_past_zero_is_clamped_unless_negative_scale_is_allowed() {
+        let total_x = |allow_negative_scale, end| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                min_scale: 0.05,
+                allow_negative_scale,
+                ..test_util::config(EnumSet::only(GizmoMode::ScaleX))
+            });
+            let path = [X_ARROW, CENTER + Vec2::new(10.0, 0.0), end];
+            let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+            match results.last() {
+                Some(&GizmoResult::Scale { total, .. }) => total.x,
+                _ => panic!("expected a scale result, got {results:?}"),
+            }
+        };
+
+        // Zero scale at the center
+        assert!((total_x(false, CENTER) - 0.05).abs() < 1e-6);
+        // The cursor is 30 pixels past the center, after grabbing 50 pixels from it
+        let negative = total_x(true, CENTER - Vec2::new(30.0, 0.0));
+        assert!((negative + 0.6).abs() < 1e-3, "{negative}");
+    }
+
+    #[test]
+    fn scale_delta_stays_finite_through_zero_scale() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            allow_negative_scale: true,
+            ..test_util::config(EnumSet::only(GizmoMode::ScaleX))
+        });
+        let path = [X_ARROW, CENTER, CENTER - Vec2::new(30.0, 0.0)];
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+
+        let scales: Vec<_> = results
+            .iter()
+            .map(|result| match *result {
+                GizmoResult::Scale { delta, total, .. } => (DVec3::from(delta), total.x),
+                _ => panic!("expected a scale result, got {result:?}"),
+            })
+            .collect();
+        assert!(
+            scales.iter().all(|(delta, _)| delta.is_finite()),
+            "{scales:?}"
+        );
+        // Scaled to zero at the center, then on past it
+        assert!(scales[1].1.abs() < 1e-6, "{scales:?}");
+        assert!((scales[2].1 + 0.6).abs() < 1e-3, "{scales:?}");
+        assert_eq!(scales[2].0.x, 1.0);
+    }
+
+    #[test]
+    fn plane_handle_scales_both_plane_axes() {
+        // Looking down the Y axis, so that the XZ plane faces the camera
+        let config = GizmoConfig {
+            view_matrix: glam::DMat4::look_at_rh(DVec3::Y * 10.0, DVec3::ZERO, -DVec3::Z).into(),
+            ..test_util::config(EnumSet::only(GizmoMode::ScaleXZ))
+        };
+        let prepared_config = test_util::prepared_config(config);
+        let grab = world_to_screen(
+            prepared_config.viewport,
+            prepared_config.view_projection,
+            plane_global_origin(&prepared_config, GizmoDirection::Y),
+        )
+        .unwrap();
+
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+        let path = [
+            grab,
+            CENTER + (grab - CENTER) * 1.5,
+            CENTER + (grab - CENTER) * 2.0,
+        ];
+        test_util::drag(&mut gizmo, &path, &mut targets);
+
+        let scale = DVec3::from(targets[0].scale);
+        assert!(
+            scale.abs_diff_eq(DVec3::new(2.0, 1.0, 2.0), 1e-3),
+            "{scale}"
+        );
+    }
+
+    #[test]
+    fn scale_snaps_to_percentage_steps() {
+        let snapped_x = |ratio: f32| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                snapping: true,
+                snap_scale: 0.25,
+                ..test_util::config(EnumSet::only(GizmoMode::ScaleX))
+            });
+            let end = CENTER + (X_ARROW - CENTER) * ratio;
+            let results = test_util::drag(&mut gizmo, &[X_ARROW, end], &mut [Transform::default()]);
+            match results.last() {
+                Some(&GizmoResult::Scale { total, .. }) => total.x,
+                _ => panic!("expected a scale result, got {results:?}"),
+            }
+        };
+
+        assert!((snapped_x(1.12) - 1.0).abs() < 1e-9);
+        assert!((snapped_x(1.3) - 1.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn total_is_relative_to_the_start_scale() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::ScaleX)));
+        let mut targets = [Transform {
+            scale: DVec3::splat(2.0).into(),
+            ..Default::default()
+        }];
+
+        let end = CENTER + (X_ARROW - CENTER) * 1.5;
+        let results = test_util::drag(&mut gizmo, &[X_ARROW, end], &mut targets);
+        let Some(&GizmoResult::Scale { total, .. }) = results.last() else {
+            panic!("expected a scale result, got {results:?}");
+        };
+
+        let total = DVec3::from(total);
+        let scale = DVec3::from(targets[0].scale);
+        assert!(
+            total.abs_diff_eq(DVec3::new(1.5, 1.0, 1.0), 1e-3),
+            "{total}"
+        );
+        assert!(
+            scale.abs_diff_eq(DVec3::new(3.0, 2.0, 2.0), 2e-3),
+            "{scale}"
+        );
+    }
+
+    #[test]
+    fn uniform_scale_applies_the_axis_factor_to_all_axes() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            uniform_scale: true,
+            ..test_util::config(EnumSet::only(GizmoMode::ScaleX))
+        });
+        let mut targets = [Transform::default()];
+
+        let end = CENTER + (X_ARROW - CENTER) * 1.4;
+        let results = test_util::drag(&mut gizmo, &[X_ARROW, end], &mut targets);
+        let Some(&GizmoResult::Scale { total, mode, .. }) = results.last() else {
+            panic!("expected a scale result, got {results:?}");
+        };
+
+        assert_eq!(mode, GizmoMode::ScaleX);
+        let total = DVec3::from(total);
+        assert!(total.abs_diff_eq(DVec3::splat(1.4), 1e-3), "{total}");
+        assert!(DVec3::from(targets[0].scale).abs_diff_eq(total, 1e-9));
+    }
+
+    #[test]
+    fn wheel_scales_by_the_hovered_handle() {
+        let config = GizmoConfig {
+            wheel_scale: true,
+            ..test_util::config(GizmoMode::all_scale())
+        };
+        let scroll = |config: GizmoConfig, pos: Pos2, scroll_delta: f32| {
+            let mut gizmo = Gizmo::new(config);
+            let mut targets = [Transform {
+                scale: DVec3::splat(2.0).into(),
+                ..Default::default()
+            }];
+            let interaction = GizmoInteraction {
+                scroll_delta,
+                ..test_util::hover(pos)
+            };
+            let result = test_util::update(&mut gizmo, interaction, &mut targets);
+            assert_eq!(gizmo.active_subgizmo_id, None);
+            (result, DVec3::from(targets[0].scale))
+        };
+
+        // 50 points with the default sensitivity of 0.002 scale by 1.1
+        let (result, scale) = scroll(config, CENTER + Vec2::splat(3.0), 50.0);
+        assert!(matches!(
+            result,
+            Some(GizmoResult::Scale {
+                mode: GizmoMode::ScaleUniform,
+                phase: GizmoPhase::End,
+                ..
+            })
+        ));
+        assert!(scale.abs_diff_eq(DVec3::splat(2.2), 1e-9), "{scale}");
+
+        let (_, scale) = scroll(config, CENTER + Vec2::splat(3.0), -50.0);
+        assert!(scale.abs_diff_eq(DVec3::splat(1.8), 1e-9), "{scale}");
+
+        // Axis handles scale only their axis
+        let (_, scale) = scroll(config, X_ARROW, 50.0);
+        assert!(
+            scale.abs_diff_eq(DVec3::new(2.2, 2.0, 2.0), 1e-9),
+            "{scale}"
+        );
+
+        let disabled = GizmoConfig {
+            wheel_scale: false,
+            ..config
+        };
+        let (result, scale) = scroll(disabled, CENTER + Vec2::splat(3.0), 50.0);
+        assert!(result.is_none());
+        assert_eq!(scale, DVec3::splat(2.0));
+    }
 }
@@ -1,11 +1,20 @@
-use crate::math::{intersect_plane, ray_to_ray, round_to_interval, DVec3};
+use std::hash::{Hash, Hasher};
+
+use crate::config::PreparedGizmoConfig;
+use crate::math::{
+    intersect_plane, ray_to_ray, round_to_interval, truncate_to_interval, DQuat, DVec3,
+};
 
 use crate::subgizmo::common::{
-    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_normal, inner_circle_radius,
-    pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_global_origin, plane_tangent,
+    draw_arrow_along, draw_plane, draw_view_square, gizmo_color, gizmo_local_normal, gizmo_normal,
+    local_to_global_direction, pick_arrow_along, pick_plane, pick_view_square, plane_axes,
+    plane_global_origin, update_snap_index, valid_snap_interval, view_square_half_size,
 };
 use crate::subgizmo::{common::TransformKind, SubGizmoConfig, SubGizmoKind};
-use crate::{gizmo::Ray, GizmoDirection, GizmoDrawData, GizmoMode, GizmoOrientation, GizmoResult};
+use crate::{
+    gizmo::{ActiveStateSnapshot, Ray},
+    GizmoDirection, GizmoDrawData, GizmoMode, GizmoOrientation, GizmoPhase, GizmoResult,
+};
 
 pub(crate) type TranslationSubGizmo = SubGizmoConfig<Translation>;
 
@@ -14,14 +23,45 @@ pub(crate) struct TranslationParams {
     pub mode: GizmoMode,
     pub direction: GizmoDirection,
     pub transform_kind: TransformKind,
+    /// If set, the subgizmo translates along this axis instead of `direction`
+    pub custom_axis: Option<CustomAxis>,
+}
+
+/// Custom translation axis
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct CustomAxis {
+    /// Index of the axis in the custom translation axes of the gizmo
+    pub index: usize,
+    /// Direction of the axis, in gizmo local space
+    pub axis: DVec3,
+}
+
+impl Hash for CustomAxis {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.axis.to_array().map(f64::to_bits).hash(state);
+    }
 }
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct TranslationState {
     start_view_dir: DVec3,
     start_point: DVec3,
+    /// Offset from the gizmo origin to the point where the subgizmo was grabbed
+    grab_offset: DVec3,
     last_point: DVec3,
     current_delta: DVec3,
+    /// Delta of the pointer from the start point, before snapping and constraints
+    unsnapped_delta: DVec3,
+    /// Part of the pointer movement that has not been applied yet, because it has not
+    /// added up to a full snapping step or was held back by the constraints
+    snap_residual: DVec3,
+    /// Part of the pointer movement that was skipped in precision mode
+    precision_offset: DVec3,
+    /// Index of the current snapping step along each snapping axis, if snapping
+    snap_index: [Option<i64>; 3],
+    /// Snapping steps crossed since they were last taken
+    snap_steps: i32,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
@@ -33,24 +73,30 @@ impl SubGizmoKind for Translation {
 
     fn pick(subgizmo: &mut TranslationSubGizmo, ray: Ray) -> Option<f64> {
         let pick_result = match (subgizmo.transform_kind, subgizmo.direction) {
-            (TransformKind::Plane, GizmoDirection::View) => pick_circle(
+            (TransformKind::Plane, GizmoDirection::View) => pick_view_square(
                 &subgizmo.config,
                 ray,
-                inner_circle_radius(&subgizmo.config),
-                true,
+                view_square_half_size(&subgizmo.config),
             ),
             (TransformKind::Plane, _) => pick_plane(&subgizmo.config, ray, subgizmo.direction),
             (TransformKind::Axis, _) => {
-                pick_arrow(&subgizmo.config, ray, subgizmo.direction, subgizmo.mode)
+                pick_arrow_along(&subgizmo.config, ray, local_axis(subgizmo), subgizmo.mode)
             }
         };
 
         subgizmo.opacity = pick_result.visibility as _;
 
+        let grab_point = drag_point(subgizmo, ray).unwrap_or(pick_result.subgizmo_point);
+
         subgizmo.state.start_view_dir = subgizmo.config.view_forward();
-        subgizmo.state.start_point = pick_result.subgizmo_point;
-        subgizmo.state.last_point = pick_result.subgizmo_point;
+        subgizmo.state.start_point = subgizmo.config.translation;
+        subgizmo.state.grab_offset = grab_point - subgizmo.config.translation;
+        subgizmo.state.last_point = subgizmo.config.translation;
         subgizmo.state.current_delta = DVec3::ZERO;
+        subgizmo.state.unsnapped_delta = DVec3::ZERO;
+        subgizmo.state.snap_residual = DVec3::ZERO;
+        subgizmo.state.precision_offset = DVec3::ZERO;
+        subgizmo.state.snap_index = [Some(0); 3];
 
         if pick_result.picked {
             Some(pick_result.t)
@@ -68,27 +114,65 @@ impl SubGizmoKind for Translation {
             Self::pick(subgizmo, ray);
         }
 
-        let mut new_point = if subgizmo.transform_kind == TransformKind::Axis {
-            point_on_axis(subgizmo, ray)
-        } else {
-            point_on_plane(
-                gizmo_normal(&subgizmo.config, subgizmo.direction),
-                plane_global_origin(&subgizmo.config, subgizmo.direction),
-                ray,
-            )?
+        let mut new_point = match drag_point(subgizmo, ray) {
+            Some(point) => point - subgizmo.state.grab_offset - subgizmo.state.precision_offset,
+            // The axis is nearly parallel to the pointer ray. Stay at the previous point
+            // instead of jumping arbitrarily far along the axis.
+            None if subgizmo.transform_kind == TransformKind::Axis => {
+                subgizmo.state.start_point + subgizmo.state.unsnapped_delta
+            }
+            None => return None,
         };
 
         let mut new_delta = new_point - subgizmo.state.start_point;
+        let mut pointer_movement = new_delta - subgizmo.state.unsnapped_delta;
+
+        // In precision mode, only a fraction of the pointer movement is applied.
+        let skipped = pointer_movement * subgizmo.config.precision_skip();
+        subgizmo.state.precision_offset += skipped;
+        pointer_movement -= skipped;
+        new_delta -= skipped;
+        new_point -= skipped;
+
+        subgizmo.state.unsnapped_delta = new_delta;
+
+        let snapping =
+            subgizmo.config.snapping() && valid_snap_interval(subgizmo.config.snap_distance as f64);
 
-        if subgizmo.config.snapping {
-            new_delta = if subgizmo.transform_kind == TransformKind::Axis {
-                snap_translation_vector(subgizmo, new_delta)
+        if snapping && subgizmo.config.snap_absolute {
+            new_point = snap_translation_absolute(subgizmo, new_point);
+            new_delta = new_point - subgizmo.state.start_point;
+        }
+
+        // The pointer movement is accumulated and only full snapping steps are taken,
+        // so that slow movements are not lost to rounding.
+        let pending = subgizmo.state.snap_residual + pointer_movement;
+        let relative_snapping = snapping && !subgizmo.config.snap_absolute;
+        if relative_snapping {
+            let step = if subgizmo.transform_kind == TransformKind::Axis {
+                snap_translation_vector(subgizmo, pending)
             } else {
-                snap_translation_plane(subgizmo, new_delta)
+                snap_translation_plane(subgizmo, pending)
             };
-            new_point = subgizmo.state.start_point + new_delta;
+            new_point = subgizmo.state.last_point + step;
         }
 
+        new_point = constrain_translation(&subgizmo.config, subgizmo.state.start_point, new_point);
+        new_delta = new_point - subgizmo.state.start_point;
+
+        // Movement held back by the constraints stays pending, so that a handle pushed
+        // against a bound only leaves it once the pointer has come back.
+        subgizmo.state.snap_residual = if relative_snapping {
+            pending - (new_delta - subgizmo.state.current_delta)
+        } else {
+            DVec3::ZERO
+        };
+
+        // The view plane is only snapped to the absolute grid.
+        let snapped = snapping
+            && (subgizmo.config.snap_absolute || subgizmo.direction != GizmoDirection::View);
+        count_snap_steps(subgizmo, new_delta, snapped);
+
         let mut translation_delta = new_point - subgizmo.state.last_point;
         let mut total_translation = new_point - subgizmo.state.start_point;
 
@@ -104,88 +188,589 @@ impl SubGizmoKind for Translation {
         Some(GizmoResult::Translation {
             delta: translation_delta.into(),
             total: total_translation.into(),
+            mode: subgizmo.mode,
+            direction: subgizmo.direction,
+            kind: subgizmo.transform_kind,
+            custom_axis: subgizmo.custom_axis.map(|axis| axis.index),
+            phase: GizmoPhase::Update,
+            interaction_id: 0,
+        })
+    }
+
+    fn state_snapshot(subgizmo: &TranslationSubGizmo) -> ActiveStateSnapshot {
+        ActiveStateSnapshot::Translation {
+            start_point: subgizmo.state.start_point.into(),
+            last_point: subgizmo.state.last_point.into(),
+            current_delta: subgizmo.state.current_delta.into(),
+        }
+    }
+
+    fn take_snap_steps(subgizmo: &mut TranslationSubGizmo) -> i32 {
+        std::mem::take(&mut subgizmo.state.snap_steps)
+    }
+
+    fn reset(subgizmo: &TranslationSubGizmo) -> Option<GizmoResult> {
+        if subgizmo.transform_kind != TransformKind::Axis {
+            return None;
+        }
+
+        Some(GizmoResult::TranslationReset {
+            axis: local_axis(subgizmo).into(),
+            interaction_id: 0,
         })
     }
 
+    fn handle(subgizmo: &TranslationSubGizmo) -> (GizmoMode, GizmoDirection, TransformKind) {
+        (subgizmo.mode, subgizmo.direction, subgizmo.transform_kind)
+    }
+
     fn draw(subgizmo: &TranslationSubGizmo) -> GizmoDrawData {
         match (subgizmo.transform_kind, subgizmo.direction) {
-            (TransformKind::Axis, _) => draw_arrow(
+            (TransformKind::Axis, _) => draw_arrow_along(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
+                local_axis(subgizmo),
                 subgizmo.mode,
             ),
-            (TransformKind::Plane, GizmoDirection::View) => draw_circle(
+            (TransformKind::Plane, GizmoDirection::View) => draw_view_square(
                 &subgizmo.config,
-                gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction),
-                inner_circle_radius(&subgizmo.config),
-                false,
+                gizmo_color(&subgizmo.config, subgizmo.highlight, subgizmo.direction),
+                view_square_half_size(&subgizmo.config),
             ),
             (TransformKind::Plane, _) => draw_plane(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
             ),
         }
     }
 }
 
-/// Finds the nearest point on line that points in translation subgizmo direction
-fn point_on_axis(subgizmo: &SubGizmoConfig<Translation>, ray: Ray) -> DVec3 {
+/// Translation axis of the subgizmo in gizmo local space
+fn local_axis(subgizmo: &SubGizmoConfig<Translation>) -> DVec3 {
+    subgizmo.custom_axis.map_or_else(
+        || gizmo_local_normal(&subgizmo.config, subgizmo.direction),
+        |custom_axis| custom_axis.axis,
+    )
+}
+
+/// Translation axis of the subgizmo in world space
+fn axis(subgizmo: &SubGizmoConfig<Translation>) -> DVec3 {
+    if subgizmo.custom_axis.is_some() {
+        local_to_global_direction(&subgizmo.config, local_axis(subgizmo))
+    } else {
+        gizmo_normal(&subgizmo.config, subgizmo.direction)
+    }
+}
+
+/// Finds the point the pointer ray is dragging on the subgizmo axis or plane
+fn drag_point(subgizmo: &SubGizmoConfig<Translation>, ray: Ray) -> Option<DVec3> {
+    if subgizmo.transform_kind == TransformKind::Axis {
+        point_on_axis(subgizmo, ray)
+    } else {
+        point_on_plane(
+            gizmo_normal(&subgizmo.config, subgizmo.direction),
+            plane_global_origin(&subgizmo.config, subgizmo.direction),
+            ray,
+            subgizmo.config.orthographic,
+        )
+    }
+}
+
+/// Finds the nearest point on line that points in translation subgizmo direction.
+/// Returns [`None`] if the line is nearly parallel to the ray.
+fn point_on_axis(subgizmo: &SubGizmoConfig<Translation>, ray: Ray) -> Option<DVec3> {
     let origin = subgizmo.config.translation;
-    let direction = gizmo_normal(&subgizmo.config, subgizmo.direction);
+    let direction = axis(subgizmo);
 
-    let (_ray_t, subgizmo_t) = ray_to_ray(ray.origin, ray.direction, origin, direction);
+    let (_ray_t, subgizmo_t) = ray_to_ray(ray.origin, ray.direction, origin, direction)?;
 
-    origin + direction * subgizmo_t
+    Some(origin + direction * subgizmo_t)
 }
 
-fn point_on_plane(plane_normal: DVec3, plane_origin: DVec3, ray: Ray) -> Option<DVec3> {
+/// Finds the intersection of the ray and a plane.
+/// In orthographic projection, the ray is treated as a full line of sight,
+/// since the near plane it starts from may be placed beyond the plane.
+fn point_on_plane(
+    plane_normal: DVec3,
+    plane_origin: DVec3,
+    ray: Ray,
+    orthographic: bool,
+) -> Option<DVec3> {
     let mut t = 0.0;
-    if !intersect_plane(
+    let in_front = intersect_plane(
         plane_normal,
         plane_origin,
         ray.origin,
         ray.direction,
         &mut t,
-    ) {
-        None
-    } else {
+    );
+
+    // t is only calculated if the ray is not parallel to the plane.
+    let behind = orthographic && !in_front && t < 0.0;
+
+    if in_front || behind {
         Some(ray.origin + ray.direction * t)
+    } else {
+        None
     }
 }
 
+/// Takes the full snapping steps of the signed projection of the delta onto the subgizmo axis.
+/// The remainder is left out, so that it can be taken once it adds up to a full step.
 fn snap_translation_vector(subgizmo: &SubGizmoConfig<Translation>, new_delta: DVec3) -> DVec3 {
-    let delta_length = new_delta.length();
-    if delta_length > 1e-5 {
-        new_delta / delta_length
-            * round_to_interval(delta_length, subgizmo.config.snap_distance as f64)
-    } else {
-        new_delta
+    let direction = axis(subgizmo);
+    let projected = new_delta.dot(direction);
+
+    direction * truncate_to_interval(projected, subgizmo.config.snap_distance as f64)
+}
+
+/// Finds the point on the line segment from `start` to `end` that is nearest to `point`
+fn nearest_point_on_segment(point: DVec3, start: DVec3, end: DVec3) -> DVec3 {
+    let direction = end - start;
+    let length_squared = direction.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+
+    let t = ((point - start).dot(direction) / length_squared).clamp(0.0, 1.0);
+    start + direction * t
+}
+
+/// Applies the locked axes, the bounds and the rail of the configuration to a translation
+/// of the gizmo from `start` to `point`. Returns the constrained point, in world space.
+pub(crate) fn constrain_translation(
+    config: &PreparedGizmoConfig,
+    start: DVec3,
+    mut point: DVec3,
+) -> DVec3 {
+    if !config.locked_axes.is_empty() {
+        point = start + remove_locked_axes(config, point - start);
+    }
+
+    if let Some((min, max)) = config.translation_bounds {
+        // Clamp each component separately, so that the gizmo
+        // slides along the bounds instead of stopping entirely.
+        point = point.max(DVec3::from(min)).min(DVec3::from(max));
+    }
+
+    if let Some((rail_start, rail_end)) = config.translation_rail {
+        point = nearest_point_on_segment(point, rail_start.into(), rail_end.into());
+    }
+
+    point
+}
+
+/// Removes the components of the delta that are along any of the locked axes
+fn remove_locked_axes(config: &PreparedGizmoConfig, delta: DVec3) -> DVec3 {
+    config
+        .locked_axes
+        .iter()
+        .filter(|axis| !matches!(axis, GizmoDirection::View | GizmoDirection::Custom))
+        .fold(delta, |delta, axis| {
+            let axis = gizmo_normal(config, axis);
+            delta - axis * delta.dot(axis)
+        })
+}
+
+/// Snaps the given position to multiples of the snap distance along the subgizmo axes.
+/// The axes are aligned to the target rotation when local orientation is used.
+fn snap_translation_absolute(subgizmo: &SubGizmoConfig<Translation>, point: DVec3) -> DVec3 {
+    let snap_distance = subgizmo.config.snap_distance as f64;
+    let (axes, axis_count) = snap_axes(subgizmo);
+
+    axes[..axis_count].iter().fold(point, |point, &axis| {
+        let coordinate = point.dot(axis);
+        point + axis * (round_to_interval(coordinate, snap_distance) - coordinate)
+    })
+}
+
+/// Axes in world space along which the subgizmo snaps to the absolute grid.
+/// Up to three axes, kept on the stack since this runs every frame of a drag.
+fn snap_axes(subgizmo: &SubGizmoConfig<Translation>) -> ([DVec3; 3], usize) {
+    match (subgizmo.transform_kind, subgizmo.direction) {
+        (TransformKind::Axis, _) => ([axis(subgizmo), DVec3::ZERO, DVec3::ZERO], 1),
+        (TransformKind::Plane, GizmoDirection::View) => {
+            let rotation = if subgizmo.config.local_space() {
+                subgizmo.config.rotation
+            } else {
+                DQuat::IDENTITY
+            };
+            (
+                [
+                    rotation * DVec3::X,
+                    rotation * DVec3::Y,
+                    rotation * DVec3::Z,
+                ],
+                3,
+            )
+        }
+        (TransformKind::Plane, _) => {
+            let (mut bitangent, mut tangent) = plane_axes(&subgizmo.config, subgizmo.direction);
+            if subgizmo.config.local_space() {
+                bitangent = subgizmo.config.rotation * bitangent;
+                tangent = subgizmo.config.rotation * tangent;
+            }
+            ([bitangent, tangent, DVec3::ZERO], 2)
+        }
+    }
+}
+
+/// Counts the snapping steps crossed along each of the snapping axes.
+/// Steps along different axes are counted separately, so that they cannot cancel out.
+fn count_snap_steps(subgizmo: &mut SubGizmoConfig<Translation>, delta: DVec3, snapped: bool) {
+    let snap_distance = subgizmo.config.snap_distance as f64;
+    let (axes, axis_count) = snap_axes(subgizmo);
+
+    let mut steps = 0;
+    let mut crossed = 0;
+    for (index, axis) in subgizmo
+        .state
+        .snap_index
+        .iter_mut()
+        .zip(axes)
+        .take(axis_count)
+    {
+        let axis_steps = update_snap_index(index, snapped.then(|| delta.dot(axis)), snap_distance);
+        steps += axis_steps;
+        crossed += axis_steps.abs();
     }
+
+    subgizmo.state.snap_steps += if steps < 0 { -crossed } else { crossed };
 }
 
 fn snap_translation_plane(subgizmo: &SubGizmoConfig<Translation>, new_delta: DVec3) -> DVec3 {
-    let mut bitangent = plane_bitangent(subgizmo.direction);
-    let mut tangent = plane_tangent(subgizmo.direction);
+    if subgizmo.direction == GizmoDirection::View {
+        // The view plane has no fixed axes to snap along
+        return new_delta;
+    }
+
+    let (mut bitangent, mut tangent) = plane_axes(&subgizmo.config, subgizmo.direction);
     if subgizmo.config.local_space() {
         bitangent = subgizmo.config.rotation * bitangent;
         tangent = subgizmo.config.rotation * tangent;
     }
-    let cb = new_delta.cross(-bitangent);
-    let ct = new_delta.cross(tangent);
-    let lb = cb.length();
-    let lt = ct.length();
-    let n = gizmo_normal(&subgizmo.config, subgizmo.direction);
-
-    if lb > 1e-5 && lt > 1e-5 {
-        bitangent * round_to_interval(lt, subgizmo.config.snap_distance as f64) * (ct / lt).dot(n)
-            + tangent
-                * round_to_interval(lb, subgizmo.config.snap_distance as f64)
-                * (cb / lb).dot(n)
-    } else {
-        new_delta
+    let snap_distance = subgizmo.config.snap_distance as f64;
+
+    bitangent * truncate_to_interval(new_delta.dot(bitangent), snap_distance)
+        + tangent * truncate_to_interval(new_delta.dot(tangent), snap_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Arc;
+
+    use enumset::EnumSet;
+
+    use super::*;
+    use crate::math::{Transform, Vec2};
+    use crate::test_util::{self, CENTER, X_ARROW};
+    use crate::{Gizmo, GizmoConfig, SnapStepCallback};
+
+    fn snapping_config(modes: EnumSet<GizmoMode>) -> GizmoConfig {
+        GizmoConfig {
+            snapping: true,
+            snap_distance: 0.5,
+            ..test_util::config(modes)
+        }
+    }
+
+    #[test]
+    fn sub_step_moves_add_up_to_snapping_steps() {
+        let mut gizmo = Gizmo::new(snapping_config(EnumSet::only(GizmoMode::TranslateX)));
+
+        let steps = Arc::new(AtomicI32::new(0));
+        let counter = Arc::clone(&steps);
+        gizmo.set_snap_step_callback(Some(SnapStepCallback::new(move |_, crossed| {
+            counter.fetch_add(crossed, Ordering::Relaxed);
+        })));
+
+        // Each move is a hundredth of a step, adding up to 1.5 steps
+        let offsets: Vec<DVec3> = (1..=150).map(|i| DVec3::X * 0.005 * i as f64).collect();
+        let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        let total = test_util::translation_total(results.last().unwrap());
+
+        assert!(total.abs_diff_eq(DVec3::X * 0.5, 1e-9), "{total}");
+        assert_eq!(steps.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn axis_snapping_is_symmetric_around_drag_start() {
+        let mut gizmo = Gizmo::new(snapping_config(EnumSet::only(GizmoMode::TranslateX)));
+        let path = test_util::offset_path(
+            &gizmo,
+            X_ARROW,
+            &[DVec3::X * 0.7, DVec3::X * -0.1, DVec3::X * -0.7],
+        );
+
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+
+        for (result, expected) in results[1..4].iter().zip([0.5, 0.0, -0.5]) {
+            let total = test_util::translation_total(result);
+            assert!(total.abs_diff_eq(DVec3::X * expected, 1e-9), "{total}");
+        }
+    }
+
+    #[test]
+    fn snapped_handle_leaves_a_bound_once_the_pointer_returns() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            translation_bounds: Some((DVec3::splat(-1.0).into(), DVec3::splat(1.0).into())),
+            ..snapping_config(EnumSet::only(GizmoMode::TranslateX))
+        });
+        let offsets = [3.0, 2.0, 0.8, 0.4].map(|offset| DVec3::X * offset);
+        let path = test_util::offset_path(&gizmo, X_ARROW, &offsets);
+
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+
+        for (result, expected) in results[1..5].iter().zip([1.0, 1.0, 1.0, 0.5]) {
+            let total = test_util::translation_total(result);
+            assert!(total.abs_diff_eq(DVec3::X * expected, 1e-9), "{total}");
+        }
+    }
+
+    #[test]
+    fn update_at_grab_point_does_not_move() {
+        let config =
+            test_util::prepared_config(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut subgizmo = TranslationSubGizmo::new(
+            config,
+            TranslationParams {
+                mode: GizmoMode::TranslateX,
+                direction: GizmoDirection::X,
+                transform_kind: TransformKind::Axis,
+                custom_axis: None,
+            },
+        );
+
+        // Grab the arrow off its center line, so the grabbed point is not the origin
+        let ray = test_util::ray(X_ARROW + Vec2::new(0.0, 3.0));
+        assert!(Translation::pick(&mut subgizmo, ray).is_some());
+
+        let Some(GizmoResult::Translation { delta, total, .. }) =
+            Translation::update(&mut subgizmo, ray)
+        else {
+            panic!("expected a translation result");
+        };
+
+        assert!(DVec3::from(delta).abs_diff_eq(DVec3::ZERO, 1e-9));
+        assert!(DVec3::from(total).abs_diff_eq(DVec3::ZERO, 1e-9));
+    }
+
+    #[test]
+    fn view_plane_moves_in_the_camera_plane() {
+        let mut gizmo = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateView)));
+        let path = [CENTER, CENTER + Vec2::new(50.0, -30.0)];
+
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        let total = test_util::translation_total(results.last().unwrap());
+
+        // The camera looks down -Z, so the view plane is the XY plane
+        assert!(total.x > 0.1 && total.y > 0.1, "{total}");
+        assert!(total.z.abs() < 1e-9, "{total}");
+    }
+
+    #[test]
+    fn nearly_parallel_ray_keeps_the_point_bounded() {
+        let config =
+            test_util::prepared_config(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let mut subgizmo = TranslationSubGizmo::new(
+            config,
+            TranslationParams {
+                mode: GizmoMode::TranslateX,
+                direction: GizmoDirection::X,
+                transform_kind: TransformKind::Axis,
+                custom_axis: None,
+            },
+        );
+
+        assert!(Translation::pick(&mut subgizmo, test_util::ray(X_ARROW)).is_some());
+
+        // A ray that is almost along the X axis, slightly above it
+        let parallel_ray = Ray {
+            screen_pos: X_ARROW,
+            origin: DVec3::new(-100.0, 1e-3, 0.0),
+            direction: DVec3::new(1.0, 1e-5, 0.0).normalize(),
+        };
+        assert_eq!(point_on_axis(&subgizmo, parallel_ray), None);
+
+        let result = Translation::update(&mut subgizmo, parallel_ray).unwrap();
+        let total = test_util::translation_total(&result);
+        assert!(total.is_finite() && total.length() < 1.0, "{total}");
+    }
+
+    /// Whether the value is a multiple of the interval
+    fn on_grid(value: f64, interval: f64) -> bool {
+        let steps = value / interval;
+        (steps - steps.round()).abs() < 1e-9
+    }
+
+    #[test]
+    fn absolute_snapping_lands_on_the_grid() {
+        for orientation in [GizmoOrientation::Global, GizmoOrientation::Local] {
+            let rotation = DQuat::from_rotation_z(0.5);
+            let mut targets = [Transform::from_scale_rotation_translation(
+                DVec3::ONE,
+                rotation,
+                DVec3::new(0.3, 0.2, 0.0),
+            )];
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                snap_absolute: true,
+                orientation,
+                ..snapping_config(EnumSet::only(GizmoMode::TranslateView))
+            });
+
+            let grab = gizmo.world_to_screen(targets[0].translation).unwrap();
+            let path = [grab, grab + Vec2::new(37.0, -23.0)];
+            test_util::drag(&mut gizmo, &path, &mut targets);
+
+            let mut translation = DVec3::from(targets[0].translation);
+            if orientation == GizmoOrientation::Local {
+                // Local absolute snapping uses the rotated axes of the target
+                translation = rotation.inverse() * translation;
+            }
+
+            assert!(translation.distance(DVec3::new(0.3, 0.2, 0.0)) > 0.1);
+            assert!(
+                on_grid(translation.x, 0.5) && on_grid(translation.y, 0.5),
+                "{orientation:?}: {translation}"
+            );
+        }
+    }
+
+    #[test]
+    fn locked_axis_is_not_translated() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            locked_axes: EnumSet::only(GizmoDirection::Y),
+            ..test_util::config(GizmoMode::all_translate())
+        });
+
+        // The free handle moves on the XY plane, since the camera looks down -Z
+        let path = [CENTER, CENTER + Vec2::new(40.0, -40.0)];
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+        let total = test_util::translation_total(results.last().unwrap());
+
+        assert!(total.x > 0.1, "{total}");
+        assert!(total.y.abs() < 1e-9 && total.z.abs() < 1e-9, "{total}");
+    }
+
+    #[test]
+    fn translation_is_clamped_to_bounds() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            translation_bounds: Some((DVec3::splat(-1.0).into(), DVec3::new(0.2, 1.0, 1.0).into())),
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateView))
+        });
+
+        let offsets = [DVec3::new(0.5, 0.25, 0.0), DVec3::new(1.0, 0.5, 0.0)];
+        let path = test_util::offset_path(&gizmo, CENTER, &offsets);
+        let mut targets = [Transform::default()];
+        let results = test_util::drag(&mut gizmo, &path, &mut targets);
+        let total = test_util::translation_total(results.last().unwrap());
+
+        // Clamped along X, while moving freely along Y
+        assert!((total.x - 0.2).abs() < 1e-9, "{total}");
+        assert!((total.y - 0.5).abs() < 1e-6, "{total}");
+        assert!((DVec3::from(targets[0].translation).x - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn custom_axis_translates_along_the_axis() {
+        let mut gizmo = Gizmo::new(GizmoConfig {
+            // Custom axes do not depend on the view direction being enabled
+            disabled_directions: EnumSet::only(GizmoDirection::View),
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateCustom))
+        });
+        gizmo.set_custom_translation_axes(&[DVec3::new(1.0, 1.0, 0.0).normalize().into()]);
+
+        // The axis points up and right in the viewport
+        let grab = CENTER + Vec2::new(1.0, -1.0).normalized() * 50.0;
+        let path = [grab, grab + Vec2::new(60.0, -20.0)];
+        let results = test_util::drag(&mut gizmo, &path, &mut [Transform::default()]);
+
+        let Some(&GizmoResult::Translation {
+            total,
+            mode,
+            direction,
+            custom_axis,
+            ..
+        }) = results.last()
+        else {
+            panic!("expected a translation result, got {results:?}");
+        };
+
+        assert_eq!(mode, GizmoMode::TranslateCustom);
+        assert_eq!(direction, GizmoDirection::Custom);
+        assert_eq!(custom_axis, Some(0));
+
+        let total = DVec3::from(total);
+        assert!(total.x > 0.1, "{total}");
+        assert!((total.x - total.y).abs() < 1e-9, "{total}");
+        assert!(total.z.abs() < 1e-9, "{total}");
+    }
+
+    #[test]
+    fn invalid_snap_distance_disables_snapping() {
+        let drag = |snapping, snap_distance| {
+            let mut gizmo = Gizmo::new(GizmoConfig {
+                snapping,
+                snap_distance,
+                ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+            });
+            let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.37]);
+            let mut targets = [Transform::default()];
+            test_util::drag(&mut gizmo, &path, &mut targets);
+            DVec3::from(targets[0].translation)
+        };
+
+        let unsnapped = drag(false, 0.5);
+        assert!((unsnapped.x - 0.37).abs() < 1e-6, "{unsnapped}");
+
+        for snap_distance in [0.0, -0.5, f32::NAN, f32::INFINITY] {
+            let translation = drag(true, snap_distance);
+            assert!(translation.is_finite(), "{snap_distance} {translation}");
+            assert!(
+                translation.abs_diff_eq(unsnapped, 1e-9),
+                "{snap_distance} {translation}"
+            );
+        }
+    }
+
+    #[test]
+    fn rail_translation_clamps_at_the_end_points() {
+        let rail_end = DVec3::new(1.0, 0.5, 0.0);
+        let config = GizmoConfig {
+            translation_rail: Some((DVec3::new(-0.5, -0.25, 0.0).into(), rail_end.into())),
+            ..test_util::config(EnumSet::only(GizmoMode::TranslateX))
+        };
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+
+        // Halfway, the object is on the rail nearest to the dragged point
+        let path = test_util::offset_path(&gizmo, X_ARROW, &[DVec3::X * 0.5, DVec3::X * 3.0]);
+        test_util::drag(&mut gizmo, &path[..2], &mut targets);
+        let translation = DVec3::from(targets[0].translation);
+        assert!(
+            translation.abs_diff_eq(DVec3::new(0.4, 0.2, 0.0), 1e-6),
+            "{translation}"
+        );
+
+        // Past the end
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+        test_util::drag(&mut gizmo, &[path[0], path[2]], &mut targets);
+        let translation = DVec3::from(targets[0].translation);
+        assert!(translation.abs_diff_eq(rail_end, 1e-9), "{translation}");
+
+        // The rail is drawn
+        let mut plain = Gizmo::new(test_util::config(EnumSet::only(GizmoMode::TranslateX)));
+        let targets = [Transform::default()];
+        plain.update(test_util::hover(CENTER), &targets);
+        gizmo.update(test_util::hover(CENTER), &targets);
+        assert!(gizmo.draw().vertices.len() > plain.draw().vertices.len());
     }
 }
@@ -0,0 +1,182 @@
+//! Helpers for driving a gizmo in unit tests, with a camera looking at the origin.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use emath::{Pos2, Rect, Vec2};
+use enumset::EnumSet;
+use glam::{DMat4, DVec3};
+
+use crate::config::PreparedGizmoConfig;
+use crate::gizmo::Ray;
+use crate::math::Transform;
+use crate::{Gizmo, GizmoConfig, GizmoInteraction, GizmoMode, GizmoResult, GizmoVisuals};
+
+/// Position of the test camera. The camera looks at the origin, with Y up.
+pub(crate) const CAMERA_POSITION: DVec3 = DVec3::new(0.0, 0.0, 10.0);
+
+/// Viewport of the test camera
+pub(crate) const VIEWPORT: Rect = Rect {
+    min: Pos2::ZERO,
+    max: Pos2::new(800.0, 600.0),
+};
+
+/// Viewport position of the world origin
+pub(crate) const CENTER: Pos2 = Pos2::new(400.0, 300.0);
+
+/// Viewport position on the X arrow of a gizmo at the origin, with the default size
+pub(crate) const X_ARROW: Pos2 = Pos2::new(CENTER.x + 50.0, CENTER.y);
+
+/// View matrix of a camera at the given position, looking at the origin with Y up
+pub(crate) fn view_matrix(eye: DVec3) -> DMat4 {
+    DMat4::look_at_rh(eye, DVec3::ZERO, DVec3::Y)
+}
+
+/// Perspective projection matching the test viewport
+pub(crate) fn projection_matrix() -> DMat4 {
+    DMat4::perspective_rh(
+        45f64.to_radians(),
+        (VIEWPORT.width() / VIEWPORT.height()) as f64,
+        0.1,
+        1000.0,
+    )
+}
+
+/// Configuration with the test camera and the given modes
+pub(crate) fn config(modes: EnumSet<GizmoMode>) -> GizmoConfig {
+    GizmoConfig {
+        view_matrix: view_matrix(CAMERA_POSITION).into(),
+        projection_matrix: projection_matrix().into(),
+        viewport: VIEWPORT,
+        modes,
+        ..Default::default()
+    }
+}
+
+/// Configuration prepared for a single target at the origin, for testing subgizmos directly
+pub(crate) fn prepared_config(config: GizmoConfig) -> PreparedGizmoConfig {
+    let mut prepared = PreparedGizmoConfig::default();
+    prepared.update_for_config(config);
+    prepared.update_for_targets(&[Transform::default()]);
+    prepared
+}
+
+/// Ray cast from the given viewport position of the test camera
+pub(crate) fn ray(pos: Pos2) -> Ray {
+    Ray::from_screen(
+        view_matrix(CAMERA_POSITION),
+        projection_matrix(),
+        VIEWPORT,
+        pos,
+    )
+}
+
+/// Interaction with the cursor at the given position, and nothing pressed
+pub(crate) fn hover(pos: Pos2) -> GizmoInteraction {
+    GizmoInteraction {
+        cursor_pos: (pos.x, pos.y),
+        ..Default::default()
+    }
+}
+
+/// Updates the gizmo and applies the updated transforms to the targets
+pub(crate) fn update(
+    gizmo: &mut Gizmo,
+    interaction: GizmoInteraction,
+    targets: &mut [Transform],
+) -> Option<GizmoResult> {
+    let (result, new_targets) = gizmo.update(interaction, targets)?;
+    targets.copy_from_slice(&new_targets);
+    Some(result)
+}
+
+/// Presses at the first position of the path, drags through the rest and releases
+/// at the last one. Returns the results of all the frames, including the release.
+pub(crate) fn drag(
+    gizmo: &mut Gizmo,
+    path: &[Pos2],
+    targets: &mut [Transform],
+) -> Vec<GizmoResult> {
+    let mut results = Vec::new();
+
+    for (index, &pos) in path.iter().enumerate() {
+        let interaction = GizmoInteraction {
+            drag_started: index == 0,
+            dragging: true,
+            ..hover(pos)
+        };
+        results.extend(update(gizmo, interaction, targets));
+    }
+
+    if let Some(&pos) = path.last() {
+        results.extend(update(gizmo, hover(pos), targets));
+    }
+
+    results
+}
+
+/// Viewport positions that start at `grab` and move the grabbed point by each of
+/// the given world space offsets. The grabbed point is on the plane through the
+/// origin that faces the test camera.
+pub(crate) fn offset_path(gizmo: &Gizmo, grab: Pos2, offsets: &[DVec3]) -> Vec<Pos2> {
+    let grab_point = DVec3::from(
+        gizmo
+            .screen_to_world_on_plane(grab, DVec3::Z, DVec3::ZERO)
+            .unwrap(),
+    );
+
+    std::iter::once(grab)
+        .chain(
+            offsets
+                .iter()
+                .map(|&offset| gizmo.world_to_screen(grab_point + offset).unwrap()),
+        )
+        .collect()
+}
+
+/// Viewport positions on the Z rotation ring of a gizmo at the origin, with the default size,
+/// at the given angles in degrees. Angles increase clockwise in the viewport.
+pub(crate) fn ring_path(angles: &[f32]) -> Vec<Pos2> {
+    let radius = GizmoVisuals::default().gizmo_size;
+    angles
+        .iter()
+        .map(|angle| CENTER + Vec2::angled(angle.to_radians()) * radius)
+        .collect()
+}
+
+/// Total translation of a translation result
+pub(crate) fn translation_total(result: &GizmoResult) -> DVec3 {
+    match *result {
+        GizmoResult::Translation { total, .. } => total.into(),
+        _ => panic!("expected a translation result, got {result:?}"),
+    }
+}
+
+/// Allocator that counts the allocations made on each thread,
+/// for testing that code paths do not allocate.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+#[allow(unsafe_code)]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // The counter may be gone while the thread is shutting down.
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of allocations made on the current thread so far
+pub(crate) fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
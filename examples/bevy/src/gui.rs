@@ -41,6 +41,7 @@ fn draw_gizmo_result(ui: &mut egui::Ui, gizmo_result: Option<GizmoResult>) {
                 delta: _,
                 total,
                 is_view_axis: _,
+                ..
             } => {
                 format!(
                     "Rotation axis: ({:.2}, {:.2}, {:.2}), Angle: {:.2} deg",
@@ -50,16 +51,22 @@ fn draw_gizmo_result(ui: &mut egui::Ui, gizmo_result: Option<GizmoResult>) {
                     total.to_degrees()
                 )
             }
-            GizmoResult::Translation { delta: _, total } => {
+            GizmoResult::Translation {
+                delta: _, total, ..
+            } => {
                 format!(
                     "Translation: ({:.2}, {:.2}, {:.2})",
                     total.x, total.y, total.z,
                 )
             }
-            GizmoResult::Scale { total } => {
+            GizmoResult::Scale {
+                delta: _, total, ..
+            } => {
                 format!("Scale: ({:.2}, {:.2}, {:.2})", total.x, total.y, total.z,)
             }
-            GizmoResult::Arcball { delta: _, total } => {
+            GizmoResult::Arcball {
+                delta: _, total, ..
+            } => {
                 let (axis, angle) = DQuat::from(total).to_axis_angle();
                 format!(
                     "Rotation axis: ({:.2}, {:.2}, {:.2}), Angle: {:.2} deg",
@@ -69,6 +76,12 @@ fn draw_gizmo_result(ui: &mut egui::Ui, gizmo_result: Option<GizmoResult>) {
                     angle.to_degrees()
                 )
             }
+            GizmoResult::TranslationReset { axis, .. } => {
+                format!(
+                    "Translation reset along ({:.2}, {:.2}, {:.2})",
+                    axis.x, axis.y, axis.z,
+                )
+            }
         };
 
         egui::Frame::none()
@@ -172,6 +185,7 @@ fn draw_options(ui: &mut egui::Ui, gizmo_options: &mut GizmoOptions) {
                     for pivot_point in [
                         TransformPivotPoint::MedianPoint,
                         TransformPivotPoint::IndividualOrigins,
+                        TransformPivotPoint::ActiveTarget,
                     ] {
                         ui.selectable_value(
                             &mut gizmo_options.pivot_point,
@@ -210,6 +224,10 @@ fn draw_options(ui: &mut egui::Ui, gizmo_options: &mut GizmoOptions) {
             egui::Slider::new(&mut gizmo_options.visuals.highlight_alpha, 0.0..=1.0).ui(ui);
             ui.end_row();
 
+            ui.label("Plane alpha");
+            egui::Slider::new(&mut gizmo_options.visuals.plane_alpha, 0.0..=1.0).ui(ui);
+            ui.end_row();
+
             ui.label("X axis color");
             draw_color_picker(ui, &mut gizmo_options.visuals.x_color);
             ui.end_row();
@@ -225,6 +243,10 @@ fn draw_options(ui: &mut egui::Ui, gizmo_options: &mut GizmoOptions) {
             ui.label("View axis color");
             draw_color_picker(ui, &mut gizmo_options.visuals.s_color);
             ui.end_row();
+
+            ui.label("Rotation readout");
+            egui::Checkbox::without_text(&mut gizmo_options.visuals.show_rotation_readout).ui(ui);
+            ui.end_row();
         });
 
     ui.separator();
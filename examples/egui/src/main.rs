@@ -41,23 +41,34 @@ impl ExampleApp {
         // Fixed camera position
         let view_matrix = DMat4::look_at_lh(DVec3::splat(5.0), DVec3::ZERO, DVec3::Y);
 
-        // Ctrl toggles snapping
-        let snapping = ui.input(|input| input.modifiers.ctrl);
-
         self.gizmo.update_config(GizmoConfig {
             view_matrix: view_matrix.into(),
             projection_matrix: projection_matrix.into(),
             viewport,
             modes: self.gizmo_modes,
             orientation: self.gizmo_orientation,
-            snapping,
             ..Default::default()
         });
 
         let mut transform =
             Transform::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
 
-        if let Some((result, new_transforms)) = self.gizmo.interact(ui, &[transform]) {
+        // Ctrl toggles snapping and shift toggles precision mode.
+        // G, R and S switch between translation, rotation and scale.
+        let key_bindings = GizmoKeyBindings {
+            snap: Some(egui::Modifiers::CTRL),
+            precision: Some(egui::Modifiers::SHIFT),
+            mode_hotkeys: Some([egui::Key::G, egui::Key::R, egui::Key::S]),
+        };
+
+        let gizmo_result = self
+            .gizmo
+            .interact_with_key_bindings(ui, &[transform], key_bindings);
+
+        // Keep the options in sync with modes switched by the hotkeys
+        self.gizmo_modes = self.gizmo.config().modes;
+
+        if let Some((result, new_transforms)) = gizmo_result {
             for (new_transform, transform) in
                 new_transforms.iter().zip(std::iter::once(&mut transform))
             {
@@ -74,6 +85,7 @@ impl ExampleApp {
                     delta: _,
                     total,
                     is_view_axis: _,
+                    ..
                 } => {
                     format!(
                         "Rotation axis: ({:.2}, {:.2}, {:.2}), Angle: {:.2} deg",
@@ -83,16 +95,22 @@ impl ExampleApp {
                         total.to_degrees()
                     )
                 }
-                GizmoResult::Translation { delta: _, total } => {
+                GizmoResult::Translation {
+                    delta: _, total, ..
+                } => {
                     format!(
                         "Translation: ({:.2}, {:.2}, {:.2})",
                         total.x, total.y, total.z,
                     )
                 }
-                GizmoResult::Scale { total } => {
+                GizmoResult::Scale {
+                    delta: _, total, ..
+                } => {
                     format!("Scale: ({:.2}, {:.2}, {:.2})", total.x, total.y, total.z,)
                 }
-                GizmoResult::Arcball { delta: _, total } => {
+                GizmoResult::Arcball {
+                    delta: _, total, ..
+                } => {
                     let (axis, angle) = DQuat::from(total).to_axis_angle();
                     format!(
                         "Rotation axis: ({:.2}, {:.2}, {:.2}), Angle: {:.2} deg",
@@ -102,6 +120,12 @@ impl ExampleApp {
                         angle.to_degrees()
                     )
                 }
+                GizmoResult::TranslationReset { axis, .. } => {
+                    format!(
+                        "Translation reset along ({:.2}, {:.2}, {:.2})",
+                        axis.x, axis.y, axis.z,
+                    )
+                }
             };
 
             ui.label(text);